@@ -0,0 +1,76 @@
+/* C ABI of machine-info, built with the `ffi` feature. See src/ffi.rs */
+
+#ifndef MACHINE_INFO_H
+#define MACHINE_INFO_H
+
+/* Generated by cbindgen, do not edit it by hand */
+
+/* Opaque machine handle */
+typedef struct machine_t machine_t;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a machine with the default settings. Release it with `machine_free`
+machine_t *machine_new(void);
+
+// Releases a machine created by `machine_new`
+//
+// # Safety
+// `machine` must be NULL or a pointer returned by `machine_new` not released yet
+void machine_free(machine_t *machine);
+
+// Releases a string returned by this library
+//
+// # Safety
+// `value` must be NULL or a string returned by this library not released yet
+void machine_string_free(char *value);
+
+// `Machine::system_info` as JSON
+//
+// # Safety
+// `machine` must be a valid pointer returned by `machine_new`
+char *machine_system_info_json(const machine_t *machine);
+
+// `Machine::system_status` as JSON
+//
+// # Safety
+// `machine` must be a valid pointer returned by `machine_new`
+char *machine_system_status_json(const machine_t *machine);
+
+// `Machine::graphics_status` as JSON
+//
+// # Safety
+// `machine` must be a valid pointer returned by `machine_new`
+char *machine_graphics_status_json(const machine_t *machine);
+
+// `Machine::processes_status` as JSON
+//
+// # Safety
+// `machine` must be a valid pointer returned by `machine_new`
+char *machine_processes_status_json(const machine_t *machine);
+
+// `Machine::snapshot` as JSON
+//
+// # Safety
+// `machine` must be a valid pointer returned by `machine_new`
+char *machine_snapshot_json(const machine_t *machine);
+
+// Starts tracking a process, see `Machine::track_process`
+//
+// # Safety
+// `machine` must be a valid pointer returned by `machine_new`
+int machine_track_process(const machine_t *machine, int pid);
+
+// Stops tracking a process
+//
+// # Safety
+// `machine` must be a valid pointer returned by `machine_new`
+void machine_untrack_process(const machine_t *machine, int pid);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MACHINE_INFO_H */
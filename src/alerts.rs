@@ -0,0 +1,565 @@
+//! Threshold alerting and alert delivery. Sinks receive the alerts and send them somewhere else
+use anyhow::{Result, Context};
+use log::warn;
+use sysinfo::Disks;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::net::{UdpSocket, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+#[cfg(feature = "serde")]
+use crate::http::HttpEndpoint;
+use crate::Machine;
+use crate::model::{Alert, AlertState, GraphicsUsage};
+use crate::host;
+
+const SYSLOG_SOCKET: &str = "/dev/log";
+// Daemon facility
+const SYSLOG_FACILITY: u8 = 3;
+const SYSLOG_WARNING: u8 = 4;
+const SYSLOG_NOTICE: u8 = 5;
+
+/// Destination of the alerts
+pub trait AlertSink: Send {
+    /// Delivers the alert
+    fn send(&mut self, alert: &Alert) -> Result<()>;
+}
+
+/// Sends every alert as a JSON document in a HTTP POST request. Only plain `http://` urls are supported. It requires
+/// the `serde` feature
+/// Example
+/// ```no_run
+/// use machine_info::{AlertSink, WebhookSink, Alert, AlertState};
+/// let mut sink = WebhookSink::new("http://localhost:8080/alerts").unwrap();
+/// sink.send(&Alert {
+///     rule: "cpu".to_string(),
+///     state: AlertState::Triggered,
+///     value: 95.0,
+///     message: "CPU over 90%".to_string(),
+///     timestamp: 0
+/// }).unwrap();
+/// ```
+#[cfg(feature = "serde")]
+pub struct WebhookSink {
+    endpoint: HttpEndpoint,
+}
+
+#[cfg(feature = "serde")]
+impl WebhookSink {
+    /// Creates a webhook sink. It fails if the url is not a valid http url
+    pub fn new(url: &str) -> Result<WebhookSink> {
+        Ok(WebhookSink {
+            endpoint: HttpEndpoint::new(url).context("Invalid webhook url")?,
+        })
+    }
+
+    /// Changes the connection and write timeout. By default 5 seconds
+    pub fn with_timeout(mut self, timeout: Duration) -> WebhookSink {
+        self.endpoint.timeout = timeout;
+        self
+    }
+}
+
+#[cfg(feature = "serde")]
+impl AlertSink for WebhookSink {
+    fn send(&mut self, alert: &Alert) -> Result<()> {
+        self.endpoint.post_json(&serde_json::to_string(alert)?)
+    }
+}
+
+enum SyslogTarget {
+    #[cfg(unix)]
+    Local(UnixDatagram),
+    Remote(UdpSocket),
+}
+
+/// Sends every alert as a syslog (RFC 3164) message, either to the local daemon or to a remote server by UDP
+/// Example
+/// ```no_run
+/// use machine_info::SyslogSink;
+/// let sink = SyslogSink::local("my-agent").unwrap();
+/// ```
+pub struct SyslogSink {
+    target: SyslogTarget,
+    tag: String,
+    hostname: String,
+}
+
+impl SyslogSink {
+    /// Connects to the local syslog daemon through /dev/log
+    #[cfg(unix)]
+    pub fn local(tag: &str) -> Result<SyslogSink> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(SYSLOG_SOCKET).context("Cannot connect to the syslog socket")?;
+        Ok(SyslogSink::with_target(SyslogTarget::Local(socket), tag))
+    }
+
+    /// Sends the messages by UDP to a remote syslog server like `logs.example.com:514`
+    pub fn remote(address: impl ToSocketAddrs, tag: &str) -> Result<SyslogSink> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(address)?;
+        Ok(SyslogSink::with_target(SyslogTarget::Remote(socket), tag))
+    }
+
+    fn with_target(target: SyslogTarget, tag: &str) -> SyslogSink {
+        SyslogSink {
+            target,
+            tag: tag.to_string(),
+            hostname: sysinfo::System::host_name().unwrap_or_else(|| "localhost".to_string()),
+        }
+    }
+}
+
+impl AlertSink for SyslogSink {
+    fn send(&mut self, alert: &Alert) -> Result<()> {
+        let severity = match alert.state {
+            AlertState::Triggered => SYSLOG_WARNING,
+            AlertState::Cleared => SYSLOG_NOTICE,
+        };
+        let priority = SYSLOG_FACILITY * 8 + severity;
+        let content = format!("{}[{}]: [{}] {:?} {}", self.tag, std::process::id(), alert.rule, alert.state, alert.message);
+        match &self.target {
+            // The local daemon adds the timestamp and the hostname
+            #[cfg(unix)]
+            SyslogTarget::Local(socket) => socket.send(format!("<{}>{}", priority, content).as_bytes())?,
+            // Without a valid timestamp the receiver does not take the hostname as such
+            SyslogTarget::Remote(socket) => socket.send(format!("<{}>{} {} {}",
+                priority, syslog_timestamp(host::now()), self.hostname, content).as_bytes())?,
+        };
+        Ok(())
+    }
+}
+
+/// RFC 3164 timestamp like `Oct  6 14:03:09`, in local time
+fn syslog_timestamp(time: SystemTime) -> String {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let seconds = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+    let (month, day, hour, minute, second) = local_time(seconds);
+    format!("{} {:>2} {:02}:{:02}:{:02}", MONTHS[month as usize % 12], day, hour, minute, second)
+}
+
+/// Month (from 0), day, hour, minute and second in the local time zone
+#[cfg(unix)]
+fn local_time(seconds: i64) -> (u32, u32, u32, u32, u32) {
+    let time = seconds as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return utc_time(seconds);
+    }
+    (tm.tm_mon as u32, tm.tm_mday as u32, tm.tm_hour as u32, tm.tm_min as u32, tm.tm_sec as u32)
+}
+
+#[cfg(not(unix))]
+fn local_time(seconds: i64) -> (u32, u32, u32, u32, u32) {
+    utc_time(seconds)
+}
+
+/// Month (from 0), day, hour, minute and second in UTC, from the civil calendar algorithm of Howard Hinnant
+fn utc_time(seconds: i64) -> (u32, u32, u32, u32, u32) {
+    let days = seconds.div_euclid(86400);
+    let of_day = seconds.rem_euclid(86400) as u32;
+    // Days since 0000-03-01, so the leap day is the last one of the year
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 2 } else { month - 10 } as u32;
+    (month, day, of_day / 3600, of_day / 60 % 60, of_day % 60)
+}
+
+/// Sends the alerts to a channel, to receive them in another thread
+impl AlertSink for mpsc::Sender<Alert> {
+    fn send(&mut self, alert: &Alert) -> Result<()> {
+        mpsc::Sender::send(self, alert.clone()).map_err(|_| anyhow::anyhow!("The alerts receiver was dropped"))
+    }
+}
+
+/// Calls a function with every alert
+struct CallbackSink<F: FnMut(&Alert) + Send>(F);
+
+impl<F: FnMut(&Alert) + Send> AlertSink for CallbackSink<F> {
+    fn send(&mut self, alert: &Alert) -> Result<()> {
+        (self.0)(alert);
+        Ok(())
+    }
+}
+
+/// Value checked by an alert rule
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertMetric {
+    /// System CPU usage as percentage
+    Cpu,
+    /// Memory used as percentage of the total
+    Memory,
+    /// Temperature in degrees of the graphic card with the given id, or the hottest one if None
+    GpuTemperature(Option<String>),
+    /// Utilization as percentage of the graphic card with the given id, or the busiest one if None
+    GpuUsage(Option<String>),
+    /// Free space as percentage of the filesystem mounted at the given path
+    DiskFree(String),
+    /// Free space in bytes of the filesystem mounted at the given path
+    DiskFreeBytes(String),
+}
+
+impl AlertMetric {
+    fn is_disk(&self) -> bool {
+        matches!(self, AlertMetric::DiskFree(_) | AlertMetric::DiskFreeBytes(_))
+    }
+}
+
+/// Threshold rule. It triggers when the value stays over (or under) the threshold for the configured duration
+/// and clears when it goes back past the threshold by the hysteresis margin
+/// Example
+/// ```
+/// use machine_info::{AlertRule, AlertMetric};
+/// use std::time::Duration;
+///
+/// let cpu = AlertRule::above("cpu", AlertMetric::Cpu, 90.0)
+///     .for_duration(Duration::from_secs(30))
+///     .with_hysteresis(5.0);
+/// let disk = AlertRule::below("disk", AlertMetric::DiskFree("/".to_string()), 5.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    name: String,
+    metric: AlertMetric,
+    threshold: f64,
+    above: bool,
+    duration: Duration,
+    hysteresis: f64,
+}
+
+impl AlertRule {
+    /// Rule triggered when the value is over the threshold
+    pub fn above(name: &str, metric: AlertMetric, threshold: f64) -> AlertRule {
+        AlertRule { name: name.to_string(), metric, threshold, above: true, duration: Duration::ZERO, hysteresis: 0.0 }
+    }
+
+    /// Rule triggered when the value is under the threshold
+    pub fn below(name: &str, metric: AlertMetric, threshold: f64) -> AlertRule {
+        AlertRule { above: false, ..AlertRule::above(name, metric, threshold) }
+    }
+
+    /// Time the condition must hold before triggering. By default it triggers on the first check
+    pub fn for_duration(mut self, duration: Duration) -> AlertRule {
+        self.duration = duration;
+        self
+    }
+
+    /// Margin the value must go back past the threshold to clear the alert, so a value oscillating around the
+    /// threshold does not flap. By default 0
+    pub fn with_hysteresis(mut self, hysteresis: f64) -> AlertRule {
+        self.hysteresis = hysteresis.abs();
+        self
+    }
+
+    fn crossed(&self, value: f64) -> bool {
+        if self.above { value > self.threshold } else { value < self.threshold }
+    }
+
+    fn recovered(&self, value: f64) -> bool {
+        if self.above { value <= self.threshold - self.hysteresis } else { value >= self.threshold + self.hysteresis }
+    }
+}
+
+struct RuleState {
+    rule: AlertRule,
+    since: Option<Instant>,
+    active: bool,
+}
+
+/// Threshold alerting. Rules are checked against the machine on every `check` call, which should be done
+/// periodically, and the alerts are delivered to the sinks when they trigger and clear
+/// Example
+/// ```no_run
+/// use machine_info::{Machine, Alerts, AlertRule, AlertMetric, SyslogSink};
+/// use std::{thread, time::Duration};
+///
+/// let m = Machine::new();
+/// let mut alerts = Alerts::new();
+/// alerts.add_rule(AlertRule::above("cpu", AlertMetric::Cpu, 90.0).for_duration(Duration::from_secs(30)));
+/// alerts.add_rule(AlertRule::above("gpu-temperature", AlertMetric::GpuTemperature(None), 85.0).with_hysteresis(5.0));
+/// alerts.add_sink(Box::new(SyslogSink::local("my-agent").unwrap()));
+/// alerts.add_callback(|alert| println!("{:?}", alert));
+/// loop {
+///     alerts.check(&m).unwrap();
+///     thread::sleep(Duration::from_secs(1));
+/// }
+/// ```
+#[derive(Default)]
+pub struct Alerts {
+    rules: Vec<RuleState>,
+    sinks: Vec<Box<dyn AlertSink>>,
+}
+
+impl Alerts {
+    /// Alerts without rules nor sinks
+    pub fn new() -> Alerts {
+        Alerts::default()
+    }
+
+    /// Adds a rule. Rules are identified by their name in the alerts
+    pub fn add_rule(&mut self, rule: AlertRule) {
+        self.rules.push(RuleState { rule, since: None, active: false });
+    }
+
+    /// Adds a destination of the alerts. A `std::sync::mpsc::Sender<Alert>` can be used as sink
+    pub fn add_sink(&mut self, sink: Box<dyn AlertSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Calls the function with every alert
+    pub fn add_callback<F: FnMut(&Alert) + Send + 'static>(&mut self, callback: F) {
+        self.sinks.push(Box::new(CallbackSink(callback)));
+    }
+
+    /// Names of the rules currently triggered
+    pub fn active(&self) -> Vec<String> {
+        self.rules.iter().filter(|state| state.active).map(|state| state.rule.name.clone()).collect()
+    }
+
+    /// Samples the machine, evaluates the rules and delivers the alerts produced. It calls `system_status`, so it
+    /// shares the CPU usage window with other callers. A sink failing does not stop the delivery to the others
+    /// Example
+    /// ```
+    /// use machine_info::{Machine, Alerts, AlertRule, AlertMetric};
+    /// let m = Machine::new();
+    /// let mut alerts = Alerts::new();
+    /// alerts.add_rule(AlertRule::above("memory", AlertMetric::Memory, 0.0));
+    /// let produced = alerts.check(&m).unwrap();
+    /// assert_eq!(produced[0].rule, "memory");
+    /// ```
+    pub fn check(&mut self, machine: &Machine) -> Result<Vec<Alert>> {
+        let readings = Readings::take(machine, &self.rules)?;
+        Ok(self.evaluate(&readings))
+    }
+
+    /// Evaluates only the disk rules, reading the free space of the mounted filesystems without sampling the
+    /// rest of the machine. Much cheaper than `check` or `system_info` to watch the disk space often
+    /// Example
+    /// ```
+    /// use machine_info::{Alerts, AlertRule, AlertMetric};
+    /// let mut alerts = Alerts::new();
+    /// alerts.add_rule(AlertRule::below("root-full", AlertMetric::DiskFree("/".to_string()), 101.0));
+    /// let produced = alerts.check_disks();
+    /// assert_eq!(produced[0].rule, "root-full");
+    /// ```
+    pub fn check_disks(&mut self) -> Vec<Alert> {
+        self.evaluate(&Readings::disks_only())
+    }
+
+    /// Evaluates the rules against the readings and delivers the alerts produced
+    fn evaluate(&mut self, readings: &Readings) -> Vec<Alert> {
+        // The hold time is measured with the monotonic clock, the timestamp of the alerts is the wall clock one
+        let now = host::instant();
+        let timestamp = host::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let mut alerts = vec![];
+        for state in self.rules.iter_mut() {
+            let rule = &state.rule;
+            let value = match readings.value(&rule.metric) {
+                Some(value) => value,
+                // The metric is not available right now (no GPU, unmounted disk...), nothing changes
+                None => continue
+            };
+            let alert_state = if state.active {
+                if !rule.recovered(value) {
+                    continue;
+                }
+                state.active = false;
+                state.since = None;
+                AlertState::Cleared
+            } else {
+                if !rule.crossed(value) {
+                    state.since = None;
+                    continue;
+                }
+                let since = *state.since.get_or_insert(now);
+                if now.duration_since(since) < rule.duration {
+                    continue;
+                }
+                state.active = true;
+                AlertState::Triggered
+            };
+            let message = match alert_state {
+                AlertState::Triggered => format!("{:?} is {:.1}, {} {}", rule.metric, value, if rule.above { "above" } else { "below" }, rule.threshold),
+                AlertState::Cleared => format!("{:?} is back to {:.1}", rule.metric, value)
+            };
+            alerts.push(Alert { rule: rule.name.clone(), state: alert_state, value, message, timestamp });
+        }
+        for alert in &alerts {
+            for sink in self.sinks.iter_mut() {
+                if let Err(err) = sink.send(alert) {
+                    warn!("Cannot deliver alert {}: {:?}", alert.rule, err);
+                }
+            }
+        }
+        alerts
+    }
+}
+
+/// Watches the free space of the filesystems in a background thread, checking the disk rules of the alerts
+/// every interval and delivering the alerts to their sinks when a filesystem crosses a threshold. Only the
+/// disks are read, so it can run often. The thread stops when the watcher is dropped
+/// Example
+/// ```
+/// use machine_info::{Alerts, AlertRule, AlertMetric, DiskWatcher};
+/// use std::sync::mpsc;
+/// use std::time::Duration;
+///
+/// let mut alerts = Alerts::new();
+/// alerts.add_rule(AlertRule::below("root-full", AlertMetric::DiskFree("/".to_string()), 101.0).with_hysteresis(2.0));
+/// alerts.add_rule(AlertRule::below("root-10gb", AlertMetric::DiskFreeBytes("/".to_string()), 10e9));
+/// let (sender, receiver) = mpsc::channel();
+/// alerts.add_sink(Box::new(sender));
+/// let watcher = DiskWatcher::start(alerts, Duration::from_secs(10));
+/// assert_eq!(receiver.recv().unwrap().rule, "root-full");
+/// ```
+pub struct DiskWatcher {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DiskWatcher {
+    /// Starts checking the disk rules of the alerts every `interval`. The other rules are ignored
+    pub fn start(mut alerts: Alerts, interval: Duration) -> DiskWatcher {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || {
+            loop {
+                let started = Instant::now();
+                alerts.check_disks();
+                // Waiting for the stop signal is the sleep between checks
+                match stopped.recv_timeout(interval.saturating_sub(started.elapsed())) {
+                    Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                    _ => break
+                }
+            }
+        });
+        DiskWatcher {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops watching and waits for the thread to finish
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for DiskWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Values sampled for a check. The expensive ones are only sampled if a rule needs them
+struct Readings {
+    cpu: Option<f64>,
+    memory: Option<f64>,
+    graphics: Vec<GraphicsUsage>,
+    // Mount point, available and total bytes
+    disks: Vec<(String, u64, u64)>,
+}
+
+impl Readings {
+    fn take(machine: &Machine, rules: &[RuleState]) -> Result<Readings> {
+        let status = machine.system_status()?;
+        let needs = |check: fn(&AlertMetric) -> bool| rules.iter().any(|state| check(&state.rule.metric));
+        let graphics = if needs(|metric| matches!(metric, AlertMetric::GpuTemperature(_) | AlertMetric::GpuUsage(_))) {
+            machine.graphics_status()
+        } else {
+            vec![]
+        };
+        let disks = if needs(AlertMetric::is_disk) {
+            Readings::disks_only().disks
+        } else {
+            vec![]
+        };
+        let memory = &status.memory_breakdown;
+        Ok(Readings {
+            cpu: Some(status.cpu as f64),
+            memory: Some(if memory.total > 0 { memory.used as f64 * 100.0 / memory.total as f64 } else { 0.0 }),
+            graphics,
+            disks,
+        })
+    }
+
+    /// Free space of the mounted filesystems only
+    fn disks_only() -> Readings {
+        Readings {
+            cpu: None,
+            memory: None,
+            graphics: vec![],
+            disks: Disks::new_with_refreshed_list().iter()
+                .filter(|disk| disk.total_space() > 0)
+                .map(|disk| (disk.mount_point().to_string_lossy().to_string(), disk.available_space(), disk.total_space()))
+                .collect(),
+        }
+    }
+
+    fn value(&self, metric: &AlertMetric) -> Option<f64> {
+        // Fields that could not be read are 0, they are skipped
+        let card = |id: &Option<String>, field: &str, value: fn(&GraphicsUsage) -> u32| self.graphics.iter()
+            .filter(|card| id.as_ref().map(|id| *id == card.id).unwrap_or(true) && card.is_read(field))
+            .map(value)
+            .max()
+            .map(|value| value as f64);
+        let disk = |path: &String| self.disks.iter().find(|(mount, _, _)| mount == path);
+        match metric {
+            AlertMetric::Cpu => self.cpu,
+            AlertMetric::Memory => self.memory,
+            AlertMetric::GpuTemperature(id) => card(id, "temperature", |card| card.temperature),
+            AlertMetric::GpuUsage(id) => card(id, "utilization", |card| card.gpu),
+            AlertMetric::DiskFree(path) => disk(path).map(|(_, available, total)| *available as f64 * 100.0 / *total as f64),
+            AlertMetric::DiskFreeBytes(path) => disk(path).map(|(_, available, _)| *available as f64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syslog_timestamps() {
+        assert_eq!(utc_time(0), (0, 1, 0, 0, 0));
+        // 2024-02-29 23:59:59 and 2023-10-06 14:03:09
+        assert_eq!(utc_time(1709251199), (1, 29, 23, 59, 59));
+        assert_eq!(utc_time(1696600989), (9, 6, 14, 3, 9));
+        let timestamp = syslog_timestamp(UNIX_EPOCH + Duration::from_secs(1696600989));
+        assert_eq!(timestamp.len(), 15);
+        assert!(timestamp.starts_with("Oct  6 ") || timestamp.starts_with("Oct  7 ") || timestamp.starts_with("Oct  5 "), "{}", timestamp);
+    }
+
+    #[test]
+    #[cfg(feature = "testkit")]
+    fn rule_triggers_after_its_duration() {
+        let mut tree = crate::testkit::FakeTree::new(1).unwrap();
+        let m = Machine::builder().with_nvml(false).build();
+        let mut alerts = Alerts::new();
+        alerts.add_rule(AlertRule::above("memory", AlertMetric::Memory, 40.0).for_duration(Duration::from_secs(30)));
+        assert!(alerts.check(&m).unwrap().is_empty());
+        tree.tick(Duration::from_secs(20), 0).unwrap();
+        assert!(alerts.check(&m).unwrap().is_empty());
+        tree.tick(Duration::from_secs(15), 0).unwrap();
+        let produced = alerts.check(&m).unwrap();
+        assert_eq!(produced.len(), 1);
+        assert_eq!(produced[0].state, AlertState::Triggered);
+        assert_eq!(alerts.active(), vec!["memory"]);
+
+        tree.set_memory(16 * 1024 * 1024, 12 * 1024 * 1024).unwrap();
+        tree.tick(Duration::from_secs(1), 0).unwrap();
+        assert_eq!(alerts.check(&m).unwrap()[0].state, AlertState::Cleared);
+    }
+}
@@ -0,0 +1,22 @@
+use std::ffi::{CStr, CString};
+
+/// Value of an Android system property, like ro.product.model
+fn property(name: &str) -> Option<String> {
+    let name = CString::new(name).ok()?;
+    let mut value = [0 as libc::c_char; libc::PROP_VALUE_MAX as usize];
+    // Returns the length of the value, 0 if the property is not set
+    let len = unsafe { libc::__system_property_get(name.as_ptr(), value.as_mut_ptr()) };
+    if len <= 0 {
+        return None;
+    }
+    let value = unsafe { CStr::from_ptr(value.as_ptr()) };
+    Some(value.to_string_lossy().trim().to_string()).filter(|value| !value.is_empty())
+}
+
+/// Manufacturer and model of the device from the system properties, like "Google Pixel 7"
+pub fn model() -> Option<String> {
+    match (property("ro.product.manufacturer"), property("ro.product.model")) {
+        (Some(manufacturer), Some(model)) if !model.starts_with(&manufacturer) => Some(format!("{} {}", manufacturer, model)),
+        (manufacturer, model) => model.or(manufacturer)
+    }
+}
@@ -0,0 +1,163 @@
+//! Async access to a `Machine` for async services. The samples are taken in a dedicated worker thread, so the
+//! slow refreshes (disks, NVML, /proc scans) never block the async runtime. It works with any runtime (tokio,
+//! async-std, smol...) since it only relies on wakers
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, mpsc};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use crate::Machine;
+use crate::model::{SystemInfo, SystemStatus, GraphicsUsage, Process, Snapshot};
+
+type Job = Box<dyn FnOnce(&mut Machine) + Send>;
+
+struct Slot<T> {
+    value: Option<T>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// Sending half of a response. If it is dropped without sending (the worker stopped), the response fails
+struct Reply<T>(Arc<Mutex<Slot<T>>>);
+
+impl<T> Reply<T> {
+    fn send(self, value: T) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).value = Some(value);
+    }
+}
+
+impl<T> Drop for Reply<T> {
+    fn drop(&mut self) {
+        let mut slot = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        slot.closed = true;
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Future of a call done by the worker. It fails if the worker stopped (a call panicked)
+pub struct Response<T>(Arc<Mutex<Slot<T>>>);
+
+impl<T> Future for Response<T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Result<T>> {
+        let mut slot = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(value) = slot.value.take() {
+            return Poll::Ready(Ok(value));
+        }
+        if slot.closed {
+            return Poll::Ready(Err(anyhow::anyhow!("The machine worker stopped")));
+        }
+        slot.waker = Some(context.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Async version of `Machine`. The machine lives in a worker thread that runs the calls one after the other
+/// Example
+/// ```
+/// use machine_info::AsyncMachine;
+///
+/// async fn report(m: &AsyncMachine) {
+///     m.track_process(std::process::id() as i32).await.unwrap();
+///     println!("{:?}", m.system_status().await);
+///     println!("{:?}", m.processes_status().await);
+/// }
+/// ```
+pub struct AsyncMachine {
+    sender: mpsc::Sender<Job>,
+}
+
+impl Default for AsyncMachine {
+    fn default() -> Self {
+        AsyncMachine::new()
+    }
+}
+
+impl AsyncMachine {
+    /// Starts the worker thread with a new `Machine`. The thread stops when this is dropped
+    pub fn new() -> AsyncMachine {
+        AsyncMachine::with(Machine::new)
+    }
+
+    /// Same as `new` but the machine is created by the given function, to configure it
+    /// Example
+    /// ```
+    /// use machine_info::{AsyncMachine, Machine, LoadProtection};
+    /// let m = AsyncMachine::with(|| {
+    ///     let mut machine = Machine::new();
+    ///     machine.set_load_protection(Some(LoadProtection::default()));
+    ///     machine
+    /// });
+    /// ```
+    pub fn with<F: FnOnce() -> Machine + Send + 'static>(create: F) -> AsyncMachine {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        thread::spawn(move || {
+            let mut machine = create();
+            for job in receiver {
+                job(&mut machine);
+            }
+        });
+        AsyncMachine { sender }
+    }
+
+    /// Runs any `Machine` call in the worker
+    /// Example
+    /// ```
+    /// use machine_info::AsyncMachine;
+    ///
+    /// async fn temperatures(m: &AsyncMachine) {
+    ///     let sensors = m.run(|machine| machine.thermal_status()).await.unwrap();
+    ///     println!("{:?}", sensors);
+    /// }
+    /// ```
+    pub fn run<T, F>(&self, call: F) -> Response<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Machine) -> T + Send + 'static
+    {
+        let slot = Arc::new(Mutex::new(Slot { value: None, closed: false, waker: None }));
+        let reply = Reply(slot.clone());
+        // If the worker stopped, the job is dropped with the reply and the response fails
+        let _ = self.sender.send(Box::new(move |machine: &mut Machine| reply.send(call(machine))));
+        Response(slot)
+    }
+
+    /// Async `Machine::system_info`
+    pub async fn system_info(&self) -> Result<SystemInfo> {
+        self.run(|machine| machine.system_info()).await
+    }
+
+    /// Async `Machine::system_status`
+    pub async fn system_status(&self) -> Result<SystemStatus> {
+        self.run(|machine| machine.system_status()).await?
+    }
+
+    /// Async `Machine::graphics_status`
+    pub async fn graphics_status(&self) -> Result<Vec<GraphicsUsage>> {
+        self.run(|machine| machine.graphics_status()).await
+    }
+
+    /// Async `Machine::track_process`
+    pub async fn track_process(&self, pid: i32) -> Result<()> {
+        self.run(move |machine| machine.track_process(pid)).await?
+    }
+
+    /// Async `Machine::untrack_process`
+    pub async fn untrack_process(&self, pid: i32) -> Result<()> {
+        self.run(move |machine| machine.untrack_process(pid)).await
+    }
+
+    /// Async `Machine::processes_status`
+    pub async fn processes_status(&self) -> Result<Vec<Process>> {
+        self.run(|machine| machine.processes_status()).await
+    }
+
+    /// Async `Machine::snapshot`
+    pub async fn snapshot(&self) -> Result<Snapshot> {
+        self.run(|machine| machine.snapshot()).await?
+    }
+}
@@ -0,0 +1,165 @@
+//! Command line interface of the crate. Build it with the `cli` feature:
+//!
+//! ```text
+//! cargo install machine-info --features cli
+//! machine-info info
+//! machine-info watch --interval 500ms --json
+//! ```
+use anyhow::{anyhow, Result};
+use machine_info::{Machine, SystemInfo, SystemStatus, GraphicsUsage};
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: machine-info <command> [--json]
+
+Commands:
+  info                     Hardware and operating system information
+  status                   CPU and memory usage
+  gpus                     Usage of the Nvidia cards
+  watch [--interval 1s]    CPU, memory and cards usage every interval until interrupted
+
+Options:
+  --json                   JSON output instead of a table
+  -h, --help               Shows this help";
+
+struct Options {
+    command: String,
+    json: bool,
+    interval: Duration,
+}
+
+/// Durations like 1s, 500ms or 2m. A number alone is in seconds
+fn parse_duration(value: &str) -> Result<Duration> {
+    let split = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number = number.parse::<f64>().map_err(|_| anyhow!("Invalid interval {}", value))?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        _ => return Err(anyhow!("Invalid interval unit {}", unit))
+    };
+    if seconds <= 0.0 {
+        return Err(anyhow!("The interval must be positive"));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options> {
+    let mut command = None;
+    let mut json = false;
+    let mut interval = Duration::from_secs(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--interval" | "-i" => {
+                let value = args.next().ok_or_else(|| anyhow!("Missing value of {}", arg))?;
+                interval = parse_duration(&value)?;
+            },
+            "-h" | "--help" => command = Some("help".to_string()),
+            _ if arg.starts_with("--interval=") => interval = parse_duration(&arg["--interval=".len()..])?,
+            _ if arg.starts_with('-') => return Err(anyhow!("Unknown option {}", arg)),
+            _ if command.is_none() => command = Some(arg),
+            _ => return Err(anyhow!("Unexpected argument {}", arg))
+        }
+    }
+    Ok(Options {
+        command: command.unwrap_or_else(|| "help".to_string()),
+        json,
+        interval,
+    })
+}
+
+fn gigabytes(bytes: u64) -> String {
+    format!("{:.1} GB", bytes as f64 / 1_000_000_000.0)
+}
+
+fn print_info(info: &SystemInfo) {
+    let rows = [
+        ("Hostname", info.hostname.clone()),
+        ("OS", format!("{} {}", info.os_name, info.os_version)),
+        ("Kernel", info.kernel_version.clone()),
+        ("Processor", format!("{} ({} cores, {} MHz)", info.processor.brand, info.total_processors, info.processor.frequency)),
+        ("Memory", gigabytes(info.memory)),
+        ("Virtualization", format!("{:?}", info.virtualization)),
+    ];
+    for (name, value) in rows {
+        println!("{:<16}{}", name, value);
+    }
+    if let Some(model) = &info.model {
+        println!("{:<16}{}", "Model", model);
+    }
+    for module in &info.security_modules {
+        let mode = module.mode.map(|mode| format!("{:?}", mode)).unwrap_or_else(|| "unknown mode".to_string());
+        println!("{:<16}{} ({})", "Security", module.name, mode);
+    }
+    if let Some(nvidia) = &info.nvidia {
+        println!("{:<16}{} (CUDA {})", "Nvidia driver", nvidia.driver_version, nvidia.cuda_version);
+    }
+    for card in &info.graphics {
+        println!("{:<16}{} {} ({}, {}°C)", "GPU", card.brand, card.name, gigabytes(card.memory), card.temperature);
+    }
+    for disk in &info.disks {
+        println!("{:<16}{} {} ({} free of {})", "Disk", disk.mount_point, disk.fs, gigabytes(disk.available), gigabytes(disk.size));
+    }
+    for camera in &info.cameras {
+        println!("{:<16}{} {}", "Camera", camera.name, camera.path);
+    }
+}
+
+fn print_status(status: &SystemStatus) {
+    println!("{:<8}{:>5}%", "CPU", status.cpu);
+    println!("{:<8}{} of {}", "Memory", gigabytes(status.memory_breakdown.used), gigabytes(status.memory_breakdown.total));
+}
+
+fn print_gpus(cards: &[GraphicsUsage]) {
+    println!("{:<42}{:>6}{:>8}{:>8}{:>8}{:>7}", "ID", "GPU", "MEMORY", "ENC", "DEC", "TEMP");
+    for card in cards {
+        println!("{:<42}{:>5}%{:>7}%{:>7}%{:>7}%{:>5}°C", card.id, card.gpu, card.memory_usage, card.encoder, card.decoder, card.temperature);
+    }
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string(value)?);
+    Ok(())
+}
+
+fn run(options: Options) -> Result<()> {
+    let machine = Machine::new();
+    match options.command.as_str() {
+        "info" if options.json => print_json(&machine.system_info())?,
+        "info" => print_info(&machine.system_info()),
+        "status" if options.json => print_json(&machine.system_status()?)?,
+        "status" => print_status(&machine.system_status()?),
+        "gpus" if options.json => print_json(&machine.graphics_status())?,
+        "gpus" => print_gpus(&machine.graphics_status()),
+        "watch" => {
+            // The first reading is the usage since boot
+            machine.system_status()?;
+            loop {
+                thread::sleep(options.interval);
+                if options.json {
+                    print_json(&machine.snapshot()?)?;
+                } else {
+                    print_status(&machine.system_status()?);
+                    let cards = machine.graphics_status();
+                    if !cards.is_empty() {
+                        print_gpus(&cards);
+                    }
+                    println!();
+                }
+            }
+        },
+        "help" => println!("{}", USAGE),
+        command => return Err(anyhow!("Unknown command {}\n\n{}", command, USAGE))
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = parse_options(std::env::args().skip(1)).and_then(run);
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
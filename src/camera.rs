@@ -1,7 +1,16 @@
 //! V4l list cameras feature
 use v4l::context;
-use crate::model::Camera;
+use v4l::{Device, FourCC, Fraction};
+use v4l::video::Capture;
+use v4l::capability::Flags;
+use v4l::framesize::FrameSizeEnum;
+use v4l::frameinterval::FrameIntervalEnum;
+use log::debug;
+use crate::model::{Camera, CameraDevice, CameraFormat, CameraResolution};
+use crate::host;
+use std::fs;
 use std::panic;
+use std::path::Path;
 /// List of attached cameras to the machine
 /// Example
 /// ```
@@ -25,11 +34,134 @@ pub fn list_cameras() -> Vec<Camera> {
             
         };
         
+        let path = dev.path();
+        let device = match Device::with_path(path) {
+            Ok(device) => Some(device),
+            Err(err) => {
+                debug!("Cannot open camera {:?}: {}", path, err);
+                None
+            }
+        };
+        let capabilities = device.as_ref().and_then(|device| device.query_caps().ok());
         cameras.push(Camera {
             name,
-            path: dev.path().as_os_str().to_str().unwrap_or("Unknown").to_owned()
+            path: path.as_os_str().to_str().unwrap_or("Unknown").to_owned(),
+            bus_info: capabilities.as_ref().map(|caps| caps.bus.clone()).filter(|bus| !bus.is_empty()),
+            serial: serial(dev.index()),
+            // A USB camera usually has a second node for metadata that cannot capture video
+            capture: capabilities.as_ref()
+                .map(|caps| caps.capabilities.intersects(Flags::VIDEO_CAPTURE | Flags::VIDEO_CAPTURE_MPLANE))
+                .unwrap_or(false),
+            formats: device.as_ref().map(|device| formats(device, path)).unwrap_or_default()
         })
     }
     cameras
 
-}
\ No newline at end of file
+}
+
+/// Serial number of the USB device the node belongs to. The node device is the USB interface, the
+/// serial is in its parent
+fn serial(index: usize) -> Option<String> {
+    let path = host::path(&format!("/sys/class/video4linux/video{}/device/../serial", index));
+    fs::read_to_string(path).ok()
+        .map(|serial| serial.trim().to_string())
+        .filter(|serial| !serial.is_empty())
+}
+
+/// Paths of the video devices. It is cheap since the devices are not opened
+pub fn camera_paths() -> Vec<String> {
+    let mut paths = context::enum_devices().iter()
+        .map(|dev| dev.path().as_os_str().to_str().unwrap_or("Unknown").to_owned())
+        .collect::<Vec<String>>();
+    paths.sort();
+    paths
+}
+
+/// Pixel formats supported by the capture device, with their resolutions and frame rates
+fn formats(device: &Device, path: &Path) -> Vec<CameraFormat> {
+    // Metadata nodes and other non capture devices fail here
+    let descriptions = match device.enum_formats() {
+        Ok(descriptions) => descriptions,
+        Err(err) => {
+            debug!("Cannot list formats of camera {:?}: {}", path, err);
+            return vec![];
+        }
+    };
+    descriptions.into_iter()
+        .map(|description| CameraFormat {
+            fourcc: description.fourcc.to_string(),
+            description: description.description,
+            resolutions: resolutions(device, description.fourcc)
+        })
+        .collect()
+}
+
+/// Stepwise and continuous ranges can have thousands of sizes, only the minimum and maximum are listed
+fn resolutions(device: &Device, fourcc: FourCC) -> Vec<CameraResolution> {
+    let mut resolutions = vec![];
+    for size in device.enum_framesizes(fourcc).unwrap_or_default() {
+        let sizes = match size.size {
+            FrameSizeEnum::Discrete(size) => vec![(size.width, size.height)],
+            FrameSizeEnum::Stepwise(size) => vec![(size.min_width, size.min_height), (size.max_width, size.max_height)]
+        };
+        for (width, height) in sizes {
+            resolutions.push(CameraResolution {
+                width,
+                height,
+                frame_rates: frame_rates(device, fourcc, width, height)
+            });
+        }
+    }
+    resolutions
+}
+
+fn frame_rates(device: &Device, fourcc: FourCC, width: u32, height: u32) -> Vec<f64> {
+    let mut rates = vec![];
+    for interval in device.enum_frameintervals(fourcc, width, height).unwrap_or_default() {
+        match interval.interval {
+            FrameIntervalEnum::Discrete(interval) => rates.push(frame_rate(interval)),
+            // The longest interval is the lowest rate
+            FrameIntervalEnum::Stepwise(interval) => {
+                rates.push(frame_rate(interval.max));
+                rates.push(frame_rate(interval.min));
+            }
+        }
+    }
+    rates.retain(|rate| *rate > 0.0);
+    rates
+}
+
+/// Frames per second from a frame interval in seconds
+fn frame_rate(interval: Fraction) -> f64 {
+    if interval.numerator == 0 {
+        0.0
+    } else {
+        interval.denominator as f64 / interval.numerator as f64
+    }
+}
+/// Groups the video nodes by physical device. The serial (or the bus when there is no serial) identifies
+/// the device, nodes without both are devices on their own
+pub fn group_cameras(cameras: Vec<Camera>) -> Vec<CameraDevice> {
+    let mut devices: Vec<CameraDevice> = vec![];
+    for camera in cameras {
+        let key = camera.serial.clone().or_else(|| camera.bus_info.clone());
+        let device = devices.iter_mut()
+            .find(|device| key.is_some() && device.nodes.iter().any(|node| node.serial.clone().or_else(|| node.bus_info.clone()) == key));
+        match device {
+            Some(device) => device.nodes.push(camera),
+            None => devices.push(CameraDevice {
+                name: camera.name.clone(),
+                bus_info: camera.bus_info.clone(),
+                serial: camera.serial.clone(),
+                nodes: vec![camera]
+            })
+        }
+    }
+    for device in devices.iter_mut() {
+        device.nodes.sort_by(|a, b| a.path.cmp(&b.path));
+        if let Some(capture) = device.nodes.iter().find(|node| node.capture) {
+            device.name = capture.name.clone();
+        }
+    }
+    devices
+}
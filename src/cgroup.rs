@@ -0,0 +1,213 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use log::debug;
+use crate::memory::read_number;
+use crate::model::{CgroupMemory, CgroupStatus};
+use crate::host;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+// cgroup v1 uses a huge number (page aligned i64::MAX) when there is no limit
+const V1_UNLIMITED: u64 = 1 << 62;
+
+/// cgroup where the given process lives. Version 2 entries look like `0::/path` and version 1
+/// entries like `4:memory:/path`
+fn process_cgroup(pid: &str, controller: &str) -> Option<(u8, String)> {
+    let raw = fs::read_to_string(host::path(&format!("/proc/{}/cgroup", pid))).ok()?;
+    let mut unified = None;
+    for line in raw.lines() {
+        let mut parts = line.splitn(3, ':');
+        let (id, controllers, path) = (parts.next()?, parts.next()?, parts.next()?);
+        if controllers.split(',').any(|c| c == controller) {
+            return Some((1, path.to_string()));
+        }
+        if id == "0" && controllers.is_empty() {
+            unified = Some((2, path.to_string()));
+        }
+    }
+    unified
+}
+
+/// Directory of the cgroup. Inside containers the cgroup namespace hides the parent directories, so if the
+/// full path does not exist the mount root is the cgroup itself
+fn cgroup_dir(base: &Path, path: &str) -> PathBuf {
+    let full = base.join(path.trim_start_matches('/'));
+    if full.exists() {
+        full
+    } else {
+        base.to_path_buf()
+    }
+}
+
+/// Memory limit and usage of the cgroup of the current process
+pub fn memory() -> Result<CgroupMemory> {
+    let (version, path) = process_cgroup("self", "memory")
+        .ok_or_else(|| anyhow::anyhow!("Process is not in a memory cgroup"))?;
+    if version == 2 {
+        let dir = cgroup_dir(&host::path(CGROUP_ROOT), &path);
+        let limit = fs::read_to_string(dir.join("memory.max"))?;
+        Ok(CgroupMemory {
+            version,
+            limit: match limit.trim() {
+                "max" => None,
+                value => Some(value.parse::<u64>()?)
+            },
+            usage: read_number(&dir.join("memory.current"))?,
+        })
+    } else {
+        let dir = cgroup_dir(&host::path(CGROUP_ROOT).join("memory"), &path);
+        let limit = read_number(&dir.join("memory.limit_in_bytes"))?;
+        debug!("cgroup v1 memory limit is {}", limit);
+        Ok(CgroupMemory {
+            version,
+            limit: if limit >= V1_UNLIMITED { None } else { Some(limit) },
+            usage: read_number(&dir.join("memory.usage_in_bytes"))?,
+        })
+    }
+}
+
+/// Counters of a cgroup (version 2) used to calculate its usage between two samples
+#[derive(Debug)]
+pub struct CgroupStats {
+    cpu_usec: u64,
+    read_bytes: u64,
+    write_bytes: u64,
+    when: Instant,
+}
+
+/// Directory of a cgroup given as a path relative to the cgroup root (like `system.slice/nginx.service`)
+/// or as an absolute path under /sys/fs/cgroup
+fn tracked_dir(path: &str) -> PathBuf {
+    let relative = path.strip_prefix(CGROUP_ROOT).unwrap_or(path);
+    host::path(CGROUP_ROOT).join(relative.trim_start_matches('/'))
+}
+
+/// Value of a `key value` line from files like cpu.stat
+fn flat_value(content: &str, key: &str) -> Option<u64> {
+    content.lines()
+        .find_map(|line| line.strip_prefix(key).and_then(|rest| rest.strip_prefix(' ')))
+        .and_then(|value| value.trim().parse::<u64>().ok())
+}
+
+/// Bytes read and written by all the devices in io.stat. Lines look like `8:0 rbytes=1 wbytes=2 rios=3 ...`
+fn io_bytes(content: &str) -> (u64, u64) {
+    let mut read = 0;
+    let mut write = 0;
+    for field in content.split_whitespace() {
+        if let Some((key, value)) = field.split_once('=') {
+            let value = value.parse::<u64>().unwrap_or_default();
+            match key {
+                "rbytes" => read += value,
+                "wbytes" => write += value,
+                _ => {}
+            }
+        }
+    }
+    (read, write)
+}
+
+impl CgroupStats {
+    pub fn read(path: &str) -> Result<CgroupStats> {
+        let dir = tracked_dir(path);
+        let cpu = fs::read_to_string(dir.join("cpu.stat"))
+            .map_err(|err| anyhow::anyhow!("Cannot read cgroup {}, only cgroup v2 is supported: {}", path, err))?;
+        // The io controller can be disabled for the cgroup
+        let (read_bytes, write_bytes) = fs::read_to_string(dir.join("io.stat"))
+            .map(|io| io_bytes(&io))
+            .unwrap_or_default();
+        Ok(CgroupStats {
+            cpu_usec: flat_value(&cpu, "usage_usec")
+                .ok_or_else(|| anyhow::anyhow!("No usage_usec found in cpu.stat of cgroup {}", path))?,
+            read_bytes,
+            write_bytes,
+            when: host::instant(),
+        })
+    }
+
+    /// Usage since the last sample, with the memory and processes at this moment
+    pub fn usage(&self, path: &str, last: &CgroupStats) -> CgroupStatus {
+        let dir = tracked_dir(path);
+        let elapsed = self.when.duration_since(last.when).as_secs_f64();
+        let rate = |current: u64, last: u64| if elapsed > 0.0 { current.saturating_sub(last) as f64 / elapsed } else { 0.0 };
+        let limit = fs::read_to_string(dir.join("memory.max")).ok();
+        // pids.current includes the child cgroups, cgroup.procs only this one
+        let processes = read_number(&dir.join("pids.current")).ok().or_else(|| {
+            fs::read_to_string(dir.join("cgroup.procs")).ok().map(|procs| procs.lines().count() as u64)
+        });
+        CgroupStatus {
+            path: path.to_string(),
+            cpu: 100.0 * rate(self.cpu_usec, last.cpu_usec) / 1_000_000.0,
+            memory: read_number(&dir.join("memory.current")).ok(),
+            memory_limit: limit.and_then(|limit| limit.trim().parse::<u64>().ok()),
+            read_bytes_per_second: rate(self.read_bytes, last.read_bytes),
+            write_bytes_per_second: rate(self.write_bytes, last.write_bytes),
+            processes,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use super::*;
+    use crate::testkit::FakeTree;
+    use std::time::Duration;
+
+    #[test]
+    fn memory_of_the_v2_cgroup() {
+        let tree = FakeTree::new(1).unwrap();
+        tree.write("/proc/self/cgroup", "0::/user.slice/session-2.scope\n").unwrap();
+        tree.write("/sys/fs/cgroup/user.slice/session-2.scope/memory.max", "max\n").unwrap();
+        tree.write("/sys/fs/cgroup/user.slice/session-2.scope/memory.current", "1048576\n").unwrap();
+        let usage = memory().unwrap();
+        assert_eq!((usage.version, usage.limit, usage.usage), (2, None, 1048576));
+
+        // Inside a container the cgroup namespace makes the root the cgroup of the process
+        tree.write("/proc/self/cgroup", "0::/../../docker-123.scope\n").unwrap();
+        tree.write("/sys/fs/cgroup/memory.max", "536870912\n").unwrap();
+        tree.write("/sys/fs/cgroup/memory.current", "4096\n").unwrap();
+        let usage = memory().unwrap();
+        assert_eq!((usage.limit, usage.usage), (Some(536870912), 4096));
+    }
+
+    #[test]
+    fn memory_of_the_v1_cgroup() {
+        let tree = FakeTree::new(1).unwrap();
+        tree.write("/proc/self/cgroup", "12:cpu,cpuacct:/\n4:memory:/docker/abc\n0::/\n").unwrap();
+        tree.write("/sys/fs/cgroup/memory/docker/abc/memory.limit_in_bytes", "9223372036854771712\n").unwrap();
+        tree.write("/sys/fs/cgroup/memory/docker/abc/memory.usage_in_bytes", "8192\n").unwrap();
+        let usage = memory().unwrap();
+        assert_eq!((usage.version, usage.limit, usage.usage), (1, None, 8192));
+    }
+
+    #[test]
+    fn usage_between_samples() {
+        let mut tree = FakeTree::new(1).unwrap();
+        let path = "system.slice/nginx.service";
+        tree.write("/sys/fs/cgroup/system.slice/nginx.service/cpu.stat", "usage_usec 1000000\nuser_usec 800000\nsystem_usec 200000\n").unwrap();
+        tree.write("/sys/fs/cgroup/system.slice/nginx.service/io.stat", "8:0 rbytes=1000 wbytes=0 rios=1 wios=0\n").unwrap();
+        tree.write("/sys/fs/cgroup/system.slice/nginx.service/memory.current", "2097152\n").unwrap();
+        tree.write("/sys/fs/cgroup/system.slice/nginx.service/memory.max", "max\n").unwrap();
+        tree.write("/sys/fs/cgroup/system.slice/nginx.service/pids.current", "4\n").unwrap();
+        let last = CgroupStats::read(path).unwrap();
+
+        // Half a core and 1MiB/s read from two devices during 10 seconds
+        tree.write("/sys/fs/cgroup/system.slice/nginx.service/cpu.stat", "usage_usec 6000000\nuser_usec 5000000\nsystem_usec 1000000\n").unwrap();
+        tree.write("/sys/fs/cgroup/system.slice/nginx.service/io.stat",
+            "8:0 rbytes=5243880 wbytes=0 rios=10 wios=0\n259:0 rbytes=5242880 wbytes=102400 rios=10 wios=5\n").unwrap();
+        tree.tick(Duration::from_secs(10), 0).unwrap();
+        let status = CgroupStats::read(&format!("/sys/fs/cgroup/{}", path)).unwrap().usage(path, &last);
+        assert!((status.cpu - 50.0).abs() < 0.5, "cpu {}", status.cpu);
+        assert!((status.read_bytes_per_second - 1048576.0).abs() < 10486.0, "read {}", status.read_bytes_per_second);
+        assert!((status.write_bytes_per_second - 10240.0).abs() < 103.0);
+        assert_eq!(status.memory, Some(2097152));
+        assert_eq!(status.memory_limit, None);
+        assert_eq!(status.processes, Some(4));
+    }
+
+    #[test]
+    fn only_v2_stats() {
+        let _tree = FakeTree::new(1).unwrap();
+        assert!(CgroupStats::read("system.slice/missing.service").is_err());
+    }
+}
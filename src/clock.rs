@@ -0,0 +1,61 @@
+use std::fs;
+use crate::model::ClockStatus;
+use crate::sensors::read_text;
+use crate::host;
+
+const CLOCKSOURCE_PATH: &str = "/sys/devices/system/clocksource/clocksource0";
+// Daemons disciplining the clock, by process name. OpenNTPD also runs as ntpd
+const SYNC_SERVICES: [&str; 4] = ["chronyd", "ntpd", "systemd-timesyn", "ptp4l"];
+
+/// Name of the first running time synchronization daemon
+fn sync_service() -> Option<String> {
+    let entries = fs::read_dir(host::path("/proc")).ok()?;
+    entries.flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().chars().all(|c| c.is_ascii_digit()))
+        .filter_map(|entry| read_text(&entry.path().join("comm")))
+        .find(|name| SYNC_SERVICES.contains(&name.as_str()))
+        // The process name is cut to 15 characters
+        .map(|name| if name == "systemd-timesyn" { "systemd-timesyncd".to_string() } else { name })
+}
+
+/// Synchronized flag, offset, maximum and estimated error in milliseconds of the kernel clock discipline, set by
+/// the daemon through adjtimex
+#[cfg(target_os = "linux")]
+fn kernel_sync() -> Option<(bool, f64, f64, f64)> {
+    const TIME_ERROR: i32 = 5;
+    const STA_NANO: i32 = 0x2000;
+    let mut timex: libc::timex = unsafe { std::mem::zeroed() };
+    // Without modes it only reads the state
+    let state = unsafe { libc::adjtimex(&mut timex) };
+    if state < 0 {
+        return None;
+    }
+    let offset_unit = if timex.status & STA_NANO != 0 { 1_000_000.0 } else { 1_000.0 };
+    Some((
+        state != TIME_ERROR,
+        timex.offset as f64 / offset_unit,
+        timex.maxerror as f64 / 1_000.0,
+        timex.esterror as f64 / 1_000.0,
+    ))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn kernel_sync() -> Option<(bool, f64, f64, f64)> {
+    None
+}
+
+pub fn clock_status() -> ClockStatus {
+    let clocksource = host::path(CLOCKSOURCE_PATH);
+    let sync = kernel_sync();
+    ClockStatus {
+        clock_source: read_text(&clocksource.join("current_clocksource")),
+        available_clock_sources: read_text(&clocksource.join("available_clocksource"))
+            .map(|sources| sources.split_whitespace().map(|source| source.to_string()).collect())
+            .unwrap_or_default(),
+        sync_service: sync_service(),
+        synchronized: sync.map(|(synchronized, _, _, _)| synchronized).unwrap_or(false),
+        offset: sync.map(|(_, offset, _, _)| offset),
+        max_error: sync.map(|(_, _, max_error, _)| max_error),
+        estimated_error: sync.map(|(_, _, _, estimated_error)| estimated_error),
+    }
+}
@@ -0,0 +1,183 @@
+//! Running containers with their CPU, memory and I/O usage. Enable it with the `containers` feature.
+//!
+//! The containers are found in the cgroup hierarchy (version 2), where Docker, Podman, containerd and CRI-O create
+//! a cgroup per container, so no engine is required. Their names and images are asked to the Docker and Podman API
+//! sockets when they can be opened
+use anyhow::Result;
+use log::debug;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+use crate::Machine;
+use crate::model::ContainerStatus;
+use crate::host;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+// Containers of Kubernetes pods are nested in kubepods.slice/<qos>/<pod>
+const MAX_DEPTH: usize = 6;
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(2);
+const ENGINE_SOCKETS: [&str; 3] = ["/run/docker.sock", "/var/run/docker.sock", "/run/podman/podman.sock"];
+// Scope names of the systemd cgroup driver, like docker-<id>.scope
+const SCOPE_PREFIXES: [(&str, &str); 4] = [
+    ("docker-", "docker"),
+    ("libpod-", "podman"),
+    ("cri-containerd-", "containerd"),
+    ("crio-", "cri-o"),
+];
+// Parent directories of the cgroupfs driver, like docker/<id>
+const CGROUPFS_PARENTS: [(&str, &str); 2] = [("docker", "docker"), ("libpod_parent", "podman")];
+
+/// Container found in the cgroup hierarchy
+struct Container {
+    id: String,
+    runtime: String,
+    cgroup: String,
+}
+
+fn is_container_id(id: &str) -> bool {
+    id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Id and runtime of the container of a cgroup directory, from its name and the one of its parent
+fn container_id(parent: &str, name: &str) -> Option<(String, String)> {
+    let scope = name.strip_suffix(".scope").unwrap_or(name);
+    let from_scope = SCOPE_PREFIXES.iter()
+        .find_map(|(prefix, runtime)| Some((scope.strip_prefix(prefix)?, *runtime)));
+    let (id, runtime) = from_scope.or_else(|| CGROUPFS_PARENTS.iter()
+        .find(|(directory, _)| *directory == parent)
+        .map(|(_, runtime)| (name, *runtime)))?;
+    // libpod-conmon-<id>.scope is the monitor of the container, not the container
+    if is_container_id(id) {
+        Some((id.to_string(), runtime.to_string()))
+    } else {
+        None
+    }
+}
+
+fn find_containers(root: &Path, dir: &Path, depth: usize, containers: &mut Vec<Container>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return
+    };
+    let parent = dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    for entry in entries.flatten() {
+        if !entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let path = entry.path();
+        match container_id(&parent, &entry.file_name().to_string_lossy()) {
+            Some((id, runtime)) => containers.push(Container {
+                id,
+                runtime,
+                cgroup: path.strip_prefix(root).unwrap_or(&path).to_string_lossy().to_string(),
+            }),
+            None if depth < MAX_DEPTH => find_containers(root, &path, depth + 1, containers),
+            None => {}
+        }
+    }
+}
+
+/// Containers with a cgroup. Stopped containers do not have one
+fn containers() -> Vec<Container> {
+    let root = host::path(CGROUP_ROOT);
+    let mut containers = vec![];
+    find_containers(&root, &root, 0, &mut containers);
+    containers.sort_by(|a, b| a.cgroup.cmp(&b.cgroup));
+    containers
+}
+
+/// Sends a GET request to the engine API. HTTP/1.0 is used so the response is not chunked
+fn engine_get(socket: &Path, path: &str) -> Result<Value> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+    stream.set_write_timeout(Some(SOCKET_TIMEOUT))?;
+    stream.write_all(format!("GET {} HTTP/1.0\r\nHost: localhost\r\n\r\n", path).as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, body) = response.split_once("\r\n\r\n")
+        .ok_or_else(|| anyhow::anyhow!("Invalid HTTP response"))?;
+    let status = head.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        return Err(anyhow::anyhow!("Request failed with status {}", status));
+    }
+    Ok(serde_json::from_str(body)?)
+}
+
+/// Names and images by container id, from the Docker and Podman sockets (rootful and the rootless one of the user)
+fn engine_metadata() -> HashMap<String, (Option<String>, Option<String>)> {
+    let mut sockets: Vec<_> = ENGINE_SOCKETS.iter().map(|socket| host::path(socket)).collect();
+    if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+        sockets.push(Path::new(&runtime_dir).join("podman/podman.sock"));
+    }
+    let mut metadata = HashMap::new();
+    for socket in sockets.iter().filter(|socket| socket.exists()) {
+        let list = match engine_get(socket, "/containers/json") {
+            Ok(list) => list,
+            Err(err) => {
+                debug!("Cannot list the containers of {:?}: {:?}", socket, err);
+                continue;
+            }
+        };
+        for container in list.as_array().into_iter().flatten() {
+            let id = match container["Id"].as_str() {
+                Some(id) => id.to_string(),
+                None => continue
+            };
+            // Docker names start with a slash
+            let name = container["Names"][0].as_str().map(|name| name.trim_start_matches('/').to_string());
+            let image = container["Image"].as_str().map(|image| image.to_string());
+            metadata.insert(id, (name, image));
+        }
+    }
+    metadata
+}
+
+impl Machine {
+    /// Running containers (Docker, Podman, containerd and CRI-O) with their CPU, memory and I/O usage since the
+    /// last call, like `cgroups_status` does for the tracked cgroups. Only cgroup v2 is supported. The names and
+    /// images are only known when the Docker or Podman socket can be opened (docker group or root)
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// use std::{thread, time};
+    ///
+    /// let m = Machine::new();
+    /// loop {
+    ///   for container in m.containers_status() {
+    ///     println!("{:?} {:.1}% {:?} bytes", container.name, container.cpu, container.memory);
+    ///   }
+    ///   thread::sleep(time::Duration::from_millis(1000));
+    /// }
+    /// ```
+    pub fn containers_status(&self) -> Vec<ContainerStatus> {
+        let containers = containers();
+        let paths: Vec<String> = containers.iter().map(|container| container.cgroup.clone()).collect();
+        let mut usage: HashMap<String, _> = self.monitor().next_containers(&paths).into_iter()
+            .map(|status| (status.path.clone(), status))
+            .collect();
+        let mut metadata = if containers.is_empty() { HashMap::new() } else { engine_metadata() };
+        containers.into_iter()
+            .filter_map(|container| {
+                let status = usage.remove(&container.cgroup)?;
+                let (name, image) = metadata.remove(&container.id).unwrap_or_default();
+                Some(ContainerStatus {
+                    id: container.id,
+                    name,
+                    image,
+                    runtime: container.runtime,
+                    cgroup: container.cgroup,
+                    cpu: status.cpu,
+                    memory: status.memory,
+                    memory_limit: status.memory_limit,
+                    read_bytes_per_second: status.read_bytes_per_second,
+                    write_bytes_per_second: status.write_bytes_per_second,
+                    processes: status.processes,
+                })
+            })
+            .collect()
+    }
+}
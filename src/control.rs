@@ -0,0 +1,327 @@
+//! Opt-in privileged APIs to change the hardware configuration. They usually require root permissions.
+//! Enable them with the `control` feature
+use anyhow::{Result, Context};
+use log::{info, warn};
+use nvml_wrapper::Device;
+use std::fs;
+use std::path::PathBuf;
+use crate::Machine;
+use crate::memory::read_number;
+use crate::sensors::HWMON_PATH;
+use crate::host;
+
+const CPU_PATH: &str = "/sys/devices/system/cpu";
+
+/// cpufreq directories of the selected CPU or all of them if None
+fn cpufreq_dirs(cpu: Option<usize>) -> Result<Vec<PathBuf>> {
+    let dirs = match cpu {
+        Some(cpu) => vec![host::path(&format!("{}/cpu{}/cpufreq", CPU_PATH, cpu))],
+        None => {
+            let mut dirs = fs::read_dir(host::path(CPU_PATH))?
+                .flatten()
+                .filter(|entry| {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    name.len() > 3 && name.starts_with("cpu") && name[3..].chars().all(|c| c.is_ascii_digit())
+                })
+                .map(|entry| entry.path().join("cpufreq"))
+                .collect::<Vec<PathBuf>>();
+            dirs.sort();
+            dirs
+        }
+    };
+    let dirs = dirs.into_iter().filter(|dir| dir.exists()).collect::<Vec<PathBuf>>();
+    if dirs.is_empty() {
+        return Err(anyhow::anyhow!("cpufreq is not available"));
+    }
+    Ok(dirs)
+}
+
+impl Machine {
+    fn nvml_device(&self, id: &str) -> Result<Device<'_>> {
+        let nvml = self.nvml().ok_or_else(|| anyhow::anyhow!("Nvidia driver not available"))?;
+        Ok(nvml.device_by_uuid(id)?)
+    }
+
+    /// Sets the power limit in milliwatts of the graphic card with the given id (as in `GraphicCard.id`).
+    /// The limit must be inside the range supported by the card. It does not persist after a reboot
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let mut m = Machine::new();
+    /// let card = &m.system_info().graphics[0];
+    /// m.set_gpu_power_limit(&card.id, 200_000).unwrap();
+    /// ```
+    pub fn set_gpu_power_limit(&self, id: &str, milliwatts: u32) -> Result<()> {
+        let mut device = self.nvml_device(id)?;
+        let constraints = device.power_management_limit_constraints()?;
+        if milliwatts < constraints.min_limit || milliwatts > constraints.max_limit {
+            return Err(anyhow::anyhow!("Power limit {}mW out of range {}-{}mW",
+                milliwatts, constraints.min_limit, constraints.max_limit));
+        }
+        device.set_power_management_limit(milliwatts)?;
+        info!("GPU {} power limit set to {}mW", id, milliwatts);
+        Ok(())
+    }
+
+    /// Restores the default power limit of the graphic card
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let card = &m.system_info().graphics[0];
+    /// m.reset_gpu_power_limit(&card.id).unwrap();
+    /// ```
+    pub fn reset_gpu_power_limit(&self, id: &str) -> Result<()> {
+        let mut device = self.nvml_device(id)?;
+        let default = device.power_management_limit_default()?;
+        device.set_power_management_limit(default)?;
+        info!("GPU {} power limit restored to {}mW", id, default);
+        Ok(())
+    }
+
+    /// Sets the application clocks (MHz) of the graphic card. The pair must be one of the supported
+    /// combinations, otherwise the driver rejects it
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let mut m = Machine::new();
+    /// let card = &m.system_info().graphics[0];
+    /// m.set_gpu_application_clocks(&card.id, 5001, 1590).unwrap();
+    /// ```
+    pub fn set_gpu_application_clocks(&self, id: &str, memory_mhz: u32, graphics_mhz: u32) -> Result<()> {
+        let mut device = self.nvml_device(id)?;
+        let memory_clocks = device.supported_memory_clocks()?;
+        if !memory_clocks.contains(&memory_mhz) {
+            return Err(anyhow::anyhow!("Memory clock {}MHz not supported. Supported: {:?}", memory_mhz, memory_clocks));
+        }
+        let graphics_clocks = device.supported_graphics_clocks(memory_mhz)?;
+        if !graphics_clocks.contains(&graphics_mhz) {
+            return Err(anyhow::anyhow!("Graphics clock {}MHz not supported. Supported: {:?}", graphics_mhz, graphics_clocks));
+        }
+        device.set_applications_clocks(memory_mhz, graphics_mhz)?;
+        info!("GPU {} application clocks set to {}MHz memory, {}MHz graphics", id, memory_mhz, graphics_mhz);
+        Ok(())
+    }
+
+    /// Restores the default application clocks of the graphic card
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let card = &m.system_info().graphics[0];
+    /// m.reset_gpu_application_clocks(&card.id).unwrap();
+    /// ```
+    pub fn reset_gpu_application_clocks(&self, id: &str) -> Result<()> {
+        self.nvml_device(id)?.reset_applications_clocks()?;
+        info!("GPU {} application clocks restored", id);
+        Ok(())
+    }
+}
+
+impl Machine {
+    /// Sets the cpufreq governor (performance, powersave, schedutil...) of one CPU or all of them if `cpu` is None
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// m.set_cpu_governor(None, "powersave").unwrap();
+    /// ```
+    pub fn set_cpu_governor(&self, cpu: Option<usize>, governor: &str) -> Result<()> {
+        let dirs = cpufreq_dirs(cpu)?;
+        // Every CPU is checked before changing any of them
+        for dir in &dirs {
+            let available = fs::read_to_string(dir.join("scaling_available_governors"))
+                .with_context(|| format!("Cannot read the governors of {:?}, no CPU was changed", dir))?;
+            if !available.split_whitespace().any(|g| g == governor) {
+                return Err(anyhow::anyhow!("Governor {} not available in {:?}, no CPU was changed. Available: {}",
+                    governor, dir, available.trim()));
+            }
+        }
+        for dir in dirs {
+            fs::write(dir.join("scaling_governor"), governor)
+                .with_context(|| format!("Cannot set governor in {:?}", dir))?;
+        }
+        info!("CPU governor set to {}", governor);
+        Ok(())
+    }
+
+    /// Sets the frequency limits in kHz of one CPU or all of them if `cpu` is None. Limits set to None are not
+    /// changed. The values must be inside the hardware limits
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// m.set_cpu_frequency_limits(None, None, Some(1_500_000)).unwrap();
+    /// ```
+    pub fn set_cpu_frequency_limits(&self, cpu: Option<usize>, min_khz: Option<u64>, max_khz: Option<u64>) -> Result<()> {
+        if let (Some(min), Some(max)) = (min_khz, max_khz) {
+            if min > max {
+                return Err(anyhow::anyhow!("Minimum frequency {}kHz is higher than maximum {}kHz", min, max));
+            }
+        }
+        // Every CPU is checked before changing any of them
+        let mut changes = vec![];
+        for dir in cpufreq_dirs(cpu)? {
+            let read = |file: &str| read_number(&dir.join(file))
+                .with_context(|| format!("Cannot read {} of {:?}, no CPU was changed", file, dir));
+            let hardware_min = read("cpuinfo_min_freq")?;
+            let hardware_max = read("cpuinfo_max_freq")?;
+            for value in [min_khz, max_khz].iter().flatten() {
+                if *value < hardware_min || *value > hardware_max {
+                    return Err(anyhow::anyhow!("Frequency {}kHz out of range {}-{}kHz in {:?}, no CPU was changed",
+                        value, hardware_min, hardware_max, dir));
+                }
+            }
+            // Write in an order that never leaves min over max
+            let current_max = read("scaling_max_freq")?;
+            let writes = match (min_khz, max_khz) {
+                (Some(min), Some(max)) if min > current_max => vec![("scaling_max_freq", max), ("scaling_min_freq", min)],
+                (Some(min), Some(max)) => vec![("scaling_min_freq", min), ("scaling_max_freq", max)],
+                (Some(min), None) => vec![("scaling_min_freq", min)],
+                (None, Some(max)) => vec![("scaling_max_freq", max)],
+                (None, None) => vec![]
+            };
+            changes.push((dir, writes));
+        }
+        for (dir, writes) in changes {
+            for (file, value) in writes {
+                fs::write(dir.join(file), value.to_string())
+                    .with_context(|| format!("Cannot write {} in {:?}", file, dir))?;
+            }
+        }
+        info!("CPU frequency limits set to {:?}-{:?}kHz", min_khz, max_khz);
+        Ok(())
+    }
+}
+
+// pwmN_enable values
+const PWM_MANUAL: u64 = 1;
+const PWM_AUTOMATIC: u64 = 2;
+
+/// Manual control of a hwmon PWM fan. The duty cycle is clamped to a safe minimum and the previous
+/// mode (usually automatic) is restored when the handle is dropped or if a write fails
+pub struct FanControl {
+    pwm: PathBuf,
+    enable: PathBuf,
+    previous_mode: u64,
+    min_duty: u8,
+}
+
+impl FanControl {
+    /// Sets the minimum duty cycle (percentage) allowed. By default 30%
+    pub fn with_min_duty(mut self, percent: u8) -> FanControl {
+        self.min_duty = percent.min(100);
+        self
+    }
+
+    /// Sets the fan duty cycle as percentage. Values lower than the minimum duty are raised to it
+    pub fn set_duty(&mut self, percent: u8) -> Result<()> {
+        let percent = percent.clamp(self.min_duty, 100);
+        let value = (percent as u64 * 255) / 100;
+        let result = fs::write(&self.enable, PWM_MANUAL.to_string())
+            .and_then(|_| fs::write(&self.pwm, value.to_string()))
+            .with_context(|| format!("Cannot set fan duty in {:?}", self.pwm));
+        if result.is_err() {
+            self.restore();
+        } else {
+            info!("Fan {:?} duty set to {}%", self.pwm, percent);
+        }
+        result
+    }
+
+    fn restore(&self) {
+        // Going back to manual mode without a duty could stop the fan, so automatic is used instead
+        let mode = if self.previous_mode == PWM_MANUAL { PWM_AUTOMATIC } else { self.previous_mode };
+        match fs::write(&self.enable, mode.to_string()) {
+            Ok(_) => info!("Fan {:?} restored to mode {}", self.pwm, mode),
+            Err(e) => warn!("Cannot restore fan {:?} mode: {}", self.pwm, e)
+        }
+    }
+}
+
+impl Drop for FanControl {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+impl Machine {
+    /// Takes manual control of the PWM fan `index` of the hwmon device `hwmon` (like hwmon2). The fan goes back
+    /// to its previous mode when the returned handle is dropped
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let mut fan = m.fan_control("hwmon2", 1).unwrap().with_min_duty(40);
+    /// fan.set_duty(60).unwrap();
+    /// ```
+    pub fn fan_control(&self, hwmon: &str, index: u32) -> Result<FanControl> {
+        // Only hwmon<N>, any other name could escape the hwmon directory
+        let valid = hwmon.strip_prefix("hwmon")
+            .map(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or(false);
+        if !valid {
+            return Err(anyhow::anyhow!("Invalid hwmon device {:?}", hwmon));
+        }
+        let dir = host::path(HWMON_PATH).join(hwmon);
+        let pwm = dir.join(format!("pwm{}", index));
+        let enable = dir.join(format!("pwm{}_enable", index));
+        if !pwm.exists() || !enable.exists() {
+            return Err(anyhow::anyhow!("{:?} has no controllable PWM fan", pwm));
+        }
+        let previous_mode = read_number(&enable)?;
+        Ok(FanControl {
+            pwm,
+            enable,
+            previous_mode,
+            min_duty: 30,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use super::*;
+    use crate::testkit::FakeTree;
+
+    fn cpu(tree: &FakeTree, cpu: usize, governors: &str, hardware_max: u64) {
+        let dir = format!("{}/cpu{}/cpufreq", CPU_PATH, cpu);
+        tree.write(&format!("{}/scaling_available_governors", dir), governors).unwrap();
+        tree.write(&format!("{}/scaling_governor", dir), "performance\n").unwrap();
+        tree.write(&format!("{}/cpuinfo_min_freq", dir), "800000\n").unwrap();
+        tree.write(&format!("{}/cpuinfo_max_freq", dir), &format!("{}\n", hardware_max)).unwrap();
+        tree.write(&format!("{}/scaling_min_freq", dir), "800000\n").unwrap();
+        tree.write(&format!("{}/scaling_max_freq", dir), &format!("{}\n", hardware_max)).unwrap();
+    }
+
+    fn read(tree: &FakeTree, cpu: usize, file: &str) -> String {
+        fs::read_to_string(tree.root().join(format!("{}/cpu{}/cpufreq/{}", CPU_PATH.trim_start_matches('/'), cpu, file))).unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn nothing_changed_when_a_cpu_fails() {
+        let tree = FakeTree::new(1).unwrap();
+        // An efficiency core with a lower maximum frequency and without powersave
+        cpu(&tree, 0, "performance powersave\n", 4_000_000);
+        cpu(&tree, 1, "performance powersave\n", 4_000_000);
+        cpu(&tree, 2, "performance\n", 2_000_000);
+        let m = Machine::builder().with_nvml(false).build();
+
+        let error = m.set_cpu_governor(None, "powersave").unwrap_err();
+        assert!(error.to_string().contains("no CPU was changed"));
+        assert!(m.set_cpu_frequency_limits(None, None, Some(3_000_000)).is_err());
+        for cpu in 0..3 {
+            assert_eq!(read(&tree, cpu, "scaling_governor"), "performance");
+            assert_ne!(read(&tree, cpu, "scaling_max_freq"), "3000000");
+        }
+
+        m.set_cpu_governor(Some(0), "powersave").unwrap();
+        m.set_cpu_frequency_limits(None, Some(1_000_000), Some(1_800_000)).unwrap();
+        assert_eq!(read(&tree, 0, "scaling_governor"), "powersave");
+        assert_eq!(read(&tree, 1, "scaling_governor"), "performance");
+        for cpu in 0..3 {
+            assert_eq!((read(&tree, cpu, "scaling_min_freq"), read(&tree, cpu, "scaling_max_freq")), ("1000000".to_string(), "1800000".to_string()));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::model::{SystemInfo, SystemInfoDiff, Change};
+
+/// Items of `after` missing in `before` and items of `before` missing in `after`
+fn compare(before: Vec<String>, after: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let added = after.iter().filter(|item| !before.contains(item)).cloned().collect();
+    let removed = before.into_iter().filter(|item| !after.contains(item)).collect();
+    (added, removed)
+}
+
+fn change<T: PartialEq + Clone>(before: &T, after: &T) -> Option<Change<T>> {
+    (before != after).then(|| Change { before: before.clone(), after: after.clone() })
+}
+
+fn disks(info: &SystemInfo) -> Vec<String> {
+    info.disks.iter().map(|disk| format!("{} on {}", disk.name, disk.mount_point)).collect()
+}
+
+impl SystemInfo {
+    /// Differences from an older snapshot to this one. The snapshots can be stored with serde to compare them
+    /// between restarts
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let before = m.system_info();
+    /// let after = m.system_info();
+    /// let diff = after.diff(&before);
+    /// assert!(diff.is_empty());
+    /// ```
+    pub fn diff(&self, before: &SystemInfo) -> SystemInfoDiff {
+        let (disks_added, disks_removed) = compare(disks(before), disks(self));
+        let ids = |info: &SystemInfo| info.graphics.iter().map(|card| card.id.clone()).collect::<Vec<String>>();
+        let (graphics_added, graphics_removed) = compare(ids(before), ids(self));
+        let paths = |info: &SystemInfo| info.cameras.iter().map(|camera| camera.path.clone()).collect::<Vec<String>>();
+        let (cameras_added, cameras_removed) = compare(paths(before), paths(self));
+        let names = |info: &SystemInfo| info.networks.iter().map(|network| network.name.clone()).collect::<Vec<String>>();
+        let (networks_added, networks_removed) = compare(names(before), names(self));
+        SystemInfoDiff {
+            disks_added,
+            disks_removed,
+            graphics_added,
+            graphics_removed,
+            cameras_added,
+            cameras_removed,
+            networks_added,
+            networks_removed,
+            memory: change(&before.memory, &self.memory),
+            total_processors: change(&before.total_processors, &self.total_processors),
+            processor: change(&before.processor.brand, &self.processor.brand),
+            kernel_version: change(&before.kernel_version, &self.kernel_version),
+            os_version: change(&before.os_version, &self.os_version),
+        }
+    }
+}
+
+impl SystemInfoDiff {
+    /// True if nothing changed
+    /// Example
+    /// ```
+    /// use machine_info::SystemInfoDiff;
+    /// assert!(SystemInfoDiff::default().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.disks_added.is_empty() && self.disks_removed.is_empty()
+            && self.graphics_added.is_empty() && self.graphics_removed.is_empty()
+            && self.cameras_added.is_empty() && self.cameras_removed.is_empty()
+            && self.networks_added.is_empty() && self.networks_removed.is_empty()
+            && self.memory.is_none() && self.total_processors.is_none() && self.processor.is_none()
+            && self.kernel_version.is_none() && self.os_version.is_none()
+    }
+}
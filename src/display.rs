@@ -0,0 +1,173 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use crate::model::Display;
+use crate::sensors::{class_entries, read_text};
+
+const DRM_PATH: &str = "/sys/class/drm";
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+// The four 18 bytes descriptors of the EDID base block, the first one is the preferred timing
+const DESCRIPTORS: [usize; 4] = [54, 72, 90, 108];
+
+/// Values of the EDID base block (VESA E-EDID 1.4)
+#[derive(Default)]
+struct Edid {
+    manufacturer: Option<String>,
+    name: Option<String>,
+    // Active width, height and refresh rate of the preferred timing
+    timing: Option<(u32, u32, f64)>,
+    size_mm: Option<(u32, u32)>,
+}
+
+impl Edid {
+    fn parse(raw: &[u8]) -> Option<Edid> {
+        if raw.len() < 128 || raw[..8] != EDID_HEADER {
+            return None;
+        }
+        let mut edid = Edid::default();
+        // Three letters of 5 bits, 1 is A
+        let id = u16::from_be_bytes([raw[8], raw[9]]);
+        let letters = [(id >> 10) & 31, (id >> 5) & 31, id & 31];
+        if letters.iter().all(|letter| (1..=26).contains(letter)) {
+            edid.manufacturer = Some(letters.iter().map(|letter| (b'@' + *letter as u8) as char).collect());
+        }
+        for offset in DESCRIPTORS {
+            let descriptor = &raw[offset..offset + 18];
+            let pixel_clock = u16::from_le_bytes([descriptor[0], descriptor[1]]) as f64 * 10_000.0;
+            if pixel_clock > 0.0 {
+                if offset == DESCRIPTORS[0] {
+                    let high = |byte: u8| ((byte >> 4) as u32) << 8;
+                    let low = |byte: u8| ((byte & 0x0f) as u32) << 8;
+                    let width = descriptor[2] as u32 | high(descriptor[4]);
+                    let horizontal_blank = descriptor[3] as u32 | low(descriptor[4]);
+                    let height = descriptor[5] as u32 | high(descriptor[7]);
+                    let vertical_blank = descriptor[6] as u32 | low(descriptor[7]);
+                    let total = ((width + horizontal_blank) * (height + vertical_blank)) as f64;
+                    if total > 0.0 {
+                        edid.timing = Some((width, height, (pixel_clock / total * 100.0).round() / 100.0));
+                    }
+                    let width_mm = descriptor[12] as u32 | high(descriptor[14]);
+                    let height_mm = descriptor[13] as u32 | low(descriptor[14]);
+                    if width_mm > 0 && height_mm > 0 {
+                        edid.size_mm = Some((width_mm, height_mm));
+                    }
+                }
+            } else if descriptor[3] == 0xfc {
+                // Monitor name, ended by a line feed
+                let text = &descriptor[5..18];
+                let end = text.iter().position(|&c| c == 0x0a).unwrap_or(text.len());
+                edid.name = Some(String::from_utf8_lossy(&text[..end]).trim().to_string()).filter(|name| !name.is_empty());
+            }
+        }
+        // Screen size in centimeters, when there is no timing with the size in millimeters
+        if edid.size_mm.is_none() && raw[21] > 0 && raw[22] > 0 {
+            edid.size_mm = Some((raw[21] as u32 * 10, raw[22] as u32 * 10));
+        }
+        Some(edid)
+    }
+}
+
+/// Resolution of a mode like 1920x1080 or 1920x1080i
+fn parse_mode(mode: &str) -> Option<(u32, u32)> {
+    let (width, height) = mode.split_once('x')?;
+    let height = height.trim_end_matches(|c: char| !c.is_ascii_digit());
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+fn display(connector: String, dir: &Path) -> Display {
+    let edid = fs::read(dir.join("edid")).ok()
+        .and_then(|raw| Edid::parse(&raw))
+        .unwrap_or_default();
+    // The modes are listed with the preferred one first
+    let mode = read_text(&dir.join("modes"))
+        .and_then(|modes| modes.lines().next().and_then(parse_mode));
+    let (width, height) = mode
+        .or(edid.timing.map(|(width, height, _)| (width, height)))
+        .unwrap_or_default();
+    // card0-HDMI-A-1 is the HDMI-A connector 1 of card0
+    let connector_type = connector.split_once('-')
+        .and_then(|(_, name)| name.rsplit_once('-'))
+        .map(|(connector_type, _)| connector_type.to_string())
+        .unwrap_or_default();
+    Display {
+        connector_type,
+        connector,
+        enabled: read_text(&dir.join("enabled")).as_deref() == Some("enabled"),
+        name: edid.name,
+        manufacturer: edid.manufacturer,
+        width,
+        height,
+        refresh_rate: edid.timing
+            .filter(|(timing_width, timing_height, _)| (*timing_width, *timing_height) == (width, height))
+            .map(|(_, _, refresh_rate)| refresh_rate),
+        width_mm: edid.size_mm.map(|(width, _)| width),
+        height_mm: edid.size_mm.map(|(_, height)| height),
+    }
+}
+
+/// Monitors connected to the DRM outputs of the graphic cards
+pub fn displays() -> Result<Vec<Display>> {
+    let mut displays = vec![];
+    for (name, dir) in class_entries(DRM_PATH, "card")? {
+        // Connectors are named after their card, like card0-DP-1
+        if !name.contains('-') || read_text(&dir.join("status")).as_deref() != Some("connected") {
+            continue;
+        }
+        displays.push(display(name, &dir));
+    }
+    Ok(displays)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// EDID of a 27" 1920x1080 60Hz monitor made by Dell
+    fn edid() -> Vec<u8> {
+        let mut raw = vec![0; 128];
+        raw[..8].copy_from_slice(&EDID_HEADER);
+        // D E L as 5 bits letters
+        raw[8..10].copy_from_slice(&((4u16 << 10) | (5 << 5) | 12).to_be_bytes());
+        raw[21] = 60;
+        raw[22] = 34;
+        // Preferred timing: 148.5MHz, 1920 + 280 blank x 1080 + 45 blank, 527x296mm
+        raw[54..72].copy_from_slice(&[0x02, 0x3a, 0x80, 0x18, 0x71, 0x38, 0x2d, 0x40, 0x58, 0x2c, 0x45, 0x00,
+            0x0f, 0x28, 0x21, 0x00, 0x00, 0x1e]);
+        raw[72..90].copy_from_slice(&[0x00, 0x00, 0x00, 0xfc, 0x00, b'D', b'E', b'L', b'L', b' ', b'U', b'2',
+            b'7', b'2', b'0', b'Q', 0x0a, b' ']);
+        raw
+    }
+
+    #[test]
+    fn edid_base_block() {
+        let edid = Edid::parse(&edid()).unwrap();
+        assert_eq!(edid.manufacturer.as_deref(), Some("DEL"));
+        assert_eq!(edid.name.as_deref(), Some("DELL U2720Q"));
+        assert_eq!(edid.timing, Some((1920, 1080, 60.0)));
+        assert_eq!(edid.size_mm, Some((527, 296)));
+    }
+
+    #[test]
+    fn edid_size_in_centimeters_without_timing() {
+        let mut raw = edid();
+        raw[54..72].fill(0);
+        let edid = Edid::parse(&raw).unwrap();
+        assert_eq!(edid.timing, None);
+        assert_eq!(edid.size_mm, Some((600, 340)));
+    }
+
+    #[test]
+    fn invalid_edid() {
+        assert!(Edid::parse(&edid()[..100]).is_none());
+        let mut raw = edid();
+        raw[0] = 0xff;
+        assert!(Edid::parse(&raw).is_none());
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(parse_mode("1920x1080"), Some((1920, 1080)));
+        assert_eq!(parse_mode("1920x1080i"), Some((1920, 1080)));
+        assert_eq!(parse_mode("preferred"), None);
+    }
+}
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use std::ffi::{CString, c_void};
+
+/// Shared library loaded at runtime with dlopen, so the crate does not need it to build or run. It is closed when
+/// dropped
+pub struct Library(*mut c_void);
+
+impl Library {
+    pub fn open(name: &str) -> Result<Library> {
+        let c_name = CString::new(name)?;
+        let handle = unsafe { libc::dlopen(c_name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            Err(anyhow::anyhow!("Cannot load {}", name))
+        } else {
+            Ok(Library(handle))
+        }
+    }
+
+    /// Function of the library
+    ///
+    /// # Safety
+    /// `T` must be an `extern "C" fn` type matching the C declaration of the function
+    pub unsafe fn function<T: Copy>(&self, name: &str) -> Option<T> {
+        let name = CString::new(name).ok()?;
+        let symbol = libc::dlsym(self.0, name.as_ptr());
+        if symbol.is_null() {
+            None
+        } else {
+            Some(std::mem::transmute_copy(&symbol))
+        }
+    }
+}
+
+impl Drop for Library {
+    fn drop(&mut self) {
+        unsafe { libc::dlclose(self.0) };
+    }
+}
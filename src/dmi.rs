@@ -0,0 +1,97 @@
+// On Windows the SMBIOS values are read from the registry instead, by windows.rs
+#![cfg_attr(windows, allow(dead_code))]
+use std::fs;
+use crate::model::{HardwareIdentity, FirmwareInfo, Baseboard};
+use crate::sensors::read_text;
+use crate::host;
+
+const DMI_PATH: &str = "/sys/class/dmi/id";
+const EFI_PATH: &str = "/sys/firmware/efi";
+const SECURE_BOOT_VARIABLE: &str = "/sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c";
+// Values left by vendors that did not fill the SMBIOS tables
+pub const PLACEHOLDERS: [&str; 7] = [
+    "To Be Filled By O.E.M.",
+    "To be filled by O.E.M.",
+    "Default string",
+    "System Product Name",
+    "System manufacturer",
+    "Not Specified",
+    "None",
+];
+
+/// SMBIOS chassis type names (DSP0134, 7.4.1)
+const CHASSIS_TYPES: [&str; 36] = [
+    "Other", "Unknown", "Desktop", "Low Profile Desktop", "Pizza Box", "Mini Tower", "Tower", "Portable",
+    "Laptop", "Notebook", "Hand Held", "Docking Station", "All in One", "Sub Notebook", "Space-saving",
+    "Lunch Box", "Main Server Chassis", "Expansion Chassis", "SubChassis", "Bus Expansion Chassis",
+    "Peripheral Chassis", "RAID Chassis", "Rack Mount Chassis", "Sealed-case PC", "Multi-system chassis",
+    "Compact PCI", "Advanced TCA", "Blade", "Blade Enclosure", "Tablet", "Convertible", "Detachable",
+    "IoT Gateway", "Embedded PC", "Mini PC", "Stick PC",
+];
+
+/// Value of a DMI attribute. Placeholders are ignored. Serials and UUID can only be read by root
+pub fn attribute(name: &str) -> Option<String> {
+    read_text(&host::path(DMI_PATH).join(name))
+        .filter(|value| !PLACEHOLDERS.contains(&value.as_str()))
+}
+
+fn chassis_type() -> Option<String> {
+    let code = attribute("chassis_type")?.parse::<usize>().ok()?;
+    CHASSIS_TYPES.get(code.checked_sub(1)?).map(|name| name.to_string())
+}
+
+/// Secure Boot state from its EFI variable. The content is 4 bytes of attributes followed by the value
+fn secure_boot() -> Option<bool> {
+    let raw = fs::read(host::path(SECURE_BOOT_VARIABLE)).ok()?;
+    raw.get(4).map(|value| *value == 1)
+}
+
+fn firmware() -> FirmwareInfo {
+    let uefi = host::path(EFI_PATH).exists();
+    FirmwareInfo {
+        vendor: attribute("bios_vendor"),
+        version: attribute("bios_version"),
+        release_date: attribute("bios_date"),
+        uefi,
+        secure_boot: if uefi { secure_boot() } else { Some(false) },
+    }
+}
+
+fn baseboard() -> Option<Baseboard> {
+    let baseboard = Baseboard {
+        manufacturer: attribute("board_vendor"),
+        product: attribute("board_name"),
+        version: attribute("board_version"),
+        serial: attribute("board_serial"),
+    };
+    if baseboard.manufacturer.is_none() && baseboard.product.is_none() {
+        None
+    } else {
+        Some(baseboard)
+    }
+}
+
+/// Hardware identity from the SMBIOS values copied to the registry by Windows
+#[cfg(windows)]
+pub fn hardware_identity() -> Option<HardwareIdentity> {
+    crate::windows::hardware_identity()
+}
+
+/// Hardware identity from the DMI (SMBIOS) tables exposed by the kernel. None if the machine has no
+/// DMI, like most ARM boards
+#[cfg(not(windows))]
+pub fn hardware_identity() -> Option<HardwareIdentity> {
+    if !host::path(DMI_PATH).exists() {
+        return None;
+    }
+    Some(HardwareIdentity {
+        vendor: attribute("sys_vendor"),
+        product_name: attribute("product_name"),
+        product_version: attribute("product_version"),
+        chassis_type: chassis_type(),
+        serial: attribute("product_serial"),
+        uuid: attribute("product_uuid"),
+        firmware: firmware(),
+        baseboard: baseboard(),
+    })
+}
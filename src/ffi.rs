@@ -0,0 +1,177 @@
+//! C ABI of the crate. Enable it with the `ffi` feature and build the crate as a `cdylib`:
+//!
+//! ```text
+//! cargo rustc --lib --release --crate-type cdylib --features ffi
+//! ```
+//!
+//! The declarations are in `include/machine_info.h`, generated with `cbindgen --config cbindgen.toml --output
+//! include/machine_info.h src/ffi.rs`. The readings are returned as JSON strings with the same
+//! format as the serde serialization of the models. Every string returned must be released with
+//! `machine_string_free` and the machine with `machine_free`. Functions returning a string return NULL on error,
+//! and the ones returning an int return 0 on success and -1 on error.
+//!
+//! Example
+//! ```c
+//! #include "machine_info.h"
+//!
+//! machine_t *machine = machine_new();
+//! char *info = machine_system_info_json(machine);
+//! printf("%s\n", info);
+//! machine_string_free(info);
+//! machine_free(machine);
+//! ```
+use anyhow::Result;
+use log::debug;
+use serde::Serialize;
+use std::ffi::{CString, c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use crate::Machine;
+
+/// JSON of a reading as a C string. Errors and panics are logged and returned as NULL
+fn json<T: Serialize>(read: impl FnOnce() -> Result<T>) -> *mut c_char {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<CString> {
+        Ok(CString::new(serde_json::to_string(&read()?)?)?)
+    }));
+    match result {
+        Ok(Ok(json)) => json.into_raw(),
+        Ok(Err(e)) => {
+            debug!("FFI call failed: {}", e);
+            std::ptr::null_mut()
+        },
+        Err(_) => {
+            debug!("FFI call panicked");
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// 0 on success and -1 on error. Errors and panics are logged
+fn status(call: impl FnOnce() -> Result<()>) -> c_int {
+    match panic::catch_unwind(AssertUnwindSafe(call)) {
+        Ok(Ok(())) => 0,
+        Ok(Err(e)) => {
+            debug!("FFI call failed: {}", e);
+            -1
+        },
+        Err(_) => {
+            debug!("FFI call panicked");
+            -1
+        }
+    }
+}
+
+/// Creates a machine with the default settings. Release it with `machine_free`
+#[no_mangle]
+pub extern "C" fn machine_new() -> *mut Machine {
+    match panic::catch_unwind(Machine::new) {
+        Ok(machine) => Box::into_raw(Box::new(machine)),
+        Err(_) => std::ptr::null_mut()
+    }
+}
+
+/// Releases a machine created by `machine_new`
+///
+/// # Safety
+/// `machine` must be NULL or a pointer returned by `machine_new` not released yet
+#[no_mangle]
+pub unsafe extern "C" fn machine_free(machine: *mut Machine) {
+    if !machine.is_null() {
+        drop(Box::from_raw(machine));
+    }
+}
+
+/// Releases a string returned by this library
+///
+/// # Safety
+/// `value` must be NULL or a string returned by this library not released yet
+#[no_mangle]
+pub unsafe extern "C" fn machine_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// `Machine::system_info` as JSON
+///
+/// # Safety
+/// `machine` must be a valid pointer returned by `machine_new`
+#[no_mangle]
+pub unsafe extern "C" fn machine_system_info_json(machine: *const Machine) -> *mut c_char {
+    match machine.as_ref() {
+        Some(machine) => json(|| Ok(machine.system_info())),
+        None => std::ptr::null_mut()
+    }
+}
+
+/// `Machine::system_status` as JSON
+///
+/// # Safety
+/// `machine` must be a valid pointer returned by `machine_new`
+#[no_mangle]
+pub unsafe extern "C" fn machine_system_status_json(machine: *const Machine) -> *mut c_char {
+    match machine.as_ref() {
+        Some(machine) => json(|| machine.system_status()),
+        None => std::ptr::null_mut()
+    }
+}
+
+/// `Machine::graphics_status` as JSON
+///
+/// # Safety
+/// `machine` must be a valid pointer returned by `machine_new`
+#[no_mangle]
+pub unsafe extern "C" fn machine_graphics_status_json(machine: *const Machine) -> *mut c_char {
+    match machine.as_ref() {
+        Some(machine) => json(|| Ok(machine.graphics_status())),
+        None => std::ptr::null_mut()
+    }
+}
+
+/// `Machine::processes_status` as JSON
+///
+/// # Safety
+/// `machine` must be a valid pointer returned by `machine_new`
+#[no_mangle]
+pub unsafe extern "C" fn machine_processes_status_json(machine: *const Machine) -> *mut c_char {
+    match machine.as_ref() {
+        Some(machine) => json(|| Ok(machine.processes_status())),
+        None => std::ptr::null_mut()
+    }
+}
+
+/// `Machine::snapshot` as JSON
+///
+/// # Safety
+/// `machine` must be a valid pointer returned by `machine_new`
+#[no_mangle]
+pub unsafe extern "C" fn machine_snapshot_json(machine: *const Machine) -> *mut c_char {
+    match machine.as_ref() {
+        Some(machine) => json(|| machine.snapshot()),
+        None => std::ptr::null_mut()
+    }
+}
+
+/// Starts tracking a process, see `Machine::track_process`
+///
+/// # Safety
+/// `machine` must be a valid pointer returned by `machine_new`
+#[no_mangle]
+pub unsafe extern "C" fn machine_track_process(machine: *const Machine, pid: c_int) -> c_int {
+    match machine.as_ref() {
+        Some(machine) => status(|| machine.track_process(pid)),
+        None => -1
+    }
+}
+
+/// Stops tracking a process
+///
+/// # Safety
+/// `machine` must be a valid pointer returned by `machine_new`
+#[no_mangle]
+pub unsafe extern "C" fn machine_untrack_process(machine: *const Machine, pid: c_int) {
+    if let Some(machine) = machine.as_ref() {
+        if panic::catch_unwind(AssertUnwindSafe(|| machine.untrack_process(pid))).is_err() {
+            debug!("FFI call panicked");
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+use crate::model::{SystemInfo, FleetValue, GpuFleetReport};
+
+/// Groups the hosts by value. Values are sorted to get a stable report
+fn group<F>(snapshots: &[SystemInfo], value: F) -> Vec<FleetValue>
+where F: Fn(&SystemInfo) -> String {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for snapshot in snapshots {
+        groups.entry(value(snapshot)).or_default().push(snapshot.hostname.clone());
+    }
+    groups.into_iter().map(|(value, hosts)| FleetValue { value, hosts }).collect()
+}
+
+fn gpus(snapshot: &SystemInfo) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for card in &snapshot.graphics {
+        let name = if card.is_read("name") { card.name.as_str() } else { "Unknown" };
+        *counts.entry(name).or_default() += 1;
+    }
+    if counts.is_empty() {
+        return "None".to_string();
+    }
+    counts.iter()
+        .map(|(name, count)| format!("{} x {}", count, name))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// CUDA version comes as an integer like 12020 (12.2)
+fn cuda_version(version: i32) -> String {
+    format!("{}.{}", version / 1000, (version % 1000) / 10)
+}
+
+impl GpuFleetReport {
+    /// Compares the graphic cards, drivers and CUDA versions of several machines. Snapshots can be
+    /// local or deserialized from other machines
+    /// Example
+    /// ```
+    /// use machine_info::{Machine, GpuFleetReport};
+    /// let m = Machine::new();
+    /// let snapshots = vec![m.system_info(), m.system_info()];
+    /// let report = GpuFleetReport::from_snapshots(&snapshots);
+    /// assert!(report.homogeneous);
+    /// ```
+    pub fn from_snapshots(snapshots: &[SystemInfo]) -> GpuFleetReport {
+        let gpus = group(snapshots, gpus);
+        let driver_versions = group(snapshots, |s| match &s.nvidia {
+            Some(nvidia) => nvidia.driver_version.clone(),
+            None => "None".to_string()
+        });
+        let cuda_versions = group(snapshots, |s| match &s.nvidia {
+            Some(nvidia) => cuda_version(nvidia.cuda_version),
+            None => "None".to_string()
+        });
+        GpuFleetReport {
+            homogeneous: gpus.len() <= 1 && driver_versions.len() <= 1 && cuda_versions.len() <= 1,
+            gpus,
+            driver_versions,
+            cuda_versions,
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Nvidia driver events. A thread waits for the events of the cards and delivers them to the subscribers
+use anyhow::Result;
+use std::sync::{Arc, Mutex, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use crate::model::{GpuEvent, GpuEventKind};
+#[cfg(target_os = "linux")]
+use nvml_wrapper::{Nvml, bitmasks::event::EventTypes, enums::event::XidError, error::NvmlError};
+#[cfg(target_os = "linux")]
+use log::debug;
+#[cfg(target_os = "linux")]
+use std::time::UNIX_EPOCH;
+#[cfg(target_os = "linux")]
+use crate::host;
+
+// Time waited for an event before checking if the listener was stopped
+#[cfg(target_os = "linux")]
+const WAIT_MS: u32 = 500;
+
+/// Events kept for a subscriber that is not receiving them. Newer events are dropped when it is full
+const SUBSCRIBER_BUFFER: usize = 64;
+
+type Callback = Box<dyn Fn(&GpuEvent) + Send>;
+
+/// Subscribers of the events, channels and callbacks
+#[derive(Default)]
+struct Subscribers {
+    channels: Vec<mpsc::SyncSender<GpuEvent>>,
+    callbacks: Vec<Callback>,
+}
+
+/// Sends the events to the channels and calls the callbacks. The callbacks run out of the lock, so a slow one
+/// does not block `subscribe` or `add_callback`, which can also be called from a callback
+#[cfg(target_os = "linux")]
+fn deliver(subscribers: &Mutex<Subscribers>, events: &[GpuEvent]) {
+    let callbacks = {
+        let mut subscribers = subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        // Subscribers that dropped their receiver are removed, the ones that are full miss the events
+        for event in events {
+            subscribers.channels.retain(|channel| !matches!(channel.try_send(event.clone()),
+                Err(mpsc::TrySendError::Disconnected(_))));
+        }
+        std::mem::take(&mut subscribers.callbacks)
+    };
+    for event in events {
+        for callback in &callbacks {
+            callback(event);
+        }
+    }
+    let mut subscribers = subscribers.lock().unwrap_or_else(|e| e.into_inner());
+    let added = std::mem::replace(&mut subscribers.callbacks, callbacks);
+    subscribers.callbacks.extend(added);
+}
+
+#[cfg(target_os = "linux")]
+fn event_types(kind: GpuEventKind) -> EventTypes {
+    match kind {
+        GpuEventKind::XidError => EventTypes::CRITICAL_XID_ERROR,
+        GpuEventKind::SingleBitEccError => EventTypes::SINGLE_BIT_ECC_ERROR,
+        GpuEventKind::DoubleBitEccError => EventTypes::DOUBLE_BIT_ECC_ERROR,
+        GpuEventKind::PstateChange => EventTypes::PSTATE_CHANGE,
+        GpuEventKind::ClockChange => EventTypes::CLOCK_CHANGE,
+        GpuEventKind::PowerSourceChange => EventTypes::POWER_SOURCE_CHANGE,
+    }
+}
+
+/// Kinds of a received event. The driver can merge several events in one
+#[cfg(target_os = "linux")]
+fn event_kinds(types: EventTypes, kinds: &[GpuEventKind]) -> Vec<GpuEventKind> {
+    kinds.iter().copied().filter(|kind| types.intersects(event_types(*kind))).collect()
+}
+
+/// Listens to the events of the Nvidia cards (Xid errors, ECC errors, clock changes...) in a background thread, so
+/// the faults are caught the moment they happen instead of polling. The thread stops when the listener is dropped.
+/// Only available on Linux, the driver does not support events on Windows
+/// Example
+/// ```no_run
+/// use machine_info::{GpuEvents, GpuEventKind};
+///
+/// let events = GpuEvents::start(&[GpuEventKind::XidError, GpuEventKind::DoubleBitEccError]).unwrap();
+/// events.add_callback(|event| eprintln!("GPU {} failed: {:?} {:?}", event.id, event.kind, event.xid));
+/// for event in events.subscribe() {
+///     println!("{:?}", event);
+/// }
+/// ```
+pub struct GpuEvents {
+    subscribers: Arc<Mutex<Subscribers>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl GpuEvents {
+    /// Starts listening to the given kinds of events on all the cards supporting them. Fails if NVML is not
+    /// available or no card supports any of the kinds
+    pub fn start(kinds: &[GpuEventKind]) -> Result<GpuEvents> {
+        let subscribers = Arc::new(Mutex::new(Subscribers::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        // The event set borrows NVML, so both live in the thread, which reports if the registration worked
+        let (ready, registered) = mpsc::channel::<Result<()>>();
+        let thread = {
+            let subscribers = subscribers.clone();
+            let stop = stop.clone();
+            let kinds = kinds.to_vec();
+            thread::spawn(move || listen(kinds, subscribers, stop, ready))
+        };
+        let events = GpuEvents {
+            subscribers,
+            stop,
+            thread: Some(thread),
+        };
+        registered.recv().unwrap_or_else(|_| Err(anyhow::anyhow!("GPU events thread failed")))?;
+        Ok(events)
+    }
+
+    /// New channel receiving every event from now on. Up to 64 events wait to be received, the ones arriving
+    /// while it is full are dropped
+    pub fn subscribe(&self) -> mpsc::Receiver<GpuEvent> {
+        let (sender, receiver) = mpsc::sync_channel(SUBSCRIBER_BUFFER);
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner()).channels.push(sender);
+        receiver
+    }
+
+    /// Calls the function for every event from now on, in the listener thread
+    pub fn add_callback<F: Fn(&GpuEvent) + Send + 'static>(&self, callback: F) {
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner()).callbacks.push(Box::new(callback));
+    }
+
+    /// Stops listening and waits for the thread to finish. The channels of the subscribers are closed
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for GpuEvents {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn listen(kinds: Vec<GpuEventKind>, subscribers: Arc<Mutex<Subscribers>>, stop: Arc<AtomicBool>, ready: mpsc::Sender<Result<()>>) {
+    if crate::machine::nvml_disabled_by_env() {
+        let _ = ready.send(Err(anyhow::anyhow!("Nvidia disabled by the environment")));
+        return;
+    }
+    let nvml = match Nvml::init() {
+        Ok(nvml) => nvml,
+        Err(e) => {
+            let _ = ready.send(Err(anyhow::anyhow!("Nvidia not available because {}", e)));
+            return;
+        }
+    };
+    let requested = kinds.iter().fold(EventTypes::empty(), |types, kind| types | event_types(*kind));
+    let mut cards = vec![];
+    for n in 0..nvml.device_count().unwrap_or(0) {
+        match nvml.device_by_index(n) {
+            Ok(device) => {
+                let types = device.supported_event_types().unwrap_or(EventTypes::empty()) & requested;
+                if types.is_empty() {
+                    debug!("NVIDIA device {} does not support the requested events", n);
+                } else {
+                    cards.push((n, device, types));
+                }
+            },
+            Err(e) => debug!("Failed to get NVIDIA device at index {} for events: {}", n, e)
+        }
+    }
+    // A failed registration releases the set, so the set is created again without that card
+    let set = 'register: loop {
+        if cards.is_empty() {
+            let _ = ready.send(Err(anyhow::anyhow!("No Nvidia card supports the requested events")));
+            return;
+        }
+        let mut set = match nvml.create_event_set() {
+            Ok(set) => set,
+            Err(e) => {
+                let _ = ready.send(Err(e.into()));
+                return;
+            }
+        };
+        for index in 0..cards.len() {
+            let (n, device, types) = &cards[index];
+            set = match device.register_events(*types, set) {
+                Ok(set) => set,
+                Err(e) => {
+                    debug!("Cannot register the events of NVIDIA device {}: {}", n, e.error);
+                    cards.remove(index);
+                    continue 'register;
+                }
+            };
+        }
+        break set;
+    };
+    let _ = ready.send(Ok(()));
+
+    while !stop.load(Ordering::Relaxed) {
+        let data = match set.wait(WAIT_MS) {
+            Ok(data) => data,
+            Err(NvmlError::Timeout) => continue,
+            Err(e) => {
+                debug!("Cannot wait for NVIDIA events: {}", e);
+                break;
+            }
+        };
+        let id = data.device.uuid().unwrap_or_default();
+        let xid = match data.event_data {
+            Some(XidError::Value(xid)) => Some(xid),
+            _ => None
+        };
+        let timestamp = host::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let events = event_kinds(data.event_type, &kinds).into_iter()
+            .map(|kind| GpuEvent {
+                id: id.clone(),
+                kind,
+                xid: if kind == GpuEventKind::XidError { xid } else { None },
+                timestamp,
+            })
+            .collect::<Vec<GpuEvent>>();
+        deliver(&subscribers, &events);
+    }
+    if let Err(e) = set.release_events() {
+        debug!("Cannot release the NVIDIA event set: {}", e);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn listen(_kinds: Vec<GpuEventKind>, _subscribers: Arc<Mutex<Subscribers>>, _stop: Arc<AtomicBool>, ready: mpsc::Sender<Result<()>>) {
+    let _ = ready.send(Err(anyhow::anyhow!("GPU events are only supported on Linux")));
+}
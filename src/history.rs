@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::model::{SystemStatus, GraphicsUsage, HistorySummary, GraphicsHistory, Statistics};
+use crate::host;
+
+struct StatusEntry {
+    when: Instant,
+    cpu: f64,
+    memory: f64,
+}
+
+struct GraphicsEntry {
+    when: Instant,
+    id: String,
+    // None if the field could not be read
+    gpu: Option<f64>,
+    memory_usage: Option<f64>,
+    temperature: Option<f64>,
+}
+
+/// Rolling history of the sampled values. Each buffer keeps the last `capacity` samples
+pub struct History {
+    capacity: usize,
+    statuses: VecDeque<StatusEntry>,
+    graphics: VecDeque<GraphicsEntry>,
+}
+
+fn push<T>(buffer: &mut VecDeque<T>, capacity: usize, entry: T) {
+    if buffer.len() == capacity {
+        buffer.pop_front();
+    }
+    buffer.push_back(entry);
+}
+
+/// Minimum, maximum and average. None if there are no values
+fn statistics(values: impl Iterator<Item = f64>) -> Option<Statistics> {
+    let mut result: Option<Statistics> = None;
+    let mut count = 0;
+    for value in values {
+        count += 1;
+        let current = result.get_or_insert(Statistics { min: value, max: value, average: 0.0 });
+        current.min = current.min.min(value);
+        current.max = current.max.max(value);
+        current.average += value;
+    }
+    result.map(|result| Statistics { average: result.average / count as f64, ..result })
+}
+
+impl History {
+    pub fn new(capacity: usize) -> History {
+        History {
+            capacity: capacity.max(1),
+            statuses: VecDeque::new(),
+            graphics: VecDeque::new(),
+        }
+    }
+
+    pub fn record_status(&mut self, status: &SystemStatus) {
+        let memory = &status.memory_breakdown;
+        push(&mut self.statuses, self.capacity, StatusEntry {
+            when: host::instant(),
+            cpu: status.cpu as f64,
+            memory: if memory.total > 0 { memory.used as f64 * 100.0 / memory.total as f64 } else { 0.0 },
+        });
+    }
+
+    /// Records the cards. The capacity is per card
+    pub fn record_graphics(&mut self, cards: &[GraphicsUsage]) {
+        let when = host::instant();
+        for card in cards {
+            push(&mut self.graphics, self.capacity * cards.len().max(1), GraphicsEntry {
+                when,
+                id: card.id.clone(),
+                gpu: card.is_read("utilization").then_some(card.gpu as f64),
+                memory_usage: card.is_read("utilization").then_some(card.memory_usage as f64),
+                temperature: card.is_read("temperature").then_some(card.temperature as f64),
+            });
+        }
+    }
+
+    /// Statistics of the samples taken during the last `window`. None if there are no status samples in it
+    pub fn summary(&self, window: Duration) -> Option<HistorySummary> {
+        // A window longer than the uptime of the monotonic clock includes every sample
+        let since = host::instant().checked_sub(window);
+        let in_window = |when: &Instant| since.is_none_or(|since| *when >= since);
+        let statuses = self.statuses.iter().filter(|entry| in_window(&entry.when)).collect::<Vec<&StatusEntry>>();
+        let entries = self.graphics.iter().filter(|entry| in_window(&entry.when)).collect::<Vec<&GraphicsEntry>>();
+        let mut ids: Vec<&String> = vec![];
+        for entry in &entries {
+            if !ids.contains(&&entry.id) {
+                ids.push(&entry.id);
+            }
+        }
+        let graphics = ids.into_iter()
+            .map(|id| {
+                let card = entries.iter().filter(|entry| entry.id == *id).collect::<Vec<&&GraphicsEntry>>();
+                GraphicsHistory {
+                    id: id.clone(),
+                    samples: card.len(),
+                    gpu: statistics(card.iter().filter_map(|entry| entry.gpu)),
+                    memory_usage: statistics(card.iter().filter_map(|entry| entry.memory_usage)),
+                    temperature: statistics(card.iter().filter_map(|entry| entry.temperature)),
+                }
+            })
+            .collect();
+        Some(HistorySummary {
+            samples: statuses.len(),
+            cpu: statistics(statuses.iter().map(|entry| entry.cpu))?,
+            memory: statistics(statuses.iter().map(|entry| entry.memory))?,
+            graphics,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use crate::Machine;
+    use crate::testkit::FakeTree;
+    use std::time::Duration;
+
+    #[test]
+    fn summary_of_the_window() {
+        let mut tree = FakeTree::new(1).unwrap();
+        let m = Machine::builder().with_nvml(false).with_history(10).build();
+        m.system_status().unwrap();
+        tree.tick(Duration::from_secs(60), 20).unwrap();
+        m.system_status().unwrap();
+        tree.tick(Duration::from_secs(60), 60).unwrap();
+        m.system_status().unwrap();
+
+        let summary = m.history(Duration::from_secs(90)).unwrap();
+        assert_eq!(summary.samples, 2);
+        assert_eq!((summary.cpu.min, summary.cpu.max, summary.cpu.average), (20.0, 60.0, 40.0));
+        assert_eq!(m.history(Duration::from_secs(3600)).unwrap().samples, 3);
+        tree.tick(Duration::from_secs(120), 0).unwrap();
+        assert!(m.history(Duration::from_secs(90)).is_none());
+    }
+}
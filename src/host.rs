@@ -0,0 +1,49 @@
+//! Access to the host files and clock. With the `testkit` feature both can be replaced by fakes
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime};
+#[cfg(feature = "testkit")]
+use std::sync::RwLock;
+#[cfg(feature = "testkit")]
+use std::sync::atomic::{AtomicU64, AtomicBool, Ordering};
+#[cfg(feature = "testkit")]
+use std::time::Duration;
+
+#[cfg(feature = "testkit")]
+pub(crate) static ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+#[cfg(feature = "testkit")]
+pub(crate) static CLOCK_OFFSET_MS: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "testkit")]
+pub(crate) static GPU_LOST: AtomicBool = AtomicBool::new(false);
+
+/// Path of a host file like /proc/stat
+pub fn path(path: &str) -> PathBuf {
+    #[cfg(feature = "testkit")]
+    if let Some(root) = ROOT.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return root.join(path.trim_start_matches('/'));
+    }
+    PathBuf::from(path)
+}
+
+/// Current time
+pub fn now() -> SystemTime {
+    #[cfg(feature = "testkit")]
+    return SystemTime::now() + Duration::from_millis(CLOCK_OFFSET_MS.load(Ordering::SeqCst));
+    #[cfg(not(feature = "testkit"))]
+    SystemTime::now()
+}
+
+/// Current monotonic time, for intervals that must not jump when the wall clock is corrected
+pub fn instant() -> Instant {
+    #[cfg(feature = "testkit")]
+    return Instant::now() + Duration::from_millis(CLOCK_OFFSET_MS.load(Ordering::SeqCst));
+    #[cfg(not(feature = "testkit"))]
+    Instant::now()
+}
+
+/// False if the GPU was removed by the testkit
+pub fn gpu_available() -> bool {
+    #[cfg(feature = "testkit")]
+    return !GPU_LOST.load(Ordering::SeqCst);
+    #[cfg(not(feature = "testkit"))]
+    true
+}
@@ -0,0 +1,90 @@
+//! Minimal HTTP/1.1 client used to push JSON documents (webhooks). Only plain `http://` urls are supported
+use anyhow::{Result, Context};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use log::debug;
+
+/// Destination of the POST requests
+pub struct HttpEndpoint {
+    host: String,
+    port: u16,
+    path: String,
+    pub timeout: Duration,
+}
+
+impl HttpEndpoint {
+    /// Parses the url. It fails if it is not a valid http url
+    pub fn new(url: &str) -> Result<HttpEndpoint> {
+        let rest = url.strip_prefix("http://")
+            .ok_or_else(|| anyhow::anyhow!("Only http urls are supported").context(url.to_owned()))?;
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (rest, "/")
+        };
+        // IPv6 literals are enclosed in brackets, like [::1]:8080
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(literal) => {
+                let (host, rest) = literal.split_once(']')
+                    .ok_or_else(|| anyhow::anyhow!("Unclosed IPv6 address").context(url.to_owned()))?;
+                match rest {
+                    "" => (host, None),
+                    _ => (host, Some(rest.strip_prefix(':').ok_or_else(|| anyhow::anyhow!("Invalid port").context(url.to_owned()))?))
+                }
+            },
+            None => match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None)
+            }
+        };
+        let port = match port {
+            Some(port) => port.parse::<u16>().context("Invalid port")?,
+            None => 80
+        };
+        if host.is_empty() {
+            return Err(anyhow::anyhow!("Url has no host").context(url.to_owned()));
+        }
+        Ok(HttpEndpoint {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    /// Sends the JSON document. It fails if the server does not answer with a 2xx status
+    pub fn post_json(&self, body: &str) -> Result<()> {
+        let address = (self.host.as_str(), self.port).to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Cannot resolve host {}", self.host))?;
+        let mut stream = TcpStream::connect_timeout(&address, self.timeout)?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.write_all(format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path, self.authority(), body.len(), body
+        ).as_bytes())?;
+
+        // Only the status line matters
+        let mut response = [0u8; 64];
+        let read = stream.read(&mut response)?;
+        let status_line = String::from_utf8_lossy(&response[..read]);
+        let status = status_line.split_whitespace().nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid HTTP response").context(status_line.to_string()))?;
+        debug!("{} answered with status {}", self.host, status);
+        if !(200..300).contains(&status) {
+            return Err(anyhow::anyhow!("Request failed with status {}", status));
+        }
+        Ok(())
+    }
+
+    /// Value of the Host header: the host, in brackets if it is an IPv6 address, and the port unless it is 80
+    fn authority(&self) -> String {
+        let host = if self.host.contains(':') { format!("[{}]", self.host) } else { self.host.clone() };
+        match self.port {
+            80 => host,
+            port => format!("{}:{}", host, port)
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Baseboard management controller (BMC) sensors. Enable it with the `ipmi` feature.
+//!
+//! The BMC is queried in-band through the OpenIPMI driver (`/dev/ipmi0`, modules `ipmi_si` and `ipmi_devintf`),
+//! like `ipmitool` does without a network session. The sensors are found in the repository (SDR) and the system
+//! power draw is read with DCMI. Opening the device usually requires root permissions
+use anyhow::{anyhow, Result};
+use log::debug;
+use std::fs::File;
+use std::mem::size_of;
+use std::os::unix::io::AsRawFd;
+use crate::Machine;
+use crate::model::{BmcStatus, BmcSensor, BmcPowerSupply};
+use crate::host;
+
+const DEVICE_PATHS: [&str; 3] = ["/dev/ipmi0", "/dev/ipmi/0", "/dev/ipmidev/0"];
+const RESPONSE_TIMEOUT_MS: i32 = 5000;
+const SYSTEM_INTERFACE_ADDR_TYPE: i32 = 0x0c;
+const BMC_CHANNEL: i16 = 0x0f;
+const BMC_ADDRESS: u8 = 0x20;
+const MAX_SDR_RECORDS: usize = 1024;
+const SDR_CHUNK: usize = 16;
+
+// Network functions and commands (IPMI 2.0 and DCMI 1.5)
+const NETFN_SENSOR: u8 = 0x04;
+const NETFN_STORAGE: u8 = 0x0a;
+const NETFN_GROUP: u8 = 0x2c;
+const GET_SENSOR_READING: u8 = 0x2d;
+const RESERVE_SDR_REPOSITORY: u8 = 0x22;
+const GET_SDR: u8 = 0x23;
+const DCMI_GET_POWER_READING: u8 = 0x02;
+const DCMI_GROUP: u8 = 0xdc;
+
+// SDR record types, sensor types and entities
+const FULL_SENSOR_RECORD: u8 = 0x01;
+const COMPACT_SENSOR_RECORD: u8 = 0x02;
+const SENSOR_TEMPERATURE: u8 = 0x01;
+const SENSOR_POWER_SUPPLY: u8 = 0x08;
+const ENTITY_AIR_INLETS: [u8; 2] = [0x37, 0x40];
+const UNIT_WATTS: u8 = 6;
+
+/// struct ipmi_system_interface_addr
+#[repr(C)]
+struct SystemInterfaceAddr {
+    addr_type: i32,
+    channel: i16,
+    lun: u8,
+}
+
+/// struct ipmi_msg
+#[repr(C)]
+struct Message {
+    netfn: u8,
+    cmd: u8,
+    data_len: u16,
+    data: *mut u8,
+}
+
+/// struct ipmi_req
+#[repr(C)]
+struct Request {
+    addr: *mut SystemInterfaceAddr,
+    addr_len: u32,
+    msgid: libc::c_long,
+    msg: Message,
+}
+
+/// struct ipmi_recv
+#[repr(C)]
+struct Received {
+    recv_type: i32,
+    addr: *mut u8,
+    addr_len: u32,
+    msgid: libc::c_long,
+    msg: Message,
+}
+
+/// _IOR('i', 13, struct ipmi_req)
+const IPMICTL_SEND_COMMAND: u64 = (2 << 30) | ((size_of::<Request>() as u64) << 16) | ((b'i' as u64) << 8) | 13;
+/// _IOWR('i', 11, struct ipmi_recv)
+const IPMICTL_RECEIVE_MSG_TRUNC: u64 = (3 << 30) | ((size_of::<Received>() as u64) << 16) | ((b'i' as u64) << 8) | 11;
+
+/// Opened OpenIPMI device
+struct Bmc {
+    file: File,
+    msgid: libc::c_long,
+}
+
+impl Bmc {
+    fn open() -> Result<Bmc> {
+        let mut last_error = None;
+        for path in DEVICE_PATHS {
+            match File::options().read(true).write(true).open(host::path(path)) {
+                Ok(file) => return Ok(Bmc { file, msgid: 0 }),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(anyhow!("Cannot open the IPMI device (are ipmi_si and ipmi_devintf loaded?): {:?}", last_error))
+    }
+
+    /// Sends a command to the BMC and waits for the response. The completion code is checked and removed
+    fn command(&mut self, netfn: u8, cmd: u8, data: &[u8]) -> Result<Vec<u8>> {
+        let fd = self.file.as_raw_fd();
+        let mut addr = SystemInterfaceAddr { addr_type: SYSTEM_INTERFACE_ADDR_TYPE, channel: BMC_CHANNEL, lun: 0 };
+        let mut data = data.to_vec();
+        self.msgid += 1;
+        let mut request = Request {
+            addr: &mut addr,
+            addr_len: size_of::<SystemInterfaceAddr>() as u32,
+            msgid: self.msgid,
+            msg: Message { netfn, cmd, data_len: data.len() as u16, data: data.as_mut_ptr() },
+        };
+        if unsafe { libc::ioctl(fd, IPMICTL_SEND_COMMAND as _, &mut request) } < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        loop {
+            let mut poll = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+            match unsafe { libc::poll(&mut poll, 1, RESPONSE_TIMEOUT_MS) } {
+                0 => return Err(anyhow!("The BMC did not answer the command {:#04x}/{:#04x}", netfn, cmd)),
+                ready if ready < 0 => return Err(std::io::Error::last_os_error().into()),
+                _ => {}
+            }
+            // Big enough for any address type and message
+            let mut response_addr = [0u8; 64];
+            let mut response = vec![0u8; 1024];
+            let mut received = Received {
+                recv_type: 0,
+                addr: response_addr.as_mut_ptr(),
+                addr_len: response_addr.len() as u32,
+                msgid: 0,
+                msg: Message { netfn: 0, cmd: 0, data_len: response.len() as u16, data: response.as_mut_ptr() },
+            };
+            if unsafe { libc::ioctl(fd, IPMICTL_RECEIVE_MSG_TRUNC as _, &mut received) } < 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            // Late responses of commands that timed out before
+            if received.msgid != self.msgid {
+                continue;
+            }
+            response.truncate(received.msg.data_len as usize);
+            return match response.first() {
+                Some(0) => Ok(response.split_off(1)),
+                Some(code) => Err(anyhow!("The command {:#04x}/{:#04x} failed with {:#04x}", netfn, cmd, code)),
+                None => Err(anyhow!("Empty response to the command {:#04x}/{:#04x}", netfn, cmd)),
+            };
+        }
+    }
+
+    /// Raw records of the sensor data repository
+    fn sdr_records(&mut self) -> Result<Vec<Vec<u8>>> {
+        let reservation = self.command(NETFN_STORAGE, RESERVE_SDR_REPOSITORY, &[])?;
+        if reservation.len() < 2 {
+            return Err(anyhow!("Invalid SDR reservation"));
+        }
+        let mut records = vec![];
+        let mut id: u16 = 0;
+        while id != 0xffff && records.len() < MAX_SDR_RECORDS {
+            let [id_low, id_high] = id.to_le_bytes();
+            // The header (5 bytes) first, then the body in small chunks because many BMCs cannot send it at once
+            let header = self.command(NETFN_STORAGE, GET_SDR, &[reservation[0], reservation[1], id_low, id_high, 0, 5])?;
+            if header.len() < 7 {
+                break;
+            }
+            let next = u16::from_le_bytes([header[0], header[1]]);
+            let mut record = header[2..].to_vec();
+            let length = 5 + record[4] as usize;
+            while record.len() < length {
+                let offset = record.len();
+                let chunk = (length - offset).min(SDR_CHUNK);
+                let part = self.command(NETFN_STORAGE, GET_SDR,
+                    &[reservation[0], reservation[1], id_low, id_high, offset as u8, chunk as u8])?;
+                if part.len() <= 2 {
+                    break;
+                }
+                record.extend_from_slice(&part[2..]);
+            }
+            records.push(record);
+            id = next;
+        }
+        Ok(records)
+    }
+
+    /// System power draw in watts (DCMI Get Power Reading)
+    fn power_reading(&mut self) -> Option<f64> {
+        let response = self.command(NETFN_GROUP, DCMI_GET_POWER_READING, &[DCMI_GROUP, 0x01, 0, 0])
+            .map_err(|e| debug!("Failed to get the DCMI power reading: {:?}", e)).ok()?;
+        // Group extension, current, minimum, maximum and average power, timestamp, period and state
+        if response.len() < 3 {
+            return None;
+        }
+        if let Some(state) = response.get(17) {
+            if state & 0x40 == 0 {
+                return None;
+            }
+        }
+        Some(u16::from_le_bytes([response[1], response[2]]) as f64)
+    }
+}
+
+/// Sensor of a full or compact SDR record
+struct SensorRecord {
+    owner: u8,
+    number: u8,
+    entity: u8,
+    sensor_type: u8,
+    name: String,
+    /// Analog data format, base unit, M, B, B exponent and result exponent. Only for linear full records
+    conversion: Option<(u8, u8, i32, i32, i32, i32)>,
+}
+
+/// Sign extends a value of `bits` bits
+fn signed(value: u16, bits: u32) -> i32 {
+    let shift = 32 - bits;
+    ((value as i32) << shift) >> shift
+}
+
+fn parse_record(record: &[u8]) -> Option<SensorRecord> {
+    let name_offset = match *record.get(3)? {
+        FULL_SENSOR_RECORD => 47,
+        COMPACT_SENSOR_RECORD => 31,
+        _ => return None,
+    };
+    let name_length = (*record.get(name_offset)? & 0x1f) as usize;
+    let name = record.get(name_offset + 1..name_offset + 1 + name_length)?;
+    let analog_format = record[20] >> 6;
+    let linear = record[23] & 0x7f == 0;
+    let conversion = if record[3] == FULL_SENSOR_RECORD && analog_format != 3 && linear {
+        let m = signed(record[24] as u16 | ((record[25] as u16 & 0xc0) << 2), 10);
+        let b = signed(record[26] as u16 | ((record[27] as u16 & 0xc0) << 2), 10);
+        let result_exponent = signed((record[29] >> 4) as u16, 4);
+        let b_exponent = signed((record[29] & 0x0f) as u16, 4);
+        Some((analog_format, record[21], m, b, b_exponent, result_exponent))
+    } else {
+        None
+    };
+    Some(SensorRecord {
+        owner: record[5],
+        number: record[7],
+        entity: record[8],
+        sensor_type: record[12],
+        name: String::from_utf8_lossy(name).trim().to_string(),
+        conversion,
+    })
+}
+
+fn sensor_kind(sensor_type: u8) -> String {
+    match sensor_type {
+        0x01 => "Temperature".to_string(),
+        0x02 => "Voltage".to_string(),
+        0x03 => "Current".to_string(),
+        0x04 => "Fan".to_string(),
+        0x05 => "PhysicalSecurity".to_string(),
+        0x07 => "Processor".to_string(),
+        0x08 => "PowerSupply".to_string(),
+        0x09 => "PowerUnit".to_string(),
+        0x0b => "Other".to_string(),
+        0x0c => "Memory".to_string(),
+        0x0d => "DriveSlot".to_string(),
+        other => format!("Type{:#04x}", other),
+    }
+}
+
+fn unit_name(unit: u8) -> Option<String> {
+    match unit {
+        1 => Some("C"),
+        2 => Some("F"),
+        4 => Some("V"),
+        5 => Some("A"),
+        6 => Some("W"),
+        18 => Some("RPM"),
+        _ => None,
+    }.map(|unit| unit.to_string())
+}
+
+/// Converts a raw reading with the linear formula y = (M * x + B * 10^Bexp) * 10^Rexp
+fn convert(raw: u8, (format, _, m, b, b_exponent, result_exponent): (u8, u8, i32, i32, i32, i32)) -> f64 {
+    let x = match format {
+        // One's complement
+        1 if raw & 0x80 != 0 => -((!raw) as f64),
+        // Two's complement
+        2 => raw as i8 as f64,
+        _ => raw as f64,
+    };
+    let value = (m as f64 * x + b as f64 * 10f64.powi(b_exponent)) * 10f64.powi(result_exponent);
+    (value * 1000.0).round() / 1000.0
+}
+
+impl Machine {
+    /// Sensors of the baseboard management controller (BMC): air intake temperature, system power draw and power
+    /// supplies state, plus the whole sensor repository. It fails on machines without a BMC or when
+    /// `/dev/ipmi0` cannot be opened
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let status = m.bmc_status().unwrap();
+    /// println!("Inlet {:?} C, drawing {:?} W", status.inlet_temperature, status.power_draw);
+    /// for psu in status.power_supplies.iter().filter(|psu| psu.failed || psu.input_lost) {
+    ///   println!("{} is not healthy", psu.name);
+    /// }
+    /// ```
+    pub fn bmc_status(&self) -> Result<BmcStatus> {
+        let mut bmc = Bmc::open()?;
+        let mut status = BmcStatus {
+            inlet_temperature: None,
+            power_draw: bmc.power_reading(),
+            power_supplies: vec![],
+            sensors: vec![],
+        };
+        for record in bmc.sdr_records()? {
+            let sensor = match parse_record(&record) {
+                // Sensors of other controllers need bridged requests
+                Some(sensor) if sensor.owner == BMC_ADDRESS => sensor,
+                _ => continue,
+            };
+            let reading = match bmc.command(NETFN_SENSOR, GET_SENSOR_READING, &[sensor.number]) {
+                Ok(reading) => reading,
+                Err(err) => {
+                    debug!("Failed to read the sensor {}: {:?}", sensor.name, err);
+                    continue;
+                }
+            };
+            // Reading unavailable or scanning disabled
+            if reading.len() < 2 || reading[1] & 0x20 != 0 || reading[1] & 0x40 == 0 {
+                continue;
+            }
+            let states = match reading.len() {
+                3 => Some(reading[2] as u16),
+                len if len >= 4 => Some(u16::from_le_bytes([reading[2], reading[3]])),
+                _ => None,
+            };
+            let value = sensor.conversion.map(|conversion| convert(reading[0], conversion));
+            let unit = sensor.conversion.and_then(|(_, unit, ..)| unit_name(unit));
+            if sensor.sensor_type == SENSOR_TEMPERATURE && status.inlet_temperature.is_none() {
+                let name = sensor.name.to_lowercase();
+                if ENTITY_AIR_INLETS.contains(&sensor.entity)
+                    || ["inlet", "intake", "ambient"].iter().any(|keyword| name.contains(keyword)) {
+                    status.inlet_temperature = value;
+                }
+            }
+            if status.power_draw.is_none() && sensor.conversion.map(|(_, unit, ..)| unit) == Some(UNIT_WATTS) {
+                status.power_draw = value;
+            }
+            if sensor.sensor_type == SENSOR_POWER_SUPPLY {
+                if let Some(states) = states {
+                    let bit = |index: u16| states & (1 << index) != 0;
+                    status.power_supplies.push(BmcPowerSupply {
+                        name: sensor.name.clone(),
+                        present: bit(0),
+                        failed: bit(1),
+                        predictive_failure: bit(2),
+                        input_lost: bit(3),
+                    });
+                }
+            }
+            status.sensors.push(BmcSensor {
+                name: sensor.name,
+                number: sensor.number,
+                kind: sensor_kind(sensor.sensor_type),
+                value,
+                unit,
+                states,
+            });
+        }
+        Ok(status)
+    }
+}
@@ -0,0 +1,128 @@
+//! NVIDIA Jetson (Tegra) integrated GPU. Jetson boards have no NVML, the values come from sysfs like tegrastats
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+use crate::memory::read_number;
+use crate::model::{GraphicsUsage, ThermalSensor};
+use crate::sensors::{class_entries, read_text, thermal_zones};
+use crate::host;
+
+/// Device directories of the integrated GPU in the different Jetson generations (TX1/TX2, Xavier, Orin)
+const GPU_PATHS: [&str; 6] = [
+    "/sys/devices/gpu.0",
+    "/sys/devices/platform/gpu.0",
+    "/sys/devices/17000000.gp10b",
+    "/sys/devices/17000000.gv11b",
+    "/sys/devices/platform/17000000.gv11b",
+    "/sys/devices/platform/17000000.ga10b",
+];
+/// Average activity of the memory controller in kHz
+const EMC_ACTIVITY_PATH: &str = "/sys/kernel/actmon_avg_activity/mc_all";
+/// Current EMC clock in Hz. Debugfs is only readable by root
+const EMC_RATE_PATHS: [&str; 2] = [
+    "/sys/kernel/debug/bpmp/debug/clk/emc/rate",
+    "/sys/kernel/debug/clk/emc/clk_rate",
+];
+const THERMAL_PATH: &str = "/sys/class/thermal";
+/// Zones of the CPU and the rest of the SoC, like CPU-therm, SOC0-therm or tj-therm (junction, the hottest point).
+/// Orin names them cpu-thermal, soc0-thermal...
+const SOC_ZONES: [&str; 3] = ["cpu", "soc", "tj"];
+
+fn gpu_path() -> Option<PathBuf> {
+    GPU_PATHS.iter()
+        .map(|path| host::path(path))
+        .find(|path| path.join("load").exists())
+}
+
+/// EMC (memory controller) utilization as percentage of its current clock, like the EMC value of tegrastats
+fn emc_usage() -> Result<u32> {
+    let activity = read_number(&host::path(EMC_ACTIVITY_PATH))?;
+    EMC_RATE_PATHS.iter()
+        .find_map(|path| read_number(&host::path(path)).ok())
+        .filter(|rate| *rate >= 1000)
+        .map(|rate| (activity * 100 / (rate / 1000)).min(100) as u32)
+        .ok_or_else(|| anyhow!("Cannot read the EMC clock, debugfs requires root permissions"))
+}
+
+/// Temperature of the GPU thermal zone (GPU-therm or gpu-thermal) in degrees
+fn gpu_temperature() -> Result<u32> {
+    let (_, dir) = class_entries(THERMAL_PATH, "thermal_zone")?.into_iter()
+        .find(|(_, dir)| read_text(&dir.join("type")).map(|zone| zone.to_lowercase().starts_with("gpu")).unwrap_or(false))
+        .ok_or_else(|| anyhow!("No GPU thermal zone found"))?;
+    Ok((read_number(&dir.join("temp"))? / 1000) as u32)
+}
+
+fn soc_temperatures() -> Vec<ThermalSensor> {
+    thermal_zones().into_iter()
+        .filter(|zone| SOC_ZONES.iter().any(|prefix| zone.chip.to_lowercase().starts_with(prefix)))
+        .collect()
+}
+
+/// Usage of the Jetson GPU. None if the machine is not a Jetson. The memory is shared with the CPU so
+/// `memory_used` is not known, and `memory_usage` is the EMC utilization. There is no encoder and decoder
+/// utilization either, so they are always in `errors`
+pub fn graphics_usage() -> Option<GraphicsUsage> {
+    let dir = gpu_path()?;
+    let mut errors = vec![
+        "memory: Shared with the CPU".to_string(),
+        "encoder: Not reported by Jetson".to_string(),
+        "decoder: Not reported by Jetson".to_string(),
+    ];
+    // The load is in tenths of percentage
+    let utilization = read_number(&dir.join("load"))
+        .and_then(|load| Ok(((load / 10).min(100) as u32, emc_usage()?)));
+    let (gpu, memory_usage) = utilization.unwrap_or_else(|e| {
+        errors.push(format!("utilization: {}", e));
+        (0, 0)
+    });
+    let temperature = gpu_temperature().unwrap_or_else(|e| {
+        errors.push(format!("temperature: {}", e));
+        0
+    });
+    Some(GraphicsUsage {
+        id: format!("tegra:{}", dir.file_name()?.to_string_lossy()),
+        memory_usage,
+        memory_used: 0,
+        encoder: 0,
+        decoder: 0,
+        gpu,
+        temperature,
+        soc_temperatures: soc_temperatures(),
+        processes: vec![],
+        errors,
+    })
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use super::*;
+    use crate::testkit::FakeTree;
+
+    #[test]
+    fn unread_values_are_errors() {
+        let tree = FakeTree::new(1).unwrap();
+        assert!(graphics_usage().is_none());
+        tree.write("/sys/devices/gpu.0/load", "455\n").unwrap();
+        tree.write("/sys/class/thermal/thermal_zone0/type", "CPU-therm\n").unwrap();
+        tree.write("/sys/class/thermal/thermal_zone0/temp", "41500\n").unwrap();
+        tree.write("/sys/class/thermal/thermal_zone1/type", "GPU-therm\n").unwrap();
+        tree.write("/sys/class/thermal/thermal_zone1/temp", "39000\n").unwrap();
+        tree.write("/sys/class/thermal/thermal_zone2/type", "PMIC-Die\n").unwrap();
+        tree.write("/sys/class/thermal/thermal_zone2/temp", "50000\n").unwrap();
+
+        // Without root the EMC clock cannot be read
+        let card = graphics_usage().unwrap();
+        assert_eq!(card.id, "tegra:gpu.0");
+        assert_eq!(card.temperature, 39);
+        assert!(card.is_read("temperature"));
+        assert!(!card.is_read("utilization") && !card.is_read("memory") && !card.is_read("encoder") && !card.is_read("decoder"));
+        assert_eq!(card.soc_temperatures.iter().map(|zone| zone.chip.as_str()).collect::<Vec<&str>>(), vec!["CPU-therm"]);
+
+        tree.write("/sys/kernel/actmon_avg_activity/mc_all", "400000\n").unwrap();
+        tree.write("/sys/kernel/debug/clk/emc/clk_rate", "1600000000\n").unwrap();
+        tree.remove("/sys/class/thermal/thermal_zone1").unwrap();
+        let card = graphics_usage().unwrap();
+        assert!(card.is_read("utilization"));
+        assert_eq!((card.gpu, card.memory_usage), (45, 25));
+        assert!(!card.is_read("temperature"));
+    }
+}
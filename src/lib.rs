@@ -7,14 +7,89 @@
 //! Also you can get a snapshot of your current hardware and system info
 //! It is meant to monitor a system so the performance is the priority. You can probe every second
 //! that it will not be harmful
+mod alerts;
+#[cfg(target_os = "android")]
+mod android;
+#[cfg(feature = "async")]
+mod asynchronous;
+mod cgroup;
+mod clock;
+mod diff;
+mod display;
+#[cfg(unix)]
+mod dl;
+mod dmi;
+mod fleet;
+mod gpu_events;
+mod history;
+mod host;
+#[cfg(feature = "serde")]
+mod http;
+mod jetson;
+#[cfg(target_os = "macos")]
+mod macos;
 mod machine;
+mod memory;
 mod model;
+mod modules;
 mod monitor;
+mod network;
+mod nvcodec;
+mod nvme;
+mod opencl;
+mod pci;
+mod power;
+mod quota;
+mod sampler;
+mod security;
+mod sensors;
+mod storage;
+mod topology;
+mod tpm;
+mod users;
+#[cfg(target_os = "linux")]
+mod vaapi;
+mod virt;
+mod wifi;
+#[cfg(windows)]
+mod windows;
 
 #[cfg(feature = "v4l")]
 pub mod camera;
 
-pub use machine::Machine;
-pub use model::{Disk, DiskUsage, Process, GraphicsProcessUtilization, SystemStatus, GraphicsUsage, Processor, GraphicCard, SystemInfo, Camera, NvidiaInfo};
+#[cfg(all(feature = "containers", target_os = "linux"))]
+pub mod containers;
+#[cfg(feature = "control")]
+pub mod control;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(all(feature = "ipmi", target_os = "linux"))]
+pub mod ipmi;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(all(feature = "rpi", target_os = "linux"))]
+pub mod rpi;
+
+#[cfg(feature = "testkit")]
+pub mod testkit;
+
+pub use machine::{Machine, MachineBuilder, LoadProtection, ChildTracking};
+#[cfg(feature = "async")]
+pub use asynchronous::{AsyncMachine, Response};
+pub use sampler::Sampler;
+pub use gpu_events::GpuEvents;
+pub use storage::DiskFilter;
+pub use monitor::{MonitorConfig, CpuScale};
+pub use alerts::{AlertSink, SyslogSink, Alerts, AlertRule, AlertMetric, DiskWatcher};
+#[cfg(feature = "serde")]
+pub use alerts::WebhookSink;
+pub use model::{Disk, DiskUsage, Process, ProcessState, ProcessEntry, GraphicsProcessUtilization, ProcessGraphicsUsage, SystemStatus, Sample, Snapshot, Statistics, HistorySummary, GraphicsHistory, GraphicsUsage, Processor, GraphicCard, SystemInfo, SystemInfoDiff, Change, Camera, CameraDevice, CameraChanges, CameraFormat, CameraResolution, NvidiaInfo, MemoryDetails, HugepagesPool, Alert, AlertState, GpuEvent, GpuEventKind, MemoryBreakdown, CgroupMemory, CgroupStatus, ContainerStatus, NumaNode, WakeOnLan, FleetValue, GpuFleetReport, PhysicalDisk, Partition, NvmeDetails, StorageTopology, RaidArray, VolumeGroup, ZfsPool, DiskStatus, NetworkInterface, Ipv6Address, IpStatus, NetworkStatus, WifiLink, SocketSummary, ThermalSensor, Fan, Battery, PowerSupplyStatus, PowerAdapter, PowerDomain, UserSession, HardwareIdentity, FirmwareInfo, Baseboard, TpmInfo, Virtualization, SecurityModule, SecurityMode, PciDevice, PassthroughStatus, Display, VaapiInfo, OpenClDevice, KernelModule, ClockStatus, VideoCodecs, ZramStats, Quota, QuotaKind, RpiStatus, RpiThrottling, BmcStatus, BmcSensor, BmcPowerSupply};
 
 
@@ -1,14 +1,53 @@
 use anyhow::Result;
-use sysinfo::{System, Disks};
-use nvml_wrapper::Nvml;
-use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
+use sysinfo::{System, Disks, ProcessesToUpdate, ProcessRefreshKind, CpuRefreshKind, MemoryRefreshKind, DiskRefreshKind};
+use nvml_wrapper::{Nvml, Device};
+use nvml_wrapper::struct_wrappers::device::ProcessUtilizationSample;
+use nvml_wrapper::enum_wrappers::device::{Clock, TemperatureSensor, TemperatureThreshold};
+use nvml_wrapper::enums::device::UsedGpuMemory;
+use nvml_wrapper::error::NvmlError;
 use log::{debug, info};
-use crate::model::{SystemInfo, Processor, Disk as DiskModel, GraphicCard, GraphicsUsage, GraphicsProcessUtilization, SystemStatus, Process, Camera, NvidiaInfo};
-use crate::monitor::Monitor;
-use std::path::Path;
+use crate::model::{SystemInfo, Processor, Disk, GraphicCard, GraphicsUsage, GraphicsProcessUtilization, SystemStatus, Process, ProcessEntry, CgroupStatus, ProcessGraphicsUsage, Camera, NvidiaInfo, MemoryDetails, NumaNode, WakeOnLan, PhysicalDisk, StorageTopology, DiskStatus, NetworkStatus, WifiLink, SocketSummary, IpStatus, ThermalSensor, Fan, Battery, PowerSupplyStatus, PowerDomain, UserSession, PciDevice, PassthroughStatus, Display, OpenClDevice, KernelModule, ClockStatus, VideoCodecs, Quota, Snapshot, HistorySummary};
+use crate::monitor::{self, Monitor, MonitorConfig, CpuScale};
+use crate::history::History;
+use crate::memory;
+use crate::nvcodec;
+use crate::modules;
+use crate::cgroup;
+use crate::clock;
+use crate::dmi;
+use crate::network;
+use crate::host;
+use crate::storage::{self, DiskFilter};
+use crate::topology;
+use crate::wifi;
+use crate::sensors;
+use crate::power;
+use crate::quota;
+use crate::users;
+use crate::pci;
+#[cfg(target_os = "linux")]
+use crate::vaapi;
+use crate::display;
+use crate::opencl;
+use crate::jetson;
+use crate::tpm;
+use crate::virt;
+use crate::security;
+#[cfg(windows)]
+use crate::windows;
+#[cfg(target_os = "macos")]
+use crate::macos;
+#[cfg(target_os = "android")]
+use crate::android;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "v4l")]
-use crate::camera::list_cameras;
+use crate::camera::{list_cameras, camera_paths, group_cameras};
+#[cfg(feature = "v4l")]
+use crate::model::{CameraChanges, CameraDevice};
 
 #[cfg(not(feature = "v4l"))]
 fn list_cameras() -> Vec<Camera> {
@@ -17,35 +56,353 @@ fn list_cameras() -> Vec<Camera> {
 
 /// Represents a machine. Currently you can monitor global CPU/Memory usage, processes CPU usage and the
 /// Nvidia GPU usage. You can also retrieve information about CPU, disks...
+///
+/// It is `Send + Sync` and the sampling methods take `&self`, so it can be shared between threads behind an `Arc`.
+/// Concurrent calls of the same method are serialized
+/// Example
+/// ```
+/// use machine_info::Machine;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let m = Arc::new(Machine::new());
+/// m.track_process(std::process::id() as i32).unwrap();
+/// let sampler = m.clone();
+/// let status = thread::spawn(move || sampler.system_status().unwrap());
+/// println!("{:?}", m.processes_status());
+/// println!("{:?}", status.join().unwrap());
+/// ```
 pub struct Machine {
-    monitor: Monitor,
-    nvml: Option<nvml_wrapper::Nvml>,
+    monitor: Mutex<Monitor>,
+    nvml: LazyNvml,
+    load_protection: Option<LoadProtection>,
+    degraded: AtomicBool,
+    // Time of the host clock when they were sampled
+    last_status: Mutex<Option<(Instant, SystemStatus)>>,
+    last_processes: Mutex<Option<(Instant, Vec<Process>)>>,
+    status_interval: Duration,
+    process_scale: CpuScale,
+    disk_filter: DiskFilter,
+    process_table: Mutex<Option<System>>,
+    system: Mutex<System>,
+    disks: Mutex<Disks>,
+    history: Option<Mutex<History>>,
+    list_cameras: bool,
+    list_disks: bool,
+    partial_results: bool,
+    new_gpu_samples_only: bool,
+    // Newest process utilization sample of every process, by card
+    gpu_samples: Mutex<HashMap<String, HashMap<u32, ProcessUtilizationSample>>>,
+    #[cfg(feature = "v4l")]
+    cameras: Mutex<Vec<Camera>>,
+    #[cfg(all(target_os = "linux", feature = "vaapi"))]
+    vaapi_drivers: vaapi::DriverCache,
 }
 
+/// First delay before trying again to load NVML, doubled after every failure up to `NVML_MAX_RETRY`
+const NVML_MIN_RETRY: Duration = Duration::from_secs(5);
+const NVML_MAX_RETRY: Duration = Duration::from_secs(300);
+/// Environment variable disabling NVML in every `Machine`, for sandboxes where loading it hangs or is forbidden
+const NO_NVML_VAR: &str = "MACHINE_INFO_NO_NVML";
+/// Process utilization samples older than this are not reported
+const GPU_SAMPLE_MAX_AGE: Duration = Duration::from_secs(5);
 
-impl Machine {
-    /// Creates a new instance of Machine. If not graphic card it will warn about it but not an error
-    /// Example
-    /// ```
-    /// use machine_info::Machine;
-    /// let m = Machine::new();
-    /// ```
-    pub fn new() -> Machine{
-        let nvml = match Nvml::init() {
+/// True if NVML is disabled by the environment: `MACHINE_INFO_NO_NVML` set to anything but empty or 0
+pub(crate) fn nvml_disabled_by_env() -> bool {
+    std::env::var_os(NO_NVML_VAR).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// NVML loaded on the first GPU query. When the driver is not loaded yet (right after boot or during a driver
+/// upgrade) the initialization is tried again on later queries with an exponential backoff
+struct LazyNvml {
+    enabled: bool,
+    nvml: OnceLock<Nvml>,
+    // Time of the next attempt and delay after it
+    retry: Mutex<(Instant, Duration)>,
+}
+
+impl LazyNvml {
+    fn new(enabled: bool) -> LazyNvml {
+        // There are no Nvidia drivers for recent macOS versions
+        let enabled = enabled && !cfg!(target_os = "macos");
+        let enabled = if enabled && nvml_disabled_by_env() {
+            debug!("Nvidia disabled by {}", NO_NVML_VAR);
+            false
+        } else {
+            if !enabled {
+                debug!("Nvidia disabled");
+            }
+            enabled
+        };
+        LazyNvml {
+            enabled,
+            nvml: OnceLock::new(),
+            retry: Mutex::new((host::instant(), NVML_MIN_RETRY)),
+        }
+    }
+
+    fn get(&self) -> Option<&Nvml> {
+        if !self.enabled {
+            return None;
+        }
+        if let Some(nvml) = self.nvml.get() {
+            return Some(nvml);
+        }
+        let mut retry = lock(&self.retry);
+        // Another thread may have loaded it while this one was waiting
+        if let Some(nvml) = self.nvml.get() {
+            return Some(nvml);
+        }
+        let now = host::instant();
+        if now < retry.0 {
+            return None;
+        }
+        match init_nvml() {
             Ok(nvml) => {
                 info!("Nvidia driver loaded");
-                Some(nvml)
+                Some(self.nvml.get_or_init(|| nvml))
             },
             Err(error) => {
-                debug!("Nvidia not available because {}", error);
+                debug!("Nvidia not available because {}, trying again in {:?}", error, retry.1);
+                *retry = (now + retry.1, (retry.1 * 2).min(NVML_MAX_RETRY));
                 None
             }
-        };
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn init_nvml() -> Result<Nvml, NvmlError> {
+    Nvml::init()
+}
+
+/// Loads nvml.dll from the PATH and then from the folder of older drivers
+#[cfg(windows)]
+fn init_nvml() -> Result<Nvml, NvmlError> {
+    Nvml::init().or_else(|error| match windows::nvml_library() {
+        Some(path) => Nvml::builder().lib_path(path.as_os_str()).init(),
+        None => Err(error)
+    })
+}
+
+/// Locks a mutex. A panic while it was locked does not leave the sampling state unusable
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Builder of a `Machine`. Subsystems that are not needed can be skipped, NVML initialization alone takes a
+/// noticeable time on some systems. By default everything is enabled
+#[derive(Debug, Clone)]
+pub struct MachineBuilder {
+    nvml: bool,
+    cameras: bool,
+    disks: bool,
+    load_protection: Option<LoadProtection>,
+    disk_filter: DiskFilter,
+    monitor: MonitorConfig,
+    history: Option<usize>,
+    partial_results: bool,
+    new_gpu_samples_only: bool,
+}
+
+impl Default for MachineBuilder {
+    fn default() -> Self {
+        MachineBuilder {
+            nvml: true,
+            cameras: true,
+            disks: true,
+            load_protection: None,
+            disk_filter: DiskFilter::default(),
+            monitor: MonitorConfig::default(),
+            history: None,
+            partial_results: false,
+            new_gpu_samples_only: true,
+        }
+    }
+}
+
+impl MachineBuilder {
+    /// Uses NVML for the Nvidia cards. It is loaded on the first GPU query and, if the driver is not loaded
+    /// yet, tried again later. Without it, no Nvidia card is reported and the NVML library is never loaded. The
+    /// `MACHINE_INFO_NO_NVML=1` environment variable disables it too, for applications that cannot be changed
+    pub fn with_nvml(mut self, enabled: bool) -> MachineBuilder {
+        self.nvml = enabled;
+        self
+    }
+
+    /// Lists the cameras in `system_info`. Opening every video device is slow
+    pub fn with_cameras(mut self, enabled: bool) -> MachineBuilder {
+        self.cameras = enabled;
+        self
+    }
+
+    /// Lists the disks in `system_info`
+    pub fn with_disks(mut self, enabled: bool) -> MachineBuilder {
+        self.disks = enabled;
+        self
+    }
+
+    /// Same as `Machine::set_load_protection`
+    pub fn with_load_protection(mut self, protection: LoadProtection) -> MachineBuilder {
+        self.load_protection = Some(protection);
+        self
+    }
+
+    /// Same as `Machine::set_disk_filter`
+    pub fn with_disk_filter(mut self, filter: DiskFilter) -> MachineBuilder {
+        self.disk_filter = filter;
+        self
+    }
+
+    /// Reports the graphic cards with the fields that cannot be read set to 0 and listed in `errors`, instead of
+    /// skipping the whole card. A card with a broken temperature sensor still reports its memory and utilization.
+    /// Check `is_read` before using a field; the exporters, the history and the alerts skip the missing ones
+    pub fn with_partial_results(mut self, enabled: bool) -> MachineBuilder {
+        self.partial_results = enabled;
+        self
+    }
+
+    /// Asks NVML only for the process utilization samples taken since the previous call, keeping the newest one
+    /// of every process of every card, so repeated `graphics_status` and `processes_status` calls do not fetch the
+    /// same samples again. A process without samples in the last seconds does not appear in the utilization of
+    /// the card. When disabled, every call reads all the samples the driver keeps. Enabled by default
+    pub fn with_new_gpu_samples_only(mut self, enabled: bool) -> MachineBuilder {
+        self.new_gpu_samples_only = enabled;
+        self
+    }
+
+    /// How the CPU usage percentages are computed: the scale of the system and processes usage and the minimum
+    /// time between two samples
+    /// Example
+    /// ```
+    /// use machine_info::{Machine, MonitorConfig, CpuScale};
+    /// use std::time::Duration;
+    /// let m = Machine::builder()
+    ///     .with_monitor_config(MonitorConfig {
+    ///         process_scale: CpuScale::Normalized,
+    ///         min_interval: Duration::from_secs(1),
+    ///         ..Default::default()
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn with_monitor_config(mut self, config: MonitorConfig) -> MachineBuilder {
+        self.monitor = config;
+        self
+    }
+
+    /// Scale of `Process.cpu`: relative to one core, going over 100 with several cores (the default, like `top`),
+    /// or normalized to the capacity of the whole machine. Shortcut of the `process_scale` of `with_monitor_config`
+    pub fn with_process_scale(mut self, scale: CpuScale) -> MachineBuilder {
+        self.monitor.process_scale = scale;
+        self
+    }
+
+    /// Same as `Machine::set_history`
+    pub fn with_history(mut self, capacity: usize) -> MachineBuilder {
+        self.history = Some(capacity);
+        self
+    }
+
+    /// Creates the machine
+    /// Example
+    /// ```
+    /// use machine_info::{Machine, LoadProtection, DiskFilter};
+    /// let m = Machine::builder()
+    ///     .with_disks(true)
+    ///     .with_disk_filter(DiskFilter::recommended())
+    ///     .with_load_protection(LoadProtection::default())
+    ///     .with_history(600)
+    ///     .build();
+    /// println!("{:?}", m.system_status());
+    /// ```
+    pub fn build(self) -> Machine {
         Machine{
-            monitor: Monitor::new(),
-            nvml: nvml
+            status_interval: self.monitor.min_interval,
+            process_scale: self.monitor.process_scale,
+            monitor: Mutex::new(Monitor::new(self.monitor)),
+            nvml: LazyNvml::new(self.nvml),
+            load_protection: self.load_protection,
+            degraded: AtomicBool::new(false),
+            last_status: Mutex::new(None),
+            last_processes: Mutex::new(None),
+            disk_filter: self.disk_filter,
+            process_table: Mutex::new(None),
+            system: Mutex::new(System::new()),
+            disks: Mutex::new(Disks::new()),
+            history: self.history.map(|capacity| Mutex::new(History::new(capacity))),
+            list_cameras: self.cameras,
+            list_disks: self.disks,
+            partial_results: self.partial_results,
+            new_gpu_samples_only: self.new_gpu_samples_only,
+            gpu_samples: Mutex::new(HashMap::new()),
+            #[cfg(feature = "v4l")]
+            cameras: Mutex::new(vec![]),
+            #[cfg(all(target_os = "linux", feature = "vaapi"))]
+            vaapi_drivers: Mutex::new(HashMap::new()),
         }
     }
+}
+
+/// Self protection settings used when the machine is under high load. While the system CPU usage is
+/// over the threshold, the machine is considered degraded: calls done more often than `min_interval`
+/// return the last sampled values and expensive probes (GPU per process stats, cameras) are skipped
+#[derive(Debug, Clone)]
+pub struct LoadProtection {
+    /// System CPU usage percentage from which the sampling is throttled
+    pub cpu_threshold: i32,
+    /// Minimum time between two real samples while degraded
+    pub min_interval: Duration,
+}
+
+impl Default for LoadProtection {
+    fn default() -> Self {
+        LoadProtection {
+            cpu_threshold: 90,
+            min_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// How the children of a tracked process are reported by `processes_status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildTracking {
+    /// The usage of all the descendants is added to the tracked process entry
+    Aggregate,
+    /// Every descendant is reported as its own entry, with `parent` set to the tracked process
+    PerChild,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine::new()
+    }
+}
+
+
+impl Machine {
+    /// Creates a new instance of Machine. NVML is only loaded on the first GPU query, so no graphic card is
+    /// not an error
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// ```
+    pub fn new() -> Machine{
+        MachineBuilder::default().build()
+    }
+
+    /// Builder to choose the subsystems initialized and the settings of the machine
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::builder()
+    ///     .with_nvml(false)
+    ///     .with_cameras(false)
+    ///     .build();
+    /// ```
+    pub fn builder() -> MachineBuilder {
+        MachineBuilder::default()
+    }
     
     /// Retrieves full information about the computer
     /// Example
@@ -54,13 +411,110 @@ impl Machine {
     /// let m = Machine::new();
     /// println!("{:?}", m.system_info())
     /// ```
-    pub fn system_info(& mut self) -> SystemInfo {
-        let mut sys = System::new();
-        sys.refresh_all();
-        
+    pub fn system_info(&self) -> SystemInfo {
+        let (processor, total_processors, total_memory) = self.processor_and_memory();
+
+        let disks = if self.list_disks {
+            self.disk_info()
+        } else {
+            vec![]
+        };
+
+        let cards = self.gpu_info();
+        let nvidia = self.nvidia_info();
+
+        // Getting the model
+        #[cfg(not(any(windows, target_os = "macos", target_os = "android")))]
+        let model = {
+            let model_path = host::path("/sys/firmware/devicetree/base/model");
+            if model_path.exists() {
+                std::fs::read_to_string(model_path)
+                    .map_err(|e| {
+                        debug!("Failed to read model path: {}", e);
+                        e
+                    })
+                    .ok()
+            } else {
+                None
+            }
+        };
+        #[cfg(windows)]
+        let model = windows::model();
+        #[cfg(target_os = "macos")]
+        let model = macos::model();
+        #[cfg(target_os = "android")]
+        let model = android::model();
+
+        // VA-API only exists on Linux
+        #[cfg(all(target_os = "linux", feature = "vaapi"))]
+        let vaapi_devices = vaapi::vaapi_devices(&self.vaapi_drivers);
+        #[cfg(all(target_os = "linux", not(feature = "vaapi")))]
+        let vaapi_devices = vaapi::vaapi_devices();
+        #[cfg(not(target_os = "linux"))]
+        let vaapi_devices = vec![];
+
+        let memory_limit = match cgroup::memory() {
+            Ok(cgroup) => cgroup.limit.filter(|limit| *limit < total_memory),
+            Err(e) => {
+                debug!("Cannot get cgroup memory limit: {}", e);
+                None
+            }
+        };
+
+        SystemInfo {
+            os_name: System::name().unwrap_or_else(|| "Unknown".to_string()),
+            kernel_version: System::kernel_version().unwrap_or_else(|| "Unknown".to_string()),
+            os_version: System::os_version().unwrap_or_else(|| "Unknown".to_string()),
+            distribution: System::distribution_id(),
+            hostname: System::host_name().unwrap_or_else(|| "Unknown".to_string()),
+            memory: total_memory,
+            memory_limit,
+            nvidia,
+            vaapi: !vaapi_devices.is_empty(),
+            vaapi_devices,
+            #[cfg(target_os = "macos")]
+            video_toolbox: macos::video_toolbox(),
+            #[cfg(not(target_os = "macos"))]
+            video_toolbox: false,
+            processor,
+            total_processors,
+            graphics: cards,
+            disks,
+            cameras: if !self.list_cameras {
+                vec![]
+            } else if self.is_degraded() {
+                debug!("Skipping cameras listing because the system is under high load");
+                vec![]
+            } else {
+                list_cameras()
+            },
+            model,
+            networks: network::network_interfaces().unwrap_or_else(|e| {
+                debug!("Cannot get network interfaces: {}", e);
+                vec![]
+            }),
+            hardware: dmi::hardware_identity(),
+            tpm: tpm::tpm(),
+            virtualization: virt::virtualization(),
+            security_modules: security::security_modules(),
+            boot_time: System::boot_time(),
+            uptime: System::uptime()
+        }
+    }
+
+    /// Processor, number of processors and total memory. The System is kept between calls and only these
+    /// values are refreshed
+    fn processor_and_memory(&self) -> (Processor, usize, u64) {
+        let mut sys = lock(&self.system);
+        if sys.cpus().is_empty() {
+            sys.refresh_cpu_list(CpuRefreshKind::nothing().with_frequency());
+        } else {
+            sys.refresh_cpu_frequency();
+        }
+        sys.refresh_memory_specifics(MemoryRefreshKind::nothing().with_ram());
+
         // Get CPU info - in sysinfo 0.37, we use cpus() to get all CPUs
-        let cpus = sys.cpus();
-        let processor = if let Some(cpu) = cpus.first() {
+        let processor = if let Some(cpu) = sys.cpus().first() {
             Processor{
                 frequency: cpu.frequency(),
                 vendor: cpu.vendor_id().to_string(),
@@ -73,185 +527,656 @@ impl Machine {
                 brand: "Unknown".to_string()
             }
         };
+        (processor, sys.cpus().len(), sys.total_memory())
+    }
 
-        // Get disks using Disks struct
-        let disks_list = Disks::new_with_refreshed_list();
-        let mut disks = Vec::new();
-        for disk in disks_list.list() {
-            // Handle potential errors when converting disk names and file systems
-            let disk_name = disk.name().to_str().unwrap_or("Unknown").to_string();
-            let fs = disk.file_system().to_string_lossy().to_string();
-            let mount_point = disk.mount_point().to_str().unwrap_or("Unknown").to_string();
-            
-            disks.push(DiskModel{
-                name: disk_name,
-                fs,
-                storage_type: match disk.kind() {
-                    sysinfo::DiskKind::HDD => "HDD".to_string(),
-                    sysinfo::DiskKind::SSD => "SSD".to_string(),
-                    _ => "Unknown".to_string()
-                },
-                available: disk.available_space(),
-                size: disk.total_space(),
-                mount_point
-            })
+    /// Retrieves only the processor information of `system_info`, without listing disks, GPUs or cameras
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.cpu_info())
+    /// ```
+    pub fn cpu_info(&self) -> Processor {
+        self.processor_and_memory().0
+    }
+
+    /// Retrieves only the disks of `system_info`, filtered with the disk filter. Filesystems mounted since
+    /// the last call are added and the unmounted ones removed
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.disk_info())
+    /// ```
+    pub fn disk_info(&self) -> Vec<Disk> {
+        self.refresh_disks();
+        storage::disks(&lock(&self.disks), &self.disk_filter)
+    }
+
+    /// Enumerates the mounted filesystems again, adding the ones mounted since the last enumeration and removing
+    /// the unmounted ones. Only the mount table and the space of the filesystems are read
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// m.refresh_disks();
+    /// println!("{:?}", m.disks())
+    /// ```
+    pub fn refresh_disks(&self) {
+        lock(&self.disks).refresh_specifics(true, DiskRefreshKind::nothing().with_kind().with_storage());
+    }
+
+    /// Filesystems found by the last enumeration, with their free space updated, filtered with the disk filter.
+    /// The mount table is not read again, so it is the cheapest way to follow the disk space. Call `refresh_disks`
+    /// when volumes are mounted or unmounted. The first call enumerates them
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for disk in m.disks() {
+    ///     println!("{} {} bytes free", disk.mount_point, disk.available);
+    /// }
+    /// ```
+    pub fn disks(&self) -> Vec<Disk> {
+        let mut disks = lock(&self.disks);
+        if disks.list().is_empty() {
+            disks.refresh_specifics(true, DiskRefreshKind::nothing().with_kind().with_storage());
+        } else {
+            for disk in disks.list_mut() {
+                disk.refresh_specifics(DiskRefreshKind::nothing().with_storage());
+            }
         }
+        storage::disks(&disks, &self.disk_filter)
+    }
 
+    /// Quotas of the users, groups and projects in the filesystem mounted at `mount_point` (like `Disk.mount_point`),
+    /// for machines shared by many tenants where the free space is not the limit. Empty if the filesystem has no
+    /// quotas enabled. Reading the quotas of other users requires root permissions
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for quota in m.quotas("/home").unwrap() {
+    ///     if let Some(limit) = quota.hard_limit {
+    ///         println!("{:?} {:?} uses {} of {} bytes", quota.kind, quota.name, quota.used, limit);
+    ///     }
+    /// }
+    /// ```
+    pub fn quotas(&self, mount_point: &str) -> Result<Vec<Quota>> {
+        quota::quotas(mount_point)
+    }
+
+    /// Retrieves only the NVIDIA cards of `system_info`. Empty when NVML is not available
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.gpu_info())
+    /// ```
+    pub fn gpu_info(&self) -> Vec<GraphicCard> {
+        let nvml = match self.nvml() {
+            Some(nvml) => nvml,
+            None => return vec![]
+        };
         let mut cards = Vec::new();
-        let nvidia = if let Some(nvml) = &self.nvml {
-            // Handle device_count() error
-            let device_count = match nvml.device_count() {
-                Ok(count) => count,
+        // Handle device_count() error
+        let device_count = match nvml.device_count() {
+            Ok(count) => count,
+            Err(e) => {
+                debug!("Failed to get NVIDIA device count: {}", e);
+                0
+            }
+        };
+        
+        for n in 0..device_count {
+            // Handle device_by_index() error
+            let device = match nvml.device_by_index(n) {
+                Ok(dev) => dev,
                 Err(e) => {
-                    debug!("Failed to get NVIDIA device count: {}", e);
-                    0
+                    debug!("Failed to get NVIDIA device at index {}: {}", n, e);
+                    continue;
                 }
             };
             
-            for n in 0..device_count {
-                // Handle device_by_index() error
-                let device = match nvml.device_by_index(n) {
-                    Ok(dev) => dev,
-                    Err(e) => {
-                        debug!("Failed to get NVIDIA device at index {}: {}", n, e);
-                        continue;
-                    }
-                };
-                
-                // Handle brand() error gracefully - it may return UnexpectedVariant for new GPU brands
-                // The error can occur when NVML returns a brand value that isn't in the enum yet
-                let brand_str = match device.brand() {
-                    Ok(brand) => match brand {
-                        nvml_wrapper::enum_wrappers::device::Brand::GeForce => "GeForce".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::Quadro => "Quadro".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::Tesla => "Tesla".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::Titan => "Titan".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::NVS => "NVS".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::GRID => "GRID".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::VApps => "VApps".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::VPC => "VPC".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::VCS => "VCS".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::VWS => "VWS".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::CloudGaming => "CloudGaming".to_string(),
-                        nvml_wrapper::enum_wrappers::device::Brand::Unknown => "Unknown".to_string(),
-                        // Handle any future brand variants
-                        _ => format!("{:?}", brand),
-                    },
-                    Err(e) => {
-                        // This handles cases where NVML returns an unknown brand variant (e.g., variant 12)
-                        // which can happen with newer GPU models not yet in the enum
-                        debug!("Failed to get GPU brand (likely UnexpectedVariant): {}", e);
-                        format!("Unknown(Error: {})", e)
-                    }
-                };
-                
-                // Handle other device operations with error handling
-                let uuid = match device.uuid() {
-                    Ok(u) => u,
-                    Err(e) => {
-                        debug!("Failed to get GPU UUID: {}", e);
-                        continue;
-                    }
-                };
-                
-                let name = match device.name() {
-                    Ok(n) => n,
-                    Err(e) => {
-                        debug!("Failed to get GPU name: {}", e);
-                        continue;
-                    }
-                };
-                
-                let memory = match device.memory_info() {
-                    Ok(m) => m.total,
-                    Err(e) => {
-                        debug!("Failed to get GPU memory info: {}", e);
-                        continue;
-                    }
-                };
-                
-                let temperature = match device.temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu) {
-                    Ok(t) => t,
-                    Err(e) => {
-                        debug!("Failed to get GPU temperature: {}", e);
-                        continue;
-                    }
-                };
-                
-                cards.push(GraphicCard{
-                    id: uuid,
-                    name,
-                    brand: brand_str,
-                    memory,
-                    temperature
-                });
-            }
+            // Handle brand() error gracefully - it may return UnexpectedVariant for new GPU brands
+            // The error can occur when NVML returns a brand value that isn't in the enum yet
+            let brand_str = match device.brand() {
+                Ok(brand) => match brand {
+                    nvml_wrapper::enum_wrappers::device::Brand::GeForce => "GeForce".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::Quadro => "Quadro".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::Tesla => "Tesla".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::Titan => "Titan".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::NVS => "NVS".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::GRID => "GRID".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::VApps => "VApps".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::VPC => "VPC".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::VCS => "VCS".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::VWS => "VWS".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::CloudGaming => "CloudGaming".to_string(),
+                    nvml_wrapper::enum_wrappers::device::Brand::Unknown => "Unknown".to_string(),
+                    // Handle any future brand variants
+                    _ => format!("{:?}", brand),
+                },
+                Err(e) => {
+                    // This handles cases where NVML returns an unknown brand variant (e.g., variant 12)
+                    // which can happen with newer GPU models not yet in the enum
+                    debug!("Failed to get GPU brand (likely UnexpectedVariant): {}", e);
+                    format!("Unknown(Error: {})", e)
+                }
+            };
             
-            // Handle NvidiaInfo creation with error handling
-            let nvidia_info = match (
-                nvml.sys_driver_version(),
-                nvml.sys_nvml_version(),
-                nvml.sys_cuda_driver_version()
-            ) {
-                (Ok(driver), Ok(nvml_ver), Ok(cuda)) => Some(NvidiaInfo {
-                    driver_version: driver,
-                    nvml_version: nvml_ver,
-                    cuda_version: cuda
-                }),
-                _ => {
-                    debug!("Failed to get some NVIDIA system info");
-                    None
+            // Handle other device operations with error handling
+            let uuid = match device.uuid() {
+                Ok(u) => u,
+                Err(e) => {
+                    debug!("Failed to get GPU UUID: {}", e);
+                    continue;
                 }
             };
-            nvidia_info
-        } else {
-            None
+            
+            let mut errors = vec![];
+            let name = match self.gpu_field(device.name(), "name", &mut errors) {
+                Some(n) => n,
+                None => continue
+            };
+            
+            let memory = match self.gpu_field(device.memory_info().map(|m| m.total), "memory", &mut errors) {
+                Some(m) => m,
+                None => continue
+            };
+            
+            let temperature = match self.gpu_field(device.temperature(TemperatureSensor::Gpu), "temperature", &mut errors) {
+                Some(t) => t,
+                None => continue
+            };
+            
+            // Not every card reports its limits, that is not an error
+            let threshold = |threshold| device.temperature_threshold(threshold)
+                .map_err(|e| debug!("Failed to get GPU {:?} temperature threshold: {}", threshold, e))
+                .ok();
+
+            cards.push(GraphicCard{
+                id: uuid,
+                bus_id: device.pci_info().ok().map(|pci| pci::normalize_address(&pci.bus_id)),
+                name,
+                brand: brand_str,
+                memory,
+                temperature,
+                slowdown_temperature: threshold(TemperatureThreshold::Slowdown),
+                shutdown_temperature: threshold(TemperatureThreshold::Shutdown),
+                max_graphics_clock: device.max_clock_info(Clock::Graphics).ok(),
+                max_memory_clock: device.max_clock_info(Clock::Memory).ok(),
+                memory_bus_width: device.memory_bus_width().ok(),
+                errors
+            });
+        }
+        cards
+    }
+
+    /// Video codecs every Nvidia card can encode and decode in hardware, with the encoding sessions running and
+    /// the maximum allowed by the driver, to place transcoding jobs without probing with ffmpeg. Empty when NVML is
+    /// not available
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for card in m.video_codecs() {
+    ///     println!("{} encodes {:?} and decodes {:?}", card.id, card.encode, card.decode);
+    /// }
+    /// ```
+    pub fn video_codecs(&self) -> Vec<VideoCodecs> {
+        let nvml = match self.nvml() {
+            Some(nvml) => nvml,
+            None => return vec![]
         };
-        
-        // Getting the model
-        let model_path = Path::new("/sys/firmware/devicetree/base/model");
-        let model = if model_path.exists() {
-            std::fs::read_to_string(model_path)
-                .map_err(|e| {
-                    debug!("Failed to read model path: {}", e);
-                    e
-                })
-                .ok()
-        } else {
-            None
+        let driver_version = nvml.sys_driver_version().unwrap_or_default();
+        let device_count = match nvml.device_count() {
+            Ok(count) => count,
+            Err(e) => {
+                debug!("Failed to get NVIDIA device count in video_codecs: {}", e);
+                0
+            }
         };
-        
-        let vaapi = Path::new("/dev/dri/renderD128").exists();
+        (0..device_count)
+            .filter_map(|n| nvml.device_by_index(n)
+                .map_err(|e| debug!("Failed to get NVIDIA device at index {} in video_codecs: {}", n, e))
+                .ok())
+            .filter_map(|device| {
+                let id = device.uuid().ok()?;
+                Some(nvcodec::video_codecs(&device, id, &driver_version))
+            })
+            .collect()
+    }
 
-        SystemInfo {
-            os_name: System::name().unwrap_or_else(|| "Unknown".to_string()),
-            kernel_version: System::kernel_version().unwrap_or_else(|| "Unknown".to_string()),
-            os_version: System::os_version().unwrap_or_else(|| "Unknown".to_string()),
-            distribution: System::distribution_id(),
-            hostname: System::host_name().unwrap_or_else(|| "Unknown".to_string()),
-            memory: sys.total_memory(),
-            nvidia,
-            vaapi,
-            processor,
-            total_processors: sys.cpus().len(),
-            graphics: cards,
-            disks,
-            cameras: list_cameras(),
-            model
+    /// Retrieves the NVIDIA driver, NVML and CUDA versions. None when NVML is not available
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.nvidia_info())
+    /// ```
+    pub fn nvidia_info(&self) -> Option<NvidiaInfo> {
+        let nvml = self.nvml()?;
+        // Handle NvidiaInfo creation with error handling
+        match (
+            nvml.sys_driver_version(),
+            nvml.sys_nvml_version(),
+            nvml.sys_cuda_driver_version()
+        ) {
+            (Ok(driver), Ok(nvml_ver), Ok(cuda)) => Some(NvidiaInfo {
+                driver_version: driver,
+                nvml_version: nvml_ver,
+                cuda_version: cuda
+            }),
+            _ => {
+                debug!("Failed to get some NVIDIA system info");
+                None
+            }
+        }
+    }
+
+    /// Lists the cameras again and returns the ones plugged and unplugged since the last call, so USB cameras
+    /// attached after startup are found. The first call returns all the cameras as added. The devices are only
+    /// opened when the list of /dev/video* nodes changed, so it can be called periodically
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let changes = m.refresh_cameras();
+    /// println!("{:?} {:?}", changes, m.cameras());
+    /// ```
+    #[cfg(feature = "v4l")]
+    pub fn refresh_cameras(&self) -> CameraChanges {
+        let mut known_cameras = lock(&self.cameras);
+        let known = known_cameras.iter().map(|camera| camera.path.clone()).collect::<Vec<String>>();
+        if camera_paths() == known {
+            return CameraChanges { added: vec![], removed: vec![] };
+        }
+        let mut cameras = list_cameras();
+        cameras.sort_by(|a, b| a.path.cmp(&b.path));
+        let added = cameras.iter()
+            .filter(|camera| !known.contains(&camera.path))
+            .cloned()
+            .collect::<Vec<Camera>>();
+        let removed = std::mem::take(&mut *known_cameras).into_iter()
+            .filter(|old| !cameras.iter().any(|camera| camera.path == old.path))
+            .collect::<Vec<Camera>>();
+        *known_cameras = cameras;
+        CameraChanges { added, removed }
+    }
+
+    /// Attached cameras grouped by physical device. A single USB camera usually exposes several video nodes
+    /// (like /dev/video0 for video and /dev/video1 for metadata), this returns it once with all its nodes
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for camera in m.camera_devices() {
+    ///     let capture = camera.nodes.iter().filter(|node| node.capture).collect::<Vec<_>>();
+    ///     println!("{} {:?}", camera.name, capture);
+    /// }
+    /// ```
+    #[cfg(feature = "v4l")]
+    pub fn camera_devices(&self) -> Vec<CameraDevice> {
+        group_cameras(list_cameras())
+    }
+
+    /// Cameras found by the last call to `refresh_cameras`
+    #[cfg(feature = "v4l")]
+    pub fn cameras(&self) -> Vec<Camera> {
+        lock(&self.cameras).clone()
+    }
+
+    /// Extended memory information like the hugepages configuration
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.memory_details())
+    /// ```
+    pub fn memory_details(&self) -> Result<MemoryDetails> {
+        memory::memory_details()
+    }
+
+    /// NUMA nodes with their CPUs and memory usage. Useful to check that the memory allocations stay local
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.numa_nodes())
+    /// ```
+    pub fn numa_nodes(&self) -> Result<Vec<NumaNode>> {
+        memory::numa_nodes()
+    }
+
+    /// Wake-on-LAN configuration of every network interface
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.wake_on_lan())
+    /// ```
+    pub fn wake_on_lan(&self) -> Result<Vec<WakeOnLan>> {
+        network::wake_on_lan()
+    }
+
+    /// Wakes up a remote machine sending a magic packet to its MAC address. The packet is sent to the
+    /// given address or to the broadcast address (255.255.255.255:9) if None
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// m.send_magic_packet("02:fc:00:00:00:01", Some("192.168.1.255:9")).unwrap();
+    /// ```
+    pub fn send_magic_packet(&self, mac: &str, address: Option<&str>) -> Result<()> {
+        network::send_magic_packet(mac, address.unwrap_or("255.255.255.255:9"))
+    }
+
+    /// Physical block devices (not partitions nor virtual devices) with their partitions
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.physical_disks())
+    /// ```
+    pub fn physical_disks(&self) -> Result<Vec<PhysicalDisk>> {
+        storage::physical_disks()
+    }
+
+    /// RAID arrays, LVM volume groups and ZFS pools with their state, so degraded storage is visible
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.storage_topology())
+    /// ```
+    pub fn storage_topology(&self) -> Result<StorageTopology> {
+        topology::storage_topology()
+    }
+
+    /// The IO usage of every disk since the last call: throughput, average latency, queue depth and utilization.
+    /// As for the CPU, the first call has nothing to compare with and reports zeros
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// use std::{thread, time};
+    ///
+    /// let m = Machine::new();
+    /// loop {
+    ///   println!("{:?}", m.disks_status());
+    ///   thread::sleep(time::Duration::from_millis(1000));
+    /// }
+    /// ```
+    pub fn disks_status(&self) -> Result<Vec<DiskStatus>> {
+        self.monitor().next_disks()
+    }
+
+    /// The traffic of every network interface since the last call. As for the CPU, the first call has nothing
+    /// to compare with and reports zeros
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// use std::{thread, time};
+    ///
+    /// let m = Machine::new();
+    /// loop {
+    ///   println!("{:?}", m.network_status());
+    ///   thread::sleep(time::Duration::from_millis(1000));
+    /// }
+    /// ```
+    pub fn network_status(&self) -> Result<Vec<NetworkStatus>> {
+        self.monitor().next_networks()
+    }
+
+    /// Link quality of the Wi-Fi interfaces: network name, signal strength, channel and bitrate
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.wifi_status())
+    /// ```
+    pub fn wifi_status(&self) -> Result<Vec<WifiLink>> {
+        wifi::wifi_links()
+    }
+
+    /// Users logged in (local terminals, graphical sessions and SSH), from utmp or systemd-logind. Useful to know
+    /// if a workstation is in interactive use before running maintenance
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.users())
+    /// ```
+    pub fn users(&self) -> Result<Vec<UserSession>> {
+        users::users()
+    }
+
+    /// Clock source of the kernel and the synchronization with NTP, chrony or PTP: the running daemon, if the clock
+    /// is synchronized and the estimated offset and error. Useful to flag machines with drifting clocks
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let clock = m.clock_status();
+    /// if !clock.synchronized {
+    ///     eprintln!("Clock not synchronized, offset {:?} ms", clock.offset);
+    /// }
+    /// ```
+    pub fn clock_status(&self) -> ClockStatus {
+        clock::clock_status()
+    }
+
+    /// Kernel modules loaded in the kernel with their size and the modules using them, from /proc/modules.
+    /// Drivers built into the kernel are not listed
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for module in m.kernel_modules().unwrap_or_default() {
+    ///     println!("{} {} bytes used by {:?}", module.name, module.size, module.used_by);
+    /// }
+    /// ```
+    pub fn kernel_modules(&self) -> Result<Vec<KernelModule>> {
+        modules::kernel_modules()
+    }
+
+    /// If a kernel module, like nvidia, uvcvideo or v4l2loopback, is loaded or built into the kernel. Dashes and
+    /// underscores are the same, as for modprobe
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// if !m.module_loaded("v4l2loopback") {
+    ///     eprintln!("Run modprobe v4l2loopback first");
+    /// }
+    /// ```
+    pub fn module_loaded(&self, name: &str) -> bool {
+        modules::module_loaded(name)
+    }
+
+    /// Devices of the PCI bus with their IDs, class and bound driver. Useful to find capture cards, NICs and
+    /// accelerators that are not NVIDIA GPUs
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.pci_devices())
+    /// ```
+    pub fn pci_devices(&self) -> Result<Vec<PciDevice>> {
+        pci::pci_devices()
+    }
+
+    /// If the machine is ready for PCI passthrough to virtual machines: IOMMU enabled, vfio-pci loaded and the
+    /// devices bound to it or supporting SR-IOV. The IOMMU group and virtual functions of every device are in
+    /// `pci_devices`
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// if let Ok(status) = m.passthrough_status() {
+    ///     if !status.iommu_enabled {
+    ///         eprintln!("Enable the IOMMU with intel_iommu=on or amd_iommu=on");
+    ///     }
+    /// }
+    /// ```
+    pub fn passthrough_status(&self) -> Result<PassthroughStatus> {
+        pci::passthrough_status()
+    }
+
+    /// Monitors connected to the graphic cards with their preferred resolution, refresh rate and connector, from
+    /// the DRM outputs and the monitors EDID
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for display in m.displays().unwrap_or_default() {
+    ///     println!("{} {}x{} {:?}Hz", display.connector, display.width, display.height, display.refresh_rate);
+    /// }
+    /// ```
+    pub fn displays(&self) -> Result<Vec<Display>> {
+        display::displays()
+    }
+
+    /// OpenCL devices of all the platforms with their compute units, work group size and memory, for workloads
+    /// that target OpenCL instead of CUDA. The OpenCL ICD loader (libOpenCL.so.1) is loaded at runtime, it fails if
+    /// it is not installed
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for device in m.opencl_devices().unwrap_or_default() {
+    ///     println!("{} {} ({} compute units, {} bytes)", device.platform, device.name, device.compute_units, device.global_memory);
+    /// }
+    /// ```
+    pub fn opencl_devices(&self) -> Result<Vec<OpenClDevice>> {
+        opencl::opencl_devices()
+    }
+
+    /// Amount of TCP and UDP sockets by state and the TCP retransmission counters
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{:?}", m.socket_summary())
+    /// ```
+    pub fn socket_summary(&self) -> Result<SocketSummary> {
+        network::socket_summary()
+    }
+
+    /// Whether IPv6 is disabled system-wide and the bytes received and sent by IPv4 and IPv6 since boot, from
+    /// /proc/net/netstat and /proc/net/snmp6. Useful to troubleshoot dual-stack hosts
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// if let Ok(ip) = m.ip_status() {
+    ///     println!("IPv6 disabled: {}, {} bytes received over IPv6", ip.ipv6_disabled, ip.ipv6_in_bytes);
+    /// }
+    /// ```
+    pub fn ip_status(&self) -> Result<IpStatus> {
+        network::ip_status()
+    }
+
+    /// All the temperature sensors of the machine: hwmon sensors (CPU, chipset, NVMe...), thermal zones
+    /// and the Nvidia GPUs
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for sensor in m.thermal_status() {
+    ///     println!("{} {} {}°C", sensor.chip, sensor.label, sensor.temperature);
+    /// }
+    /// ```
+    pub fn thermal_status(&self) -> Vec<ThermalSensor> {
+        let mut sensors = sensors::thermal_sensors();
+        if let Some(nvml) = self.nvml() {
+            for n in 0..nvml.device_count().unwrap_or(0) {
+                let device = match nvml.device_by_index(n) {
+                    Ok(dev) => dev,
+                    Err(e) => {
+                        debug!("Failed to get NVIDIA device at index {} in thermal_status: {}", n, e);
+                        continue;
+                    }
+                };
+                match (device.uuid(), device.temperature(TemperatureSensor::Gpu)) {
+                    (Ok(id), Ok(temperature)) => sensors.push(ThermalSensor {
+                        source: id,
+                        chip: "nvidia".to_string(),
+                        label: device.name().unwrap_or_else(|_| "GPU".to_string()),
+                        temperature: temperature as f64,
+                        high: None,
+                        critical: None,
+                    }),
+                    (Err(e), _) | (_, Err(e)) => debug!("Failed to get GPU temperature in thermal_status: {}", e)
+                }
+            }
         }
+        sensors
     }
 
-    /*pub fn disks_status(&self) {
-        //TODO
-        /*
-        let mut disks = Vec::new();
-        for disk in self.sys.disks() {
-            disks.push(api::model::Disk{
-            })
-            */
-    }*/
+    /// Fans of the machine with their speed (RPM) and PWM target, from hwmon. The `hwmon` and `index` fields
+    /// are the ones used by `fan_control` with the `control` feature
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for fan in m.fans() {
+    ///     println!("{} {:?} RPM", fan.label, fan.speed);
+    /// }
+    /// ```
+    pub fn fans(&self) -> Vec<Fan> {
+        sensors::fans()
+    }
+
+    /// Charge, state, remaining time, cycles and health of the batteries (if any)
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for battery in m.battery_status() {
+    ///     println!("{} {:?}% {}", battery.name, battery.capacity, battery.status);
+    /// }
+    /// ```
+    pub fn battery_status(&self) -> Vec<Battery> {
+        power::batteries()
+    }
+
+    /// Whether the machine is on AC or battery, with the adapters and UPS found in /sys/class/power_supply.
+    /// Long running jobs can use it to react to power loss
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// if m.power_supply().on_battery {
+    ///     println!("Running on battery");
+    /// }
+    /// ```
+    pub fn power_supply(&self) -> PowerSupplyStatus {
+        power::power_supply()
+    }
+
+    /// Energy and average power since the last call of the CPU packages and their domains (core, uncore, DRAM)
+    /// from RAPL. The first call reports 0 watts. Reading the counters usually requires root, zones that
+    /// cannot be read are skipped
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// use std::{thread, time};
+    ///
+    /// let m = Machine::new();
+    /// loop {
+    ///   for domain in m.power_status() {
+    ///     println!("{} {:.1}W", domain.name, domain.power);
+    ///   }
+    ///   thread::sleep(time::Duration::from_millis(1000));
+    /// }
+    /// ```
+    pub fn power_status(&self) -> Vec<PowerDomain> {
+        self.monitor().next_power()
+    }
 
-    /// The current usage of all graphic cards (if any)
+    /// The current usage of all graphic cards (if any). NVIDIA cards through NVML, or the integrated GPU of Jetson boards
     /// Example
     /// ```
     /// use machine_info::Machine;
@@ -260,7 +1185,7 @@ impl Machine {
     /// ```
     pub fn graphics_status(&self) -> Vec<GraphicsUsage> {
         let mut cards = Vec::new();
-        if let Some(nvml) = &self.nvml {
+        if let Some(nvml) = self.nvml() {
             // Handle device_count() error
             let device_count = match nvml.device_count() {
                 Ok(count) => count,
@@ -280,86 +1205,46 @@ impl Machine {
                     }
                 };
                 
-                let mut processes = Vec::new();
-                let stats = device.process_utilization_stats(None);
-                if let Ok(stats) = stats {
-                    for p in stats {
-                        processes.push(GraphicsProcessUtilization{
-                            pid: p.pid,
-                            gpu: p.sm_util,
-                            memory: p.mem_util,
-                            encoder: p.enc_util,
-                            decoder: p.dec_util
-                        });
-                    }
+                if let Some(card) = self.card_usage(&device) {
+                    cards.push(card);
                 }
-    
-                // Handle all device operations with error handling
-                let uuid = match device.uuid() {
-                    Ok(u) => u,
-                    Err(e) => {
-                        debug!("Failed to get GPU UUID in graphics_status: {}", e);
-                        continue;
-                    }
-                };
-                
-                let memory_info = match device.memory_info() {
-                    Ok(m) => m.used,
-                    Err(e) => {
-                        debug!("Failed to get GPU memory info in graphics_status: {}", e);
-                        continue;
-                    }
-                };
-                
-                let encoder = match device.encoder_utilization() {
-                    Ok(e) => e.utilization,
-                    Err(e) => {
-                        debug!("Failed to get GPU encoder utilization: {}", e);
-                        continue;
-                    }
-                };
-                
-                let decoder = match device.decoder_utilization() {
-                    Ok(d) => d.utilization,
-                    Err(e) => {
-                        debug!("Failed to get GPU decoder utilization: {}", e);
-                        continue;
-                    }
-                };
-                
-                let utilization_rates = match device.utilization_rates() {
-                    Ok(r) => r,
-                    Err(e) => {
-                        debug!("Failed to get GPU utilization rates: {}", e);
-                        continue;
-                    }
-                };
-                
-                let temperature = match device.temperature(TemperatureSensor::Gpu) {
-                    Ok(t) => t,
-                    Err(e) => {
-                        debug!("Failed to get GPU temperature in graphics_status: {}", e);
-                        continue;
-                    }
-                };
-                
-                cards.push(GraphicsUsage {
-                    id: uuid,
-                    memory_used: memory_info,
-                    encoder,
-                    decoder,
-                    gpu: utilization_rates.gpu,
-                    memory_usage: utilization_rates.memory,
-                    temperature,
-                    processes
-                });
             }
         }
-        
+        // Jetson boards have an integrated GPU without NVML
+        if cards.is_empty() {
+            cards.extend(jetson::graphics_usage());
+        }
+        if let Some(history) = &self.history {
+            lock(history).record_graphics(&cards);
+        }
         cards
         
     }
 
+    /// Current usage of the Nvidia card at the given PCI address, like 0000:65:00.0 (see `GraphicCard.bus_id`).
+    /// The domain can be omitted or written with 8 digits as NVML does. None if there is no such card
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// for card in m.gpu_info() {
+    ///     if let Some(bus_id) = card.bus_id {
+    ///         println!("{} {:?}", bus_id, m.graphics_status_by_bus_id(&bus_id));
+    ///     }
+    /// }
+    /// ```
+    pub fn graphics_status_by_bus_id(&self, bus_id: &str) -> Option<GraphicsUsage> {
+        let nvml = self.nvml()?;
+        let bus_id = pci::normalize_address(bus_id);
+        let device_count = nvml.device_count()
+            .map_err(|e| debug!("Failed to get NVIDIA device count in graphics_status_by_bus_id: {}", e))
+            .ok()?;
+        (0..device_count)
+            .filter_map(|n| nvml.device_by_index(n).ok())
+            .find(|device| device.pci_info().map(|pci| pci::normalize_address(&pci.bus_id) == bus_id).unwrap_or(false))
+            .and_then(|device| self.card_usage(&device))
+    }
+
 
     /// To calculate the CPU usage of a process we have to keep track in time the process so first we have to register the process.
     /// You need to know the PID of your process and use it as parameters. In case you provide an invalid PID it will return error
@@ -367,11 +1252,55 @@ impl Machine {
     /// ```
     /// use machine_info::Machine;
     /// let m = Machine::new();
-    /// let process_pid = 3218;
-    /// m.track_process(process_pid)
+    /// let process_pid = std::process::id() as i32;
+    /// m.track_process(process_pid).unwrap();
+    /// ```
+    pub fn track_process(&self, pid: i32) -> Result<()>{
+        self.monitor().track_process(pid, None)
+    }
+
+    /// Same as `track_process` but it also tracks all the descendants of the process, including the ones created
+    /// after this call. Useful for supervisors tracking workers that fork. Children are measured from the moment
+    /// they are found, so a new child reports 0% CPU on its first sample. The aggregated CPU also includes the
+    /// children waited by the tree since the last call, so short-lived children that were never sampled are counted.
+    /// Aggregated memory is the sum of every process, so pages shared between them are counted more than once
+    /// Example
+    /// ```
+    /// use machine_info::{Machine, ChildTracking};
+    /// let m = Machine::new();
+    /// let process_pid = std::process::id() as i32;
+    /// m.track_process_tree(process_pid, ChildTracking::Aggregate).unwrap();
+    /// ```
+    pub fn track_process_tree(&self, pid: i32, children: ChildTracking) -> Result<()>{
+        self.monitor().track_process(pid, Some(children))
+    }
+
+    /// Tracks a named group of processes, like the master and workers of nginx, reported as a single entry of
+    /// `processes_status` with the CPU, memory and I/O of all of them added up and `group` set to the name.
+    /// Members that exit leave the group, once all of them exited the group is reported as exited and removed.
+    /// Tracking a group with the same name replaces it. It fails if any PID is invalid
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// m.track_group("myself", &[std::process::id() as i32]).unwrap();
+    /// let processes = m.processes_status();
+    /// assert_eq!(processes[0].group.as_deref(), Some("myself"));
+    /// ```
+    pub fn track_group(&self, name: &str, pids: &[i32]) -> Result<()> {
+        self.monitor().track_group(name, pids)
+    }
+
+    /// Stops tracking a group. If there is no group with that name it does nothing
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// m.track_group("myself", &[std::process::id() as i32]).unwrap();
+    /// m.untrack_group("myself");
     /// ```
-    pub fn track_process(&mut self, pid: i32) -> Result<()>{
-        self.monitor.track_process(pid)
+    pub fn untrack_group(&self, name: &str) {
+        self.monitor().untrack_group(name);
     }
 
     /// Once we dont need to track a process it is recommended to not keep using resources on it. You should know the PID of your process.
@@ -380,24 +1309,28 @@ impl Machine {
     /// ```
     /// use machine_info::Machine;
     /// let m = Machine::new();
-    /// let process_pid = 3218;
-    /// m.track_process(process_pid)
-    /// m.untrack_process(process_pid)
+    /// let process_pid = std::process::id() as i32;
+    /// m.track_process(process_pid).unwrap();
+    /// m.untrack_process(process_pid);
     /// ```
-    pub fn untrack_process(&mut self, pid: i32) {
-        self.monitor.untrack_process(pid);
+    pub fn untrack_process(&self, pid: i32) {
+        self.monitor().untrack_process(pid);
     }
 
     /// The CPU usage of all tracked processes since the last call. So if you call it every 10 seconds, you will
-    /// get the CPU usage during the last 10 seconds. More calls will make the value more accurate but also more expensive
+    /// get the CPU usage during the last 10 seconds. More calls will make the value more accurate but also more expensive.
+    /// Calls sooner than the `MonitorConfig` minimum interval return the last values.
+    /// The resident, virtual and shared memory of each process are the values at this moment. A tracked process that
+    /// finished is reported once with the `Exited` state and then untracked. The usage of the Nvidia cards is included
+    /// for every process using them, unless the machine is degraded
     /// Example
-    /// ```
+    /// ```no_run
     /// use machine_info::Machine;
     /// use std::{thread, time};
     /// 
     /// let m = Machine::new();
-    /// m.track_process(3218)
-    /// m.track_process(4467)
+    /// m.track_process(3218).unwrap();
+    /// m.track_process(4467).unwrap();
     /// loop {   
     ///   let status = m.processes_status();
     ///   println!("{:?}", status);
@@ -405,20 +1338,201 @@ impl Machine {
     /// }
     /// 
     /// ```
-    pub fn processes_status(& mut self) -> Vec<Process> {
-        self.monitor.next_processes().iter().map(|(pid, cpu)| Process{pid:*pid, cpu:*cpu}).collect::<Vec<Process>>()
+    pub fn processes_status(&self) -> Vec<Process> {
+        let mut last_processes = lock(&self.last_processes);
+        match &*last_processes {
+            Some((when, processes)) if self.throttled(*when) => processes.clone(),
+            _ => {
+                let mut processes = self.monitor().next_processes();
+                if !processes.is_empty() {
+                    let mut graphics = self.processes_graphics();
+                    for process in processes.iter_mut() {
+                        if let Some(usage) = graphics.remove(&(process.pid as u32)) {
+                            process.graphics = usage;
+                        }
+                    }
+                }
+                *last_processes = Some((host::instant(), processes.clone()));
+                processes
+            }
+        }
+    }
+
+    /// Same as `processes_status` with `Process.cpu` in the given scale instead of the `process_scale` of the
+    /// machine, for consumers expecting a different convention than the rest of the application
+    /// Example
+    /// ```
+    /// use machine_info::{Machine, CpuScale};
+    /// let m = Machine::new();
+    /// m.track_process(std::process::id() as i32).unwrap();
+    /// let per_core = m.processes_status_scaled(CpuScale::PerCore);
+    /// let normalized = m.processes_status_scaled(CpuScale::Normalized);
+    /// assert!(normalized[0].cpu <= per_core[0].cpu);
+    /// ```
+    pub fn processes_status_scaled(&self, scale: CpuScale) -> Vec<Process> {
+        let mut processes = self.processes_status();
+        if scale != self.process_scale && !processes.is_empty() {
+            let cores = monitor::cores() as f64;
+            for process in processes.iter_mut() {
+                process.cpu = match scale {
+                    CpuScale::Normalized => process.cpu / cores,
+                    CpuScale::PerCore => process.cpu * cores
+                };
+            }
+        }
+        processes
+    }
+
+    /// Tracks everything inside a cgroup (version 2) as a unit, like a systemd service or a container. The path
+    /// can be relative to the cgroup root (`system.slice/nginx.service`) or absolute (`/sys/fs/cgroup/...`)
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// m.track_cgroup("system.slice/nginx.service").unwrap();
+    /// ```
+    pub fn track_cgroup(&self, path: &str) -> Result<()> {
+        self.monitor().track_cgroup(path)
+    }
+
+    /// Stops tracking a cgroup. If it was not tracked, it will just do nothing
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// m.track_cgroup("system.slice/nginx.service").unwrap();
+    /// m.untrack_cgroup("system.slice/nginx.service");
+    /// ```
+    pub fn untrack_cgroup(&self, path: &str) {
+        self.monitor().untrack_cgroup(path);
+    }
+
+    /// CPU, memory and I/O usage of the tracked cgroups since the last call, like `processes_status` does for
+    /// processes. A cgroup that cannot be read (a stopped service) is skipped but kept tracked
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// use std::{thread, time};
+    ///
+    /// let m = Machine::new();
+    /// m.track_cgroup("system.slice/nginx.service").unwrap();
+    /// loop {
+    ///   println!("{:?}", m.cgroups_status());
+    ///   thread::sleep(time::Duration::from_millis(1000));
+    /// }
+    /// ```
+    pub fn cgroups_status(&self) -> Vec<CgroupStatus> {
+        self.monitor().next_cgroups()
+    }
+
+    /// All the processes running, like a one-shot `top`, without tracking them first. The CPU usage is the one since
+    /// the last call, so the first call reports 0 for every process. Sorted by PID
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let processes = m.all_processes();
+    /// assert!(processes.iter().any(|p| p.pid == std::process::id() as i32));
+    /// ```
+    pub fn all_processes(&self) -> Vec<ProcessEntry> {
+        let mut process_table = lock(&self.process_table);
+        let system = process_table.get_or_insert_with(System::new);
+        system.refresh_processes_specifics(ProcessesToUpdate::All, true,
+            ProcessRefreshKind::nothing().without_tasks().with_cpu().with_memory());
+        let mut processes = system.processes().values()
+            .filter(|process| process.thread_kind().is_none())
+            .map(|process| ProcessEntry {
+                pid: process.pid().as_u32() as i32,
+                parent: process.parent().map(|parent| parent.as_u32() as i32),
+                name: process.name().to_string_lossy().to_string(),
+                cpu: process.cpu_usage() as f64,
+                memory: process.memory(),
+                virtual_memory: process.virtual_memory(),
+            })
+            .collect::<Vec<ProcessEntry>>();
+        processes.sort_by_key(|process| process.pid);
+        processes
+    }
+
+    /// Usage of the Nvidia cards by PID. Empty when degraded, since process stats are expensive
+    fn processes_graphics(&self) -> HashMap<u32, Vec<ProcessGraphicsUsage>> {
+        let mut result: HashMap<u32, Vec<ProcessGraphicsUsage>> = HashMap::new();
+        let nvml = match self.nvml() {
+            Some(nvml) if !self.is_degraded() => nvml,
+            _ => return result
+        };
+        let device_count = match nvml.device_count() {
+            Ok(count) => count,
+            Err(e) => {
+                debug!("Failed to get NVIDIA device count in processes_status: {}", e);
+                return result;
+            }
+        };
+        for n in 0..device_count {
+            let device = match nvml.device_by_index(n) {
+                Ok(dev) => dev,
+                Err(e) => {
+                    debug!("Failed to get NVIDIA device at index {} in processes_status: {}", n, e);
+                    continue;
+                }
+            };
+            let id = match device.uuid() {
+                Ok(u) => u,
+                Err(e) => {
+                    debug!("Failed to get GPU UUID in processes_status: {}", e);
+                    continue;
+                }
+            };
+            // A process can appear as compute and graphics at the same time, the memory is the same
+            let mut memory_used = HashMap::new();
+            for info in device.running_compute_processes().unwrap_or_default().into_iter()
+                .chain(device.running_graphics_processes().unwrap_or_default()) {
+                if let UsedGpuMemory::Used(used) = info.used_gpu_memory {
+                    memory_used.insert(info.pid, used);
+                }
+            }
+            let mut usages: HashMap<u32, ProcessGraphicsUsage> = HashMap::new();
+            for pid in memory_used.keys() {
+                usages.insert(*pid, ProcessGraphicsUsage {
+                    id: id.clone(),
+                    gpu: 0,
+                    memory: 0,
+                    memory_used: memory_used.get(pid).copied(),
+                    encoder: 0,
+                    decoder: 0
+                });
+            }
+            for p in self.process_samples(&device, &id) {
+                let usage = usages.entry(p.pid).or_insert_with(|| ProcessGraphicsUsage {
+                    id: id.clone(),
+                    gpu: 0,
+                    memory: 0,
+                    memory_used: memory_used.get(&p.pid).copied(),
+                    encoder: 0,
+                    decoder: 0
+                });
+                usage.gpu = p.sm_util;
+                usage.memory = p.mem_util;
+                usage.encoder = p.enc_util;
+                usage.decoder = p.dec_util;
+            }
+            for (pid, usage) in usages {
+                result.entry(pid).or_default().push(usage);
+            }
+        }
+        result
     }
 
     /// The CPU and memory usage. For the CPU, it is the same as for `processes_status`. For the memory it returs the amount
     /// a this moment
     /// Example
-    /// ```
+    /// ```no_run
     /// use machine_info::Machine;
     /// use std::{thread, time};
     /// 
     /// let m = Machine::new();
-    /// m.track_process(3218)
-    /// m.track_process(4467)
+    /// m.track_process(3218).unwrap();
+    /// m.track_process(4467).unwrap();
     /// loop {   
     ///   let status = m.system_status();
     ///   println!("{:?}", status);
@@ -426,12 +1540,246 @@ impl Machine {
     /// }
     /// 
     /// ```
-    pub fn system_status(& mut self) -> Result<SystemStatus> {
-        let (cpu, memory) = self.monitor.next()?;
-        Ok(SystemStatus {
-            memory,
+    pub fn system_status(&self) -> Result<SystemStatus> {
+        let mut last_status = lock(&self.last_status);
+        if let Some((when, status)) = &*last_status {
+            if self.throttled(*when) {
+                return Ok(SystemStatus { degraded: true, ..status.clone() });
+            }
+            // Calls coming faster than the minimum interval, like several subsystems sharing the machine, get the
+            // same status instead of deltas over a few milliseconds
+            if host::instant().duration_since(*when) < self.status_interval {
+                return Ok(status.clone());
+            }
+        }
+
+        let (cpu, memory) = self.monitor().next()?;
+        if let Some(protection) = &self.load_protection {
+            let degraded = cpu >= protection.cpu_threshold;
+            if self.degraded.swap(degraded, Ordering::Relaxed) != degraded {
+                info!("High load protection {}", if degraded { "enabled" } else { "disabled" });
+            }
+        }
+        let status = SystemStatus {
+            memory: memory.usage(),
+            memory_breakdown: memory.breakdown(),
             cpu,
+            degraded: self.is_degraded(),
+            cgroup_memory: cgroup::memory().ok(),
+            uptime: System::uptime(),
+        };
+        if let Some(history) = &self.history {
+            lock(history).record_status(&status);
+        }
+        *last_status = Some((host::instant(), status.clone()));
+        Ok(status)
+    }
+
+    /// System information, system status, graphics status and processes status taken together, so a single
+    /// self-contained record can be emitted per tick
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let snapshot = m.snapshot().unwrap();
+    /// println!("{} {}%", snapshot.timestamp, snapshot.status.cpu);
+    /// ```
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        let status = self.system_status()?;
+        Ok(Snapshot {
+            timestamp: host::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            info: self.system_info(),
+            status,
+            graphics: self.graphics_status(),
+            processes: self.processes_status(),
+        })
+    }
+
+    /// Same as `snapshot` but as a JSON document. It requires the `serde` feature
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{}", m.snapshot_json().unwrap());
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn snapshot_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self.snapshot()?)?)
+    }
+
+    /// Enables (or disables with `None`) the self protection under high load. The load is checked on every
+    /// `system_status` call, so it only works if you call it periodically
+    /// Example
+    /// ```
+    /// use machine_info::{Machine, LoadProtection};
+    /// let mut m = Machine::new();
+    /// m.set_load_protection(Some(LoadProtection::default()));
+    /// println!("{:?}", m.system_status());
+    /// ```
+    pub fn set_load_protection(&mut self, protection: Option<LoadProtection>) {
+        if protection.is_none() {
+            self.degraded.store(false, Ordering::Relaxed);
+        }
+        self.load_protection = protection;
+    }
+
+    /// Sets which disks are excluded from `system_info().disks`. By default nothing is excluded
+    /// Example
+    /// ```
+    /// use machine_info::{Machine, DiskFilter};
+    /// let mut m = Machine::new();
+    /// m.set_disk_filter(DiskFilter::recommended());
+    /// println!("{:?}", m.system_info().disks);
+    /// ```
+    pub fn set_disk_filter(&mut self, filter: DiskFilter) {
+        self.disk_filter = filter;
+    }
+
+    /// Keeps (or stops keeping with `None`) the last `capacity` samples of `system_status` and `graphics_status`
+    /// (per card), so `history` can summarize them
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let mut m = Machine::new();
+    /// m.set_history(Some(600));
+    /// ```
+    pub fn set_history(&mut self, capacity: Option<usize>) {
+        self.history = capacity.map(|capacity| Mutex::new(History::new(capacity)));
+    }
+
+    /// Minimum, maximum and average of the CPU, memory and graphic cards usage sampled during the last `window`.
+    /// None if the history is disabled or there are no samples in the window
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// use std::time::Duration;
+    /// let mut m = Machine::new();
+    /// m.set_history(Some(600));
+    /// m.system_status().unwrap();
+    /// let summary = m.history(Duration::from_secs(300)).unwrap();
+    /// println!("CPU average {:.1}% max {:.1}%", summary.cpu.average, summary.cpu.max);
+    /// ```
+    pub fn history(&self, window: Duration) -> Option<HistorySummary> {
+        lock(self.history.as_ref()?).summary(window)
+    }
+
+    /// True if the sampling is currently throttled because of high load
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// println!("{}", m.is_degraded());
+    /// ```
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// Usage of an Nvidia card. None if it cannot be read
+    fn card_usage(&self, device: &Device) -> Option<GraphicsUsage> {
+        let uuid = match device.uuid() {
+            Ok(u) => u,
+            Err(e) => {
+                debug!("Failed to get GPU UUID in graphics_status: {}", e);
+                return None;
+            }
+        };
+
+        let mut processes = Vec::new();
+        // Process stats are expensive, skip them under high load
+        let stats = if self.is_degraded() {
+            vec![]
+        } else {
+            self.process_samples(device, &uuid)
+        };
+        for p in stats {
+            processes.push(GraphicsProcessUtilization{
+                pid: p.pid,
+                gpu: p.sm_util,
+                memory: p.mem_util,
+                encoder: p.enc_util,
+                decoder: p.dec_util
+            });
+        }
+
+        let mut errors = vec![];
+        let memory_used = self.gpu_field(device.memory_info().map(|m| m.used), "memory", &mut errors)?;
+        let encoder = self.gpu_field(device.encoder_utilization().map(|e| e.utilization), "encoder", &mut errors)?;
+        let decoder = self.gpu_field(device.decoder_utilization().map(|d| d.utilization), "decoder", &mut errors)?;
+        let (gpu, memory_usage) = self.gpu_field(device.utilization_rates().map(|r| (r.gpu, r.memory)), "utilization", &mut errors)?;
+        let temperature = self.gpu_field(device.temperature(TemperatureSensor::Gpu), "temperature", &mut errors)?;
+        Some(GraphicsUsage {
+            id: uuid,
+            memory_used,
+            encoder,
+            decoder,
+            gpu,
+            memory_usage,
+            temperature,
+            soc_temperatures: vec![],
+            processes,
+            errors
         })
     }
 
+    /// Process utilization samples of a card, the newest one of every process. With `new_gpu_samples_only` only
+    /// the samples taken since the newest one already known are fetched and merged into the ones of the previous
+    /// calls, so every caller (`graphics_status`, `processes_status`...) gets the same utilization
+    fn process_samples(&self, device: &Device, id: &str) -> Vec<ProcessUtilizationSample> {
+        let mut cache = lock(&self.gpu_samples);
+        let newest = cache.entry(id.to_string()).or_default();
+        if !self.new_gpu_samples_only {
+            newest.clear();
+        }
+        let last_seen = newest.values().map(|sample| sample.timestamp).max();
+        // NVML fails with NotFound when there is no sample newer than the timestamp
+        let samples = device.process_utilization_stats(last_seen).unwrap_or_default();
+        // The driver keeps several samples per process, the newest one is the current utilization
+        for sample in samples {
+            match newest.get(&sample.pid) {
+                Some(seen) if seen.timestamp >= sample.timestamp => {},
+                _ => {
+                    newest.insert(sample.pid, sample);
+                }
+            }
+        }
+        // Processes without recent samples are idle or exited. The timestamps are in microseconds of the CPU clock
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64;
+        let max_age = GPU_SAMPLE_MAX_AGE.as_micros() as u64;
+        newest.retain(|_, sample| now.saturating_sub(sample.timestamp) <= max_age);
+        let mut samples: Vec<ProcessUtilizationSample> = newest.values().cloned().collect();
+        samples.sort_by_key(|sample| sample.pid);
+        samples
+    }
+
+    /// Value of a card field. On failure the card is skipped (None), unless partial results are enabled: then the
+    /// error is recorded and the default value is used
+    fn gpu_field<T: Default>(&self, result: Result<T, NvmlError>, field: &str, errors: &mut Vec<String>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                debug!("Failed to get GPU {}: {}", field, e);
+                if !self.partial_results {
+                    return None;
+                }
+                errors.push(format!("{}: {}", field, e));
+                Some(T::default())
+            }
+        }
+    }
+
+    pub(crate) fn monitor(&self) -> MutexGuard<'_, Monitor> {
+        lock(&self.monitor)
+    }
+
+    pub(crate) fn nvml(&self) -> Option<&Nvml> {
+        self.nvml.get().filter(|_| host::gpu_available())
+    }
+
+    fn throttled(&self, last_sample: Instant) -> bool {
+        match &self.load_protection {
+            Some(protection) if self.is_degraded() => host::instant().duration_since(last_sample) < protection.min_interval,
+            _ => false
+        }
+    }
+
 }
\ No newline at end of file
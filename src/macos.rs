@@ -0,0 +1,36 @@
+use std::ffi::CString;
+use std::path::Path;
+use log::debug;
+
+const VIDEO_TOOLBOX_PATH: &str = "/System/Library/Frameworks/VideoToolbox.framework";
+
+/// String value of a sysctl, like hw.model
+fn sysctl(name: &str) -> Option<String> {
+    let name = CString::new(name).ok()?;
+    let mut size: libc::size_t = 0;
+    // The first call only returns the size of the value
+    let result = unsafe { libc::sysctlbyname(name.as_ptr(), std::ptr::null_mut(), &mut size, std::ptr::null_mut(), 0) };
+    if result != 0 || size == 0 {
+        debug!("Cannot read sysctl {:?}: {}", name, std::io::Error::last_os_error());
+        return None;
+    }
+    let mut value = vec![0u8; size];
+    let result = unsafe { libc::sysctlbyname(name.as_ptr(), value.as_mut_ptr() as *mut libc::c_void, &mut size, std::ptr::null_mut(), 0) };
+    if result != 0 {
+        debug!("Cannot read sysctl {:?}: {}", name, std::io::Error::last_os_error());
+        return None;
+    }
+    value.truncate(size);
+    let end = value.iter().position(|&c| c == 0).unwrap_or(value.len());
+    Some(String::from_utf8_lossy(&value[..end]).to_string()).filter(|value| !value.is_empty())
+}
+
+/// Model identifier of the Mac, like MacBookPro18,3
+pub fn model() -> Option<String> {
+    sysctl("hw.model")
+}
+
+/// VideoToolbox is the hardware video encoding and decoding framework of macOS
+pub fn video_toolbox() -> bool {
+    Path::new(VIDEO_TOOLBOX_PATH).exists()
+}
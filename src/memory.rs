@@ -0,0 +1,127 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use log::debug;
+use crate::model::{HugepagesPool, MemoryDetails, NumaNode};
+use crate::host;
+
+const HUGEPAGES_PATH: &str = "/sys/kernel/mm/hugepages";
+const TRANSPARENT_HUGEPAGES_PATH: &str = "/sys/kernel/mm/transparent_hugepage/enabled";
+const NUMA_NODES_PATH: &str = "/sys/devices/system/node";
+
+/// Reads a sysfs file containing a single number
+pub fn read_number(path: &Path) -> Result<u64> {
+    Ok(fs::read_to_string(path)?.trim().parse::<u64>()?)
+}
+
+/// Selected value of a sysfs option list like "always [madvise] never"
+pub fn selected_option(raw: &str) -> Option<String> {
+    let start = raw.find('[')?;
+    let end = raw[start..].find(']')? + start;
+    Some(raw[start + 1..end].to_string())
+}
+
+/// Value of a /proc/meminfo field in bytes
+pub fn meminfo_value(meminfo: &str, field: &str) -> Option<u64> {
+    meminfo.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| *name == field)
+        .and_then(|(_, value)| {
+            let mut parts = value.split_whitespace();
+            let number = parts.next()?.parse::<u64>().ok()?;
+            match parts.next() {
+                Some("kB") => Some(number * 1024),
+                _ => Some(number)
+            }
+        })
+}
+
+fn hugepages_pool(path: &Path) -> Result<HugepagesPool> {
+    // Directories are named like hugepages-2048kB
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let size = name.trim_start_matches("hugepages-").trim_end_matches("kB").parse::<u64>()?;
+    Ok(HugepagesPool {
+        page_size: size * 1024,
+        total: read_number(&path.join("nr_hugepages"))?,
+        free: read_number(&path.join("free_hugepages"))?,
+        reserved: read_number(&path.join("resv_hugepages"))?,
+        surplus: read_number(&path.join("surplus_hugepages"))?,
+    })
+}
+
+pub fn memory_details() -> Result<MemoryDetails> {
+    let meminfo = fs::read_to_string(host::path("/proc/meminfo"))?;
+
+    let mut hugepages = Vec::new();
+    match fs::read_dir(host::path(HUGEPAGES_PATH)) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                match hugepages_pool(&entry.path()) {
+                    Ok(pool) => hugepages.push(pool),
+                    Err(e) => debug!("Failed to read hugepages pool {:?}: {}", entry.path(), e)
+                }
+            }
+        },
+        Err(e) => debug!("Hugepages not available: {}", e)
+    }
+    hugepages.sort_by_key(|pool| pool.page_size);
+
+    let transparent_hugepages = fs::read_to_string(host::path(TRANSPARENT_HUGEPAGES_PATH))
+        .map_err(|e| debug!("Transparent hugepages not available: {}", e))
+        .ok()
+        .and_then(|raw| selected_option(&raw));
+
+    Ok(MemoryDetails {
+        default_hugepage_size: meminfo_value(&meminfo, "Hugepagesize").unwrap_or(0),
+        hugepages,
+        transparent_hugepages,
+    })
+}
+
+/// Parses kernel lists like "0-3,8,10-11"
+pub fn parse_list(raw: &str) -> Result<Vec<usize>> {
+    let mut values = Vec::new();
+    for range in raw.trim().split(',').filter(|r| !r.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => values.extend(start.parse::<usize>()?..=end.parse::<usize>()?),
+            None => values.push(range.parse::<usize>()?)
+        }
+    }
+    Ok(values)
+}
+
+fn numa_node(path: &Path, id: usize) -> Result<NumaNode> {
+    // Lines look like "Node 0 MemTotal:       5471992 kB", so the node prefix is removed
+    let meminfo = fs::read_to_string(path.join("meminfo"))?
+        .lines()
+        .filter_map(|line| line.splitn(3, ' ').nth(2))
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let total = meminfo_value(&meminfo, "MemTotal")
+        .ok_or_else(|| anyhow::anyhow!("NUMA node {} has no MemTotal", id))?;
+    let free = meminfo_value(&meminfo, "MemFree").unwrap_or(0);
+    Ok(NumaNode {
+        id,
+        cpus: parse_list(&fs::read_to_string(path.join("cpulist"))?)?,
+        memory_total: total,
+        memory_free: free,
+        memory_used: total.saturating_sub(free),
+    })
+}
+
+pub fn numa_nodes() -> Result<Vec<NumaNode>> {
+    let mut nodes = Vec::new();
+    for entry in fs::read_dir(host::path(NUMA_NODES_PATH))?.flatten() {
+        let name = entry.file_name();
+        let id = match name.to_str().and_then(|n| n.strip_prefix("node")).and_then(|n| n.parse::<usize>().ok()) {
+            Some(id) => id,
+            None => continue
+        };
+        match numa_node(&entry.path(), id) {
+            Ok(node) => nodes.push(node),
+            Err(e) => debug!("Failed to read NUMA node {}: {}", id, e)
+        }
+    }
+    nodes.sort_by_key(|node| node.id);
+    Ok(nodes)
+}
@@ -1,8 +1,9 @@
+#[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
 /// System status
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct DiskUsage {
     /// Name of the disk
     pub name: String,
@@ -13,19 +14,121 @@ pub struct DiskUsage {
 }
 
 /// Process usage
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct Process {
     /// Process identificator
     pub pid: i32,
-    /// Cpu used as percentage
+    /// Process name (limited by the kernel to 15 characters)
+    pub name: String,
+    /// Scheduling state
+    pub state: ProcessState,
+    /// Nice value, from -20 (highest priority) to 19 (lowest)
+    pub nice: i32,
+    /// Path of the executable. None if it cannot be read (usually processes of other users)
+    pub executable: Option<String>,
+    /// Command line arguments, starting with the program. Empty for kernel threads and zombies
+    pub command_line: Vec<String>,
+    /// Cpu used as percentage, of one core by default (see `MonitorConfig`)
+    pub cpu: f64,
+    /// Resident memory (RSS) in bytes
+    pub memory: u64,
+    /// Virtual memory in bytes
+    pub virtual_memory: u64,
+    /// Resident shared memory (file backed pages) in bytes
+    pub shared_memory: u64,
+    /// Unix timestamp (seconds) when the process started. A value changing between calls for the same
+    /// tracked PID means the PID was reused
+    pub start_time: Option<u64>,
+    /// Seconds the process has been running
+    pub uptime: Option<u64>,
+    /// Bytes read from storage per second since the last call. None if /proc/<pid>/io cannot be read (usually
+    /// processes of other users)
+    pub read_bytes_per_second: Option<f64>,
+    /// Bytes written to storage per second since the last call. None if /proc/<pid>/io cannot be read
+    pub write_bytes_per_second: Option<f64>,
+    /// Open file descriptors. None if they cannot be listed (usually processes of other users). For aggregated
+    /// trees, the ones of the tracked process
+    pub open_files: Option<u64>,
+    /// Soft limit of open file descriptors (EMFILE is returned once reached). None if unlimited or unknown
+    pub open_files_soft_limit: Option<u64>,
+    /// Hard limit of open file descriptors. None if unlimited or unknown
+    pub open_files_hard_limit: Option<u64>,
+    /// Usage of the Nvidia graphic cards by this PID. Empty if it uses none
+    pub graphics: Vec<ProcessGraphicsUsage>,
+    /// Tracked process this one descends from, when it is reported as a child of it
+    pub parent: Option<i32>,
+    /// Number of live child processes found for a process tracked with its children. With aggregated
+    /// tracking, their usage is included in this entry. For a group, the other live members
+    pub children: u32,
+    /// Name of the group when the entry aggregates a group of processes (see `Machine::track_group`). The
+    /// other fields, like the name, are the ones of its first live member
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub group: Option<String>,
+}
+
+/// Entry of the full process table
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct ProcessEntry {
+    /// Process identificator
+    pub pid: i32,
+    /// Parent process identificator
+    pub parent: Option<i32>,
+    /// Process name
+    pub name: String,
+    /// Cpu used as percentage of one core since the last call
     pub cpu: f64,
-    
+    /// Resident memory (RSS) in bytes
+    pub memory: u64,
+    /// Virtual memory in bytes
+    pub virtual_memory: u64,
+}
+
+/// Scheduling state of a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum ProcessState {
+    /// Running or runnable
+    Running,
+    /// Interruptible sleep, waiting for an event
+    Sleeping,
+    /// Uninterruptible sleep, usually waiting for I/O
+    DiskSleep,
+    /// Stopped by a signal or a debugger
+    Stopped,
+    /// Finished but not yet waited by its parent
+    Zombie,
+    /// Being destroyed
+    Dead,
+    /// Idle kernel thread
+    Idle,
+    /// The process does not exist anymore (or its PID was reused by another one). It is reported once by
+    /// `processes_status` and then it is not tracked anymore
+    Exited,
+    /// State not known by this crate
+    Unknown,
+}
+
+impl ProcessState {
+    /// State from the letter used in /proc/<pid>/stat
+    pub fn from_code(code: char) -> ProcessState {
+        match code {
+            'R' => ProcessState::Running,
+            'S' => ProcessState::Sleeping,
+            'D' => ProcessState::DiskSleep,
+            'T' | 't' => ProcessState::Stopped,
+            'Z' => ProcessState::Zombie,
+            'X' | 'x' => ProcessState::Dead,
+            'I' => ProcessState::Idle,
+            _ => ProcessState::Unknown,
+        }
+    }
 }
 
 /// Graphic card usage by process
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct GraphicsProcessUtilization {
     /// Process identificator
     pub pid: u32,
@@ -39,9 +142,27 @@ pub struct GraphicsProcessUtilization {
     pub decoder: u32    
 }
 
+/// Usage of one graphic card by a tracked process
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct ProcessGraphicsUsage {
+    /// Graphic card id
+    pub id: String,
+    /// Gpu utilization as percentage
+    pub gpu: u32,
+    /// Memory controller utilization as percentage
+    pub memory: u32,
+    /// Memory (VRAM) used in bytes. None if the driver does not report it
+    pub memory_used: Option<u64>,
+    /// Gpu encoder utilization as percentage
+    pub encoder: u32,
+    /// Gpu decoder utilization as percentage
+    pub decoder: u32,
+}
+
 /// Graphic card usage summary
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct GraphicsUsage {
     /// Graphic card id
     pub id: String,
@@ -57,23 +178,125 @@ pub struct GraphicsUsage {
     pub gpu: u32,
     /// Gpu temperature
     pub temperature: u32,
+    /// Temperatures of the rest of the chip (CPU, SoC, junction...) when the GPU is part of a SoC like the Jetson
+    /// boards. Empty for discrete cards
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub soc_temperatures: Vec<ThermalSensor>,
     /// Processes using this GPU
-    pub processes: Vec<GraphicsProcessUtilization>
+    pub processes: Vec<GraphicsProcessUtilization>,
+    /// Fields that could not be read, as `field: error`. Filled in partial results mode, where those fields are 0
+    /// instead of the card being skipped, and for the fields that integrated GPUs (Jetson) do not report
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub errors: Vec<String>
+}
+
+/// False if the field is in the errors of a partial result
+fn is_read(errors: &[String], field: &str) -> bool {
+    !errors.iter().any(|error| error.split(':').next() == Some(field))
+}
+
+impl GraphicsUsage {
+    /// False if the field could not be read in partial results mode, so its value is a placeholder 0. The fields
+    /// are named as in `errors`: memory (`memory_used`), encoder, decoder, utilization (`gpu` and `memory_usage`)
+    /// and temperature
+    pub fn is_read(&self, field: &str) -> bool {
+        is_read(&self.errors, field)
+    }
 }
 
 /// System global utilization
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct SystemStatus {
     /// Total memory used
     pub memory: i32,
-    /// Total CPU used as percentage
+    /// Detailed memory usage
+    pub memory_breakdown: MemoryBreakdown,
+    /// Total CPU used as percentage, of all the cores by default (see `MonitorConfig`)
     pub cpu: i32,
+    /// True if the sampling was throttled because of high load. Values may be outdated
+    pub degraded: bool,
+    /// Memory limit and usage of the cgroup this process belongs to
+    pub cgroup_memory: Option<CgroupMemory>,
+    /// Seconds since the system booted
+    pub uptime: u64,
+}
+
+/// Usage sampled by a `Sampler`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Sample {
+    /// Unix timestamp (seconds) when it was taken
+    pub timestamp: u64,
+    /// Global CPU and memory usage
+    pub status: SystemStatus,
+    /// Usage of the graphic cards
+    pub graphics: Vec<GraphicsUsage>,
+    /// Usage of the tracked processes
+    pub processes: Vec<Process>,
+}
+
+/// Minimum, maximum and average of a value over a period
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Statistics {
+    /// Lowest value
+    pub min: f64,
+    /// Highest value
+    pub max: f64,
+    /// Average of the samples
+    pub average: f64,
+}
+
+/// Statistics of the samples kept in the history during a period
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct HistorySummary {
+    /// Number of `system_status` samples in the period
+    pub samples: usize,
+    /// CPU usage as percentage
+    pub cpu: Statistics,
+    /// Memory used as percentage of the total
+    pub memory: Statistics,
+    /// Statistics of every graphic card sampled in the period
+    pub graphics: Vec<GraphicsHistory>,
+}
+
+/// Statistics of a graphic card kept in the history
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct GraphicsHistory {
+    /// Graphic card id
+    pub id: String,
+    /// Number of `graphics_status` samples of this card in the period
+    pub samples: usize,
+    /// Gpu utilization as percentage. None if it could not be read in any sample
+    pub gpu: Option<Statistics>,
+    /// Memory utilization as percentage. None if it could not be read in any sample
+    pub memory_usage: Option<Statistics>,
+    /// Gpu temperature. None if it could not be read in any sample
+    pub temperature: Option<Statistics>,
+}
+
+/// Telemetry record combining the system information and the usage at a given moment
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Snapshot {
+    /// Unix timestamp (seconds) when it was taken
+    pub timestamp: u64,
+    /// Hardware and system information
+    pub info: SystemInfo,
+    /// Global CPU and memory usage
+    pub status: SystemStatus,
+    /// Usage of the graphic cards
+    pub graphics: Vec<GraphicsUsage>,
+    /// Usage of the tracked processes
+    pub processes: Vec<Process>,
 }
 
 /// Summary of the system
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct SystemInfo {
     /// Operating system name
     pub os_name: String,
@@ -85,8 +308,10 @@ pub struct SystemInfo {
     pub hostname: String,
     /// Distribution id like ubuntu, neon, raspbian...
     pub distribution: String,
-    /// Total memory of the machine
+    /// Total memory of the machine. Inside containers it is the host memory
     pub memory: u64,
+    /// Memory limit of the cgroup (container, systemd service...) if it is lower than the total memory
+    pub memory_limit: Option<u64>,
     /// Microprocessor description
     pub processor: Processor,
     /// Total amount of processors
@@ -99,15 +324,206 @@ pub struct SystemInfo {
     pub cameras: Vec<Camera>,
     /// Nvidia driver info
     pub nvidia: Option<NvidiaInfo>,
-    /// If the machine supports vaapi
+    /// If the machine has a render node for vaapi, the same as `!vaapi_devices.is_empty()`
     pub vaapi: bool,
+    /// Render nodes usable by VA-API. The VA-API driver and profiles are only filled with the `vaapi` feature
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub vaapi_devices: Vec<VaapiInfo>,
+    /// If the machine supports VideoToolbox, the hardware video encoding and decoding of macOS
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub video_toolbox: bool,
     /// Machine model. Some machines has special models like rpi
-    pub model: Option<String>
+    pub model: Option<String>,
+    /// Network interfaces
+    pub networks: Vec<NetworkInterface>,
+    /// Hardware identity from DMI/SMBIOS. None on machines without DMI, like most ARM boards
+    pub hardware: Option<HardwareIdentity>,
+    /// Trusted Platform Module. None if the machine has no TPM or the kernel driver is not loaded
+    pub tpm: Option<TpmInfo>,
+    /// Virtual machine or container this system runs in
+    pub virtualization: Virtualization,
+    /// Active SELinux or AppArmor. They can deny reading some files and processes even as root
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub security_modules: Vec<SecurityModule>,
+    /// Unix timestamp (seconds) when the system booted
+    pub boot_time: u64,
+    /// Seconds since the system booted
+    pub uptime: u64
+}
+
+/// Value that changed between two snapshots
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Change<T> {
+    /// Value in the older snapshot
+    pub before: T,
+    /// Value in the newer snapshot
+    pub after: T,
+}
+
+/// Differences between two `SystemInfo` snapshots, like hardware added or removed between agent restarts
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct SystemInfoDiff {
+    /// Disks (by name and mount point) that appeared
+    pub disks_added: Vec<String>,
+    /// Disks that are gone
+    pub disks_removed: Vec<String>,
+    /// Graphic cards (by id) that appeared
+    pub graphics_added: Vec<String>,
+    /// Graphic cards that are gone
+    pub graphics_removed: Vec<String>,
+    /// Cameras (by path) that appeared
+    pub cameras_added: Vec<String>,
+    /// Cameras that are gone
+    pub cameras_removed: Vec<String>,
+    /// Network interfaces (by name) that appeared
+    pub networks_added: Vec<String>,
+    /// Network interfaces that are gone
+    pub networks_removed: Vec<String>,
+    /// Total memory
+    pub memory: Option<Change<u64>>,
+    /// Amount of processors
+    pub total_processors: Option<Change<usize>>,
+    /// Processor brand
+    pub processor: Option<Change<String>>,
+    /// Running kernel version
+    pub kernel_version: Option<Change<String>>,
+    /// Operating system version
+    pub os_version: Option<Change<String>>,
+}
+
+/// Hardware identity from the DMI/SMBIOS tables. Values not filled by the vendor are None
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct HardwareIdentity {
+    /// System vendor, like Dell Inc. or LENOVO
+    pub vendor: Option<String>,
+    /// Product name, like PowerEdge R740
+    pub product_name: Option<String>,
+    /// Product version
+    pub product_version: Option<String>,
+    /// Chassis type, like Desktop, Notebook or Rack Mount Chassis
+    pub chassis_type: Option<String>,
+    /// System serial number. Only readable by root
+    pub serial: Option<String>,
+    /// System UUID. Only readable by root
+    pub uuid: Option<String>,
+    /// BIOS/UEFI firmware
+    pub firmware: FirmwareInfo,
+    /// Motherboard. None if the vendor did not fill it
+    pub baseboard: Option<Baseboard>,
+}
+
+/// Mode of a Linux security module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum SecurityMode {
+    /// Denied accesses are blocked
+    Enforcing,
+    /// Denied accesses are only logged (complain mode of AppArmor)
+    Permissive,
+}
+
+/// Linux security module restricting what processes can access, like SELinux or AppArmor
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct SecurityModule {
+    /// selinux or apparmor
+    pub name: String,
+    /// None if the mode cannot be read, the AppArmor profiles are only readable by root
+    pub mode: Option<SecurityMode>,
+    /// SELinux policy, like targeted or mls
+    pub policy: Option<String>,
+}
+
+/// Virtualization technology the system runs in, detected like systemd-detect-virt does. Containers
+/// take precedence over the virtual machine they may run in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum Virtualization {
+    /// Not virtualized
+    BareMetal,
+    /// KVM virtual machine (including most clouds)
+    Kvm,
+    /// QEMU without KVM acceleration
+    Qemu,
+    /// VMware virtual machine
+    Vmware,
+    /// Microsoft Hyper-V virtual machine
+    HyperV,
+    /// Xen domain
+    Xen,
+    /// Oracle VirtualBox virtual machine
+    VirtualBox,
+    /// Other virtual machine, detected by the hypervisor CPU flag
+    OtherVm,
+    /// Windows Subsystem for Linux
+    Wsl,
+    /// Docker container
+    Docker,
+    /// Podman container
+    Podman,
+    /// LXC container
+    Lxc,
+    /// Other container (systemd-nspawn, rkt...)
+    OtherContainer,
+}
+
+impl Virtualization {
+    /// True for containers, which share the kernel of the host
+    pub fn is_container(&self) -> bool {
+        matches!(self, Virtualization::Docker | Virtualization::Podman | Virtualization::Lxc | Virtualization::OtherContainer)
+    }
+}
+
+/// Trusted Platform Module of the machine
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct TpmInfo {
+    /// TPM specification version, 1.2 or 2.0
+    pub version: String,
+    /// Device node like /dev/tpmrm0 (the resource manager of TPM 2.0) or /dev/tpm0
+    pub device: String,
+    /// True if the current user can open the device node
+    pub accessible: bool,
+    /// Description given by the firmware, like TPM 2.0 Device
+    pub description: Option<String>,
+}
+
+/// Motherboard (baseboard) details
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Baseboard {
+    /// Board manufacturer, like ASUSTeK COMPUTER INC.
+    pub manufacturer: Option<String>,
+    /// Board product name, like PRIME B450M-A
+    pub product: Option<String>,
+    /// Board revision
+    pub version: Option<String>,
+    /// Board serial number. Only readable by root
+    pub serial: Option<String>,
+}
+
+/// BIOS/UEFI firmware information
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct FirmwareInfo {
+    /// Firmware vendor, like American Megatrends Inc.
+    pub vendor: Option<String>,
+    /// Firmware version
+    pub version: Option<String>,
+    /// Release date as reported by the firmware, usually MM/DD/YYYY
+    pub release_date: Option<String>,
+    /// True if the system booted via UEFI, false for legacy BIOS
+    pub uefi: bool,
+    /// Secure Boot state. None if it cannot be read
+    pub secure_boot: Option<bool>,
 }
 
 /// Information about microprocessor
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct Processor {
     /// Processor clock speed
     pub frequency: u64,
@@ -118,11 +534,14 @@ pub struct Processor {
 }
 
 /// Information about a graphic card
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct GraphicCard {
     /// Device id
     pub id: String,
+    /// PCI address of the card, like 0000:65:00.0
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bus_id: Option<String>,
     /// Device id
     pub name: String,
     /// Device brand
@@ -130,12 +549,39 @@ pub struct GraphicCard {
     /// Total memory
     pub memory: u64,
     /// Device temperature
-    pub temperature: u32
+    pub temperature: u32,
+    /// Temperature in degrees at which the card starts hardware throttling. None if the card does not report it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub slowdown_temperature: Option<u32>,
+    /// Temperature in degrees at which the card shuts down to protect itself. None if the card does not report it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub shutdown_temperature: Option<u32>,
+    /// Maximum graphics clock in MHz
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_graphics_clock: Option<u32>,
+    /// Maximum memory clock in MHz
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_memory_clock: Option<u32>,
+    /// Width of the memory bus in bits
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub memory_bus_width: Option<u32>,
+    /// Fields that could not be read, as `field: error`. Only filled in partial results mode, where those fields
+    /// are 0 instead of the card being skipped
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub errors: Vec<String>
+}
+
+impl GraphicCard {
+    /// False if the field could not be read in partial results mode, so its value is a placeholder. The fields
+    /// are named as in `errors`: name, memory and temperature
+    pub fn is_read(&self, field: &str) -> bool {
+        is_read(&self.errors, field)
+    }
 }
 
 /// Information about a hard disk
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct Disk {
     /// Disk name
     pub name: String,
@@ -145,25 +591,554 @@ pub struct Disk {
     pub storage_type: String,
     /// Where it is mounted
     pub mount_point: String,
+    /// Physical device holding this filesystem (like sda for /dev/sda1). None for virtual filesystems
+    pub parent_device: Option<String>,
+    /// Model of the physical device
+    pub model: Option<String>,
+    /// Serial number of the physical device
+    pub serial: Option<String>,
+    /// Firmware revision of the physical device
+    pub firmware: Option<String>,
+    /// Mount options like rw,relatime,errors=remount-ro
+    pub mount_options: String,
+    /// Mounted as read only. A root filesystem remounted read only usually means disk errors
+    pub read_only: bool,
+    /// Binaries cannot be executed from this filesystem
+    pub noexec: bool,
+    /// setuid bits are ignored in this filesystem
+    pub nosuid: bool,
+    /// Encryption of the device holding this filesystem: LUKS1, LUKS2, dm-crypt (plain mode) or BitLocker.
+    /// None if it is not encrypted
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub encryption: Option<String>,
+    /// The filesystem lives in RAM: tmpfs, ramfs or a RAM block device (zram, brd). Its space is memory, so
+    /// capacity dashboards should not count it as storage
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub memory_backed: bool,
+    /// Compression stats when the filesystem is on a zram device
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub zram: Option<ZramStats>,
     /// Available space
     pub available: u64,
     /// Total size
     pub size: u64
 }
 
+/// Compressed RAM block device (zram) stats, from mm_stat
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct ZramStats {
+    /// Device name like zram0
+    pub device: String,
+    /// Compression algorithm like lz4 or zstd
+    pub algorithm: Option<String>,
+    /// Uncompressed size of the device in bytes
+    pub disk_size: u64,
+    /// Uncompressed size of the data stored in bytes
+    pub original_size: u64,
+    /// Compressed size of the data stored in bytes
+    pub compressed_size: u64,
+    /// Memory used to store the data, including the allocator overhead, in bytes
+    pub memory_used: u64,
+    /// Maximum memory the device can use in bytes. None if unlimited
+    pub memory_limit: Option<u64>,
+    /// Original size divided by the compressed size. None if empty
+    pub compression_ratio: Option<f64>,
+}
+
+/// Owner kind of a filesystem quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum QuotaKind {
+    /// Files owned by a user
+    User,
+    /// Files owned by a group
+    Group,
+    /// Files of a project (directory tree), usually on XFS
+    Project,
+}
+
+/// Usage and limits of a user, group or project in a filesystem with quotas
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Quota {
+    /// Owner kind
+    pub kind: QuotaKind,
+    /// User, group or project id
+    pub id: u32,
+    /// User or group name, from /etc/passwd and /etc/group, or project name from /etc/projid
+    pub name: Option<String>,
+    /// Space used in bytes
+    pub used: u64,
+    /// Space that can be exceeded during the grace period, in bytes. None if there is no limit
+    pub soft_limit: Option<u64>,
+    /// Space that cannot be exceeded, in bytes. None if there is no limit
+    pub hard_limit: Option<u64>,
+    /// Files (inodes) used
+    pub files: u64,
+    /// Files that can be exceeded during the grace period. None if there is no limit
+    pub files_soft_limit: Option<u64>,
+    /// Files that cannot be exceeded. None if there is no limit
+    pub files_hard_limit: Option<u64>,
+}
+
 /// Connected camera information
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct Camera {
     /// The camera name
     pub name: String,
     /// Camera path like /dev/video0
-    pub path: String
+    pub path: String,
+    /// Bus where the device is attached, like usb-0000:00:14.0-1
+    pub bus_info: Option<String>,
+    /// Serial number of USB cameras, if the device has one
+    pub serial: Option<String>,
+    /// True if the node can capture video. Metadata nodes of the same camera are false
+    pub capture: bool,
+    /// Supported pixel formats. Empty if the device cannot be opened or it is not a capture device
+    pub formats: Vec<CameraFormat>
+}
+
+/// Physical camera with all its video nodes
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CameraDevice {
+    /// Name of the capture node (or of the first node if none can capture)
+    pub name: String,
+    /// Bus where the device is attached
+    pub bus_info: Option<String>,
+    /// Serial number, if the device has one
+    pub serial: Option<String>,
+    /// Video nodes of the device. Use the ones with `capture` to get video
+    pub nodes: Vec<Camera>
+}
+
+/// Cameras plugged and unplugged between two calls to `refresh_cameras`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CameraChanges {
+    /// Cameras that appeared
+    pub added: Vec<Camera>,
+    /// Cameras that are not attached anymore
+    pub removed: Vec<Camera>
+}
+
+/// Pixel format supported by a camera
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CameraFormat {
+    /// Four character code like YUYV or MJPG
+    pub fourcc: String,
+    /// Format description given by the driver
+    pub description: String,
+    /// Supported resolutions. For ranges, only the minimum and maximum
+    pub resolutions: Vec<CameraResolution>
+}
+
+/// Camera resolution with its frame rates
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CameraResolution {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Supported frames per second. For ranges, only the minimum and maximum
+    pub frame_rates: Vec<f64>
+}
+
+/// Temperature sensor
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct ThermalSensor {
+    /// Where the value comes from, like hwmon1/temp2, thermal_zone0 or the GPU id
+    pub source: String,
+    /// Chip or zone type, like coretemp, nvme, acpitz or x86_pkg_temp
+    pub chip: String,
+    /// Sensor label, like Package id 0 or Composite. The zone type for thermal zones
+    pub label: String,
+    /// Current temperature in Celsius
+    pub temperature: f64,
+    /// Temperature in Celsius from which the device is considered hot (usually it starts throttling)
+    pub high: Option<f64>,
+    /// Critical temperature in Celsius (usually the device shuts down)
+    pub critical: Option<f64>,
+}
+
+/// Fan of a hwmon device
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Fan {
+    /// Where the value comes from, like hwmon2/fan1
+    pub source: String,
+    /// hwmon device, like hwmon2
+    pub hwmon: String,
+    /// Fan number in the hwmon device
+    pub index: u32,
+    /// Chip name, like nct6775 or dell_smm
+    pub chip: String,
+    /// Fan label, like CPU Fan. fanN if the driver has no label
+    pub label: String,
+    /// Current speed in RPM. A stopped or dead fan reports 0. None if it cannot be read
+    pub speed: Option<u64>,
+    /// Minimum speed in RPM from which the fan is considered failing
+    pub min_speed: Option<u64>,
+    /// PWM target duty cycle as percentage, if the fan is driven by a PWM output
+    pub duty: Option<u8>,
+    /// PWM mode (full, manual or automatic)
+    pub mode: Option<String>,
+}
+
+/// Battery of the machine
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Battery {
+    /// Power supply name, like BAT0
+    pub name: String,
+    /// Battery model
+    pub model: Option<String>,
+    /// Charge as percentage
+    pub capacity: Option<u8>,
+    /// Charging, Discharging, Full, Not charging or Unknown
+    pub status: String,
+    /// Seconds until the battery is empty, while discharging
+    pub time_to_empty: Option<u64>,
+    /// Seconds until the battery is full, while charging
+    pub time_to_full: Option<u64>,
+    /// Charge cycles
+    pub cycle_count: Option<u64>,
+    /// Full capacity compared with the design capacity, as percentage. It goes down as the battery wears
+    pub health: Option<f64>,
+}
+
+/// Power sources of the machine
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct PowerSupplyStatus {
+    /// True if a battery or UPS is discharging and no adapter is online
+    pub on_battery: bool,
+    /// AC adapters and USB power sources
+    pub adapters: Vec<PowerAdapter>,
+    /// UPS reported by the kernel (UPS managed by tools like NUT are not included)
+    pub ups: Vec<Battery>,
+}
+
+/// AC adapter or USB power source
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct PowerAdapter {
+    /// Power supply name, like AC or ADP1
+    pub name: String,
+    /// Mains, USB, USB_PD or USB_C
+    pub kind: String,
+    /// True if it is plugged and powering the machine
+    pub online: bool,
+    /// Maximum power in watts, when the driver reports it
+    pub power: Option<f64>,
+}
+
+/// Energy and power of a RAPL domain
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct PowerDomain {
+    /// powercap zone, like intel-rapl:0 or intel-rapl:0:1
+    pub zone: String,
+    /// Domain name, like package-0, core, uncore or dram
+    pub name: String,
+    /// Energy counter in microjoules. It wraps around, so only differences are meaningful
+    pub energy: u64,
+    /// Average power in watts since the last call
+    pub power: f64,
+}
+
+/// Login session of a user
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct UserSession {
+    /// User name
+    pub user: String,
+    /// Terminal like pts/0 or tty2, or the X display
+    pub terminal: Option<String>,
+    /// Unix timestamp (seconds) of the login
+    pub login_time: u64,
+    /// Host the user logged in from, for remote sessions (SSH...)
+    pub remote_host: Option<String>,
+    /// PID of the session leader (login shell, sshd...)
+    pub pid: Option<i32>,
+}
+
+/// Device on the PCI bus (GPU, NIC, capture card, accelerator...)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct PciDevice {
+    /// Bus address like 0000:01:00.0
+    pub address: String,
+    /// Vendor ID, like 0x10de for NVIDIA
+    pub vendor_id: u16,
+    /// Device ID
+    pub device_id: u16,
+    /// Subsystem vendor ID (the board maker)
+    pub subsystem_vendor_id: Option<u16>,
+    /// Subsystem device ID
+    pub subsystem_device_id: Option<u16>,
+    /// Class code (base class, subclass and programming interface), like 0x030000 for a VGA controller
+    pub class: u32,
+    /// Name of the base class, like Display controller
+    pub class_name: String,
+    /// Kernel driver bound to the device. None if no driver is bound. vfio-pci when it is reserved for passthrough
+    pub driver: Option<String>,
+    /// IOMMU group of the device, all the devices of a group are passed through together. None without IOMMU
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub iommu_group: Option<u32>,
+    /// SR-IOV virtual functions the device supports. None if it does not support SR-IOV
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sriov_total_vfs: Option<u32>,
+    /// SR-IOV virtual functions currently enabled
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sriov_num_vfs: Option<u32>,
+    /// Address of the physical function when the device is an SR-IOV virtual function
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub physical_function: Option<String>,
+}
+
+/// Prerequisites of PCI passthrough to virtual machines
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct PassthroughStatus {
+    /// If the IOMMU is enabled (intel_iommu=on or amd_iommu on the kernel command line and VT-d or AMD-Vi in the
+    /// firmware), so the kernel created IOMMU groups
+    pub iommu_enabled: bool,
+    /// Number of IOMMU groups
+    pub iommu_groups: u32,
+    /// If the vfio-pci driver is loaded
+    pub vfio_loaded: bool,
+    /// Addresses of the devices bound to vfio-pci, ready to be passed through
+    pub vfio_devices: Vec<String>,
+    /// Addresses of the devices supporting SR-IOV
+    pub sriov_devices: Vec<String>,
+}
+
+/// Render node of a graphic card usable for hardware video encoding and decoding with VA-API
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct VaapiInfo {
+    /// Render node, like /dev/dri/renderD128
+    pub path: String,
+    /// Kernel driver of the card, like i915, amdgpu or nouveau
+    pub kernel_driver: Option<String>,
+    /// PCI vendor ID of the card, like 0x8086 for Intel
+    pub vendor_id: Option<u16>,
+    /// PCI device ID of the card
+    pub device_id: Option<u16>,
+    /// VA-API driver description, like "Intel iHD driver for Intel(R) Gen Graphics - 23.1.1". None without the
+    /// `vaapi` feature or if libva cannot be loaded
+    pub driver: Option<String>,
+    /// Supported VA-API profiles, like H264Main, HEVCMain10 or AV1Profile0
+    pub profiles: Vec<String>,
+}
+
+/// OpenCL device of a platform, like a GPU, a CPU or an accelerator
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct OpenClDevice {
+    /// Platform name, like NVIDIA CUDA, Intel(R) OpenCL Graphics or rusticl
+    pub platform: String,
+    /// Platform vendor
+    pub platform_vendor: String,
+    /// OpenCL version of the platform, like "OpenCL 3.0 CUDA 12.4.131"
+    pub platform_version: String,
+    /// Device name
+    pub name: String,
+    /// Device vendor
+    pub vendor: String,
+    /// GPU, CPU, Accelerator or Custom
+    pub device_type: String,
+    /// OpenCL version supported by the device
+    pub version: String,
+    /// Driver version
+    pub driver_version: String,
+    /// Parallel compute units, the streaming multiprocessors of Nvidia cards or the cores of a CPU
+    pub compute_units: u32,
+    /// Maximum work items of a work group
+    pub max_work_group_size: u64,
+    /// Maximum clock frequency in MHz
+    pub max_clock_frequency: u32,
+    /// Global memory in bytes
+    pub global_memory: u64,
+    /// Local memory of a work group in bytes
+    pub local_memory: u64,
+    /// Largest buffer that can be allocated in bytes
+    pub max_allocation: u64,
+}
+
+/// Kernel module loaded in the running kernel
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct KernelModule {
+    /// Module name, like nvidia, uvcvideo or v4l2loopback
+    pub name: String,
+    /// Memory used by the module in bytes
+    pub size: u64,
+    /// Times the module is in use (references from other modules and open devices)
+    pub instances: u32,
+    /// Modules depending on this one
+    pub used_by: Vec<String>,
+    /// Live, Loading or Unloading
+    pub state: String,
+}
+
+/// System clock and its synchronization with NTP, chrony or PTP
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct ClockStatus {
+    /// Hardware counter the kernel keeps the time with, like tsc, kvm-clock or arch_sys_counter
+    pub clock_source: Option<String>,
+    /// Clock sources the kernel can use
+    pub available_clock_sources: Vec<String>,
+    /// Running synchronization daemon, like chronyd, ntpd, systemd-timesyncd or ptp4l
+    pub sync_service: Option<String>,
+    /// If the kernel clock is synchronized by a daemon
+    pub synchronized: bool,
+    /// Estimated offset of the clock from the reference in milliseconds, the last correction being applied
+    pub offset: Option<f64>,
+    /// Maximum error of the clock in milliseconds
+    pub max_error: Option<f64>,
+    /// Estimated error of the clock in milliseconds
+    pub estimated_error: Option<f64>,
+}
+
+/// Video codecs of the hardware encoders (NVENC) and decoders (NVDEC) of an Nvidia card
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct VideoCodecs {
+    /// Graphic card id
+    pub id: String,
+    /// Codecs that can be encoded: H264, HEVC and AV1. Empty if the card has no encoder
+    pub encode: Vec<String>,
+    /// Codecs that can be decoded: H264, HEVC, VP9 and AV1. Only known on Linux, where NVDEC can be asked
+    pub decode: Vec<String>,
+    /// Encoding sessions running right now
+    pub encoder_sessions: u32,
+    /// Concurrent encoding sessions allowed by the driver, limited on GeForce cards. None if unlimited
+    pub max_encoder_sessions: Option<u32>,
+}
+
+/// Monitor connected to a display output
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Display {
+    /// DRM connector, like card0-HDMI-A-1
+    pub connector: String,
+    /// Connector type, like HDMI-A, DP or eDP (laptop panels)
+    pub connector_type: String,
+    /// If the output is enabled. A connected monitor can be switched off by the compositor
+    pub enabled: bool,
+    /// Monitor name from its EDID
+    pub name: Option<String>,
+    /// Manufacturer PNP ID from the EDID, like DEL or SAM
+    pub manufacturer: Option<String>,
+    /// Horizontal resolution of the preferred mode in pixels
+    pub width: u32,
+    /// Vertical resolution of the preferred mode in pixels
+    pub height: u32,
+    /// Refresh rate of the preferred mode in Hz, from the EDID
+    pub refresh_rate: Option<f64>,
+    /// Physical width in millimeters
+    pub width_mm: Option<u32>,
+    /// Physical height in millimeters
+    pub height_mm: Option<u32>,
+}
+
+/// Raspberry Pi firmware telemetry, the same values reported by vcgencmd
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct RpiStatus {
+    /// SoC temperature in degrees
+    pub soc_temperature: Option<f64>,
+    /// Throttling flags. None if the firmware cannot be queried
+    pub throttling: Option<RpiThrottling>,
+    /// Core voltage in volts
+    pub core_voltage: Option<f64>,
+    /// Memory reserved for the GPU (VideoCore) in bytes
+    pub gpu_memory: Option<u64>,
+}
+
+/// Throttling flags of a Raspberry Pi (vcgencmd get_throttled). The `occurred` ones are sticky since boot
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct RpiThrottling {
+    /// Raw value as returned by the firmware
+    pub raw: u32,
+    /// The supply voltage is currently too low
+    pub under_voltage: bool,
+    /// The ARM frequency is currently capped
+    pub frequency_capped: bool,
+    /// The SoC is currently throttled
+    pub throttled: bool,
+    /// The soft temperature limit is currently active
+    pub soft_temperature_limit: bool,
+    /// Under-voltage has occurred since boot
+    pub under_voltage_occurred: bool,
+    /// Frequency capping has occurred since boot
+    pub frequency_capped_occurred: bool,
+    /// Throttling has occurred since boot
+    pub throttled_occurred: bool,
+    /// The soft temperature limit has been reached since boot
+    pub soft_temperature_limit_occurred: bool,
+}
+
+/// Out-of-band health reported by the baseboard management controller (BMC) through IPMI
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct BmcStatus {
+    /// Air intake (inlet) temperature in degrees
+    pub inlet_temperature: Option<f64>,
+    /// System power draw in watts, from DCMI or the power consumption sensor
+    pub power_draw: Option<f64>,
+    /// Power supply units
+    pub power_supplies: Vec<BmcPowerSupply>,
+    /// All the sensors of the BMC repository
+    pub sensors: Vec<BmcSensor>,
+}
+
+/// A sensor of the BMC repository (SDR), like `ipmitool sdr` lists it
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct BmcSensor {
+    /// Sensor name, like "Inlet Temp"
+    pub name: String,
+    /// Sensor number
+    pub number: u8,
+    /// Sensor type (Temperature, Voltage, Current, Fan, PowerSupply...)
+    pub kind: String,
+    /// Reading converted to the unit. None for discrete sensors or when it is not available
+    pub value: Option<f64>,
+    /// Unit of the value (C, F, V, A, W, RPM)
+    pub unit: Option<String>,
+    /// Asserted states of discrete sensors, one bit per state
+    pub states: Option<u16>,
+}
+
+/// Power supply unit state, from its discrete sensor
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct BmcPowerSupply {
+    /// Sensor name, like "PS1 Status"
+    pub name: String,
+    /// The unit is present
+    pub present: bool,
+    /// The unit has failed
+    pub failed: bool,
+    /// The unit predicts a failure
+    pub predictive_failure: bool,
+    /// The unit lost its input (AC or DC)
+    pub input_lost: bool,
 }
 
 /// Nvidia drivers configuration
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct NvidiaInfo {
      /// Nvidia drivers
      pub driver_version: String,
@@ -171,4 +1146,480 @@ pub struct NvidiaInfo {
      pub nvml_version: String,
      /// Cuda version
      pub cuda_version: i32,
-}
\ No newline at end of file
+}
+/// Hugepages pool of a given page size
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct HugepagesPool {
+    /// Size of every page in bytes
+    pub page_size: u64,
+    /// Configured amount of pages
+    pub total: u64,
+    /// Pages not allocated yet
+    pub free: u64,
+    /// Pages reserved but not allocated yet
+    pub reserved: u64,
+    /// Pages allocated over the configured amount
+    pub surplus: u64,
+}
+
+/// Extended memory information
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct MemoryDetails {
+    /// Default hugepage size in bytes
+    pub default_hugepage_size: u64,
+    /// Hugepages pools, one per supported page size
+    pub hugepages: Vec<HugepagesPool>,
+    /// Transparent hugepages mode (always, madvise, never). None if the kernel does not support it
+    pub transparent_hugepages: Option<String>,
+}
+
+/// State of an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum AlertState {
+    /// The condition started
+    Triggered,
+    /// The condition is not happening anymore
+    Cleared,
+}
+
+/// Alert produced when a threshold is crossed
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Alert {
+    /// Name of the rule that produced the alert
+    pub rule: String,
+    /// If the alert starts or ends
+    pub state: AlertState,
+    /// Value that crossed the threshold
+    pub value: f64,
+    /// Human readable description
+    pub message: String,
+    /// Unix timestamp (seconds) when the alert was produced
+    pub timestamp: u64,
+}
+
+/// Kind of event reported by the Nvidia driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum GpuEventKind {
+    /// Critical Xid error, like a GPU that fell off the bus or a page fault
+    XidError,
+    /// Corrected single bit ECC error
+    SingleBitEccError,
+    /// Uncorrectable double bit ECC error
+    DoubleBitEccError,
+    /// Performance state change
+    PstateChange,
+    /// Clock change, like the throttling of an overheated card
+    ClockChange,
+    /// Power source change (AC or battery)
+    PowerSourceChange,
+}
+
+/// Event reported by the Nvidia driver
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct GpuEvent {
+    /// Card UUID, like the `id` of `GraphicsUsage`
+    pub id: String,
+    /// Kind of event
+    pub kind: GpuEventKind,
+    /// Xid error code of `XidError` events. None if the driver does not know it
+    pub xid: Option<u64>,
+    /// Unix timestamp (seconds) when the event was received
+    pub timestamp: u64,
+}
+
+/// Memory usage breakdown. All the values are in bytes
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct MemoryBreakdown {
+    /// Total memory
+    pub total: u64,
+    /// Used memory computed like `free` does: total minus available
+    pub used: u64,
+    /// Memory not used at all
+    pub free: u64,
+    /// Memory that can be given to new applications without swapping
+    pub available: u64,
+    /// Memory used by kernel buffers
+    pub buffers: u64,
+    /// Memory used by the page cache and reclaimable slabs
+    pub cached: u64,
+    /// Memory waiting to be written back to disk
+    pub dirty: u64,
+}
+
+/// Memory limit and usage of a cgroup
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CgroupMemory {
+    /// cgroup version (1 or 2)
+    pub version: u8,
+    /// Memory limit in bytes. None if unlimited
+    pub limit: Option<u64>,
+    /// Memory used by the cgroup in bytes
+    pub usage: u64,
+}
+
+/// Usage of a tracked cgroup (version 2), like a systemd service or a container
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CgroupStatus {
+    /// cgroup path as it was tracked
+    pub path: String,
+    /// Cpu used since the last call as percentage of one core
+    pub cpu: f64,
+    /// Memory used in bytes. None if the memory controller is not enabled
+    pub memory: Option<u64>,
+    /// Memory limit in bytes. None if unlimited
+    pub memory_limit: Option<u64>,
+    /// Bytes read from storage per second since the last call
+    pub read_bytes_per_second: f64,
+    /// Bytes written to storage per second since the last call
+    pub write_bytes_per_second: f64,
+    /// Processes in the cgroup
+    pub processes: Option<u64>,
+}
+
+/// Usage of a running container since the last call, from its cgroup (version 2)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct ContainerStatus {
+    /// Container id (64 hexadecimal characters)
+    pub id: String,
+    /// Name of the container. None if the engine socket cannot be queried
+    pub name: Option<String>,
+    /// Image of the container. None if the engine socket cannot be queried
+    pub image: Option<String>,
+    /// Runtime: docker, podman, containerd or cri-o
+    pub runtime: String,
+    /// cgroup path relative to the cgroup root
+    pub cgroup: String,
+    /// Cpu used since the last call as percentage of one core. 0 the first time the container is seen
+    pub cpu: f64,
+    /// Memory used in bytes. None if the memory controller is not enabled
+    pub memory: Option<u64>,
+    /// Memory limit in bytes. None if unlimited
+    pub memory_limit: Option<u64>,
+    /// Bytes read from storage per second since the last call
+    pub read_bytes_per_second: f64,
+    /// Bytes written to storage per second since the last call
+    pub write_bytes_per_second: f64,
+    /// Processes in the container
+    pub processes: Option<u64>,
+}
+
+/// NUMA node with its CPUs and memory. Memory values are in bytes
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct NumaNode {
+    /// Node id
+    pub id: usize,
+    /// Ids of the CPUs in this node
+    pub cpus: Vec<usize>,
+    /// Memory attached to this node
+    pub memory_total: u64,
+    /// Free memory in this node
+    pub memory_free: u64,
+    /// Used memory in this node
+    pub memory_used: u64,
+}
+
+/// Wake-on-LAN configuration of a network interface
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct WakeOnLan {
+    /// Interface name
+    pub interface: String,
+    /// Interface MAC address
+    pub mac: String,
+    /// Supported wake up modes (phy, unicast, multicast, broadcast, arp, magic, magicsecure)
+    pub supported: Vec<String>,
+    /// Enabled wake up modes
+    pub enabled: Vec<String>,
+}
+
+/// A value found in a fleet and the hosts where it was found
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct FleetValue {
+    /// The value, like a driver version
+    pub value: String,
+    /// Hostnames with this value
+    pub hosts: Vec<String>,
+}
+
+/// Comparison of the graphic cards of several machines. Every list contains the distinct values found,
+/// so a list with more than one entry means the fleet is heterogeneous
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct GpuFleetReport {
+    /// True if all the machines have the same graphic cards, drivers and CUDA version
+    pub homogeneous: bool,
+    /// Graphic cards of every machine like "2 x NVIDIA A100-SXM4-40GB"
+    pub gpus: Vec<FleetValue>,
+    /// Nvidia driver versions
+    pub driver_versions: Vec<FleetValue>,
+    /// CUDA versions
+    pub cuda_versions: Vec<FleetValue>,
+}
+
+/// Partition of a physical disk
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Partition {
+    /// Partition name like sda1
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Where it is mounted (if it is)
+    pub mount_point: Option<String>,
+    /// Encryption of the partition (LUKS1, LUKS2, dm-crypt or BitLocker), even when it is not opened
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub encryption: Option<String>,
+}
+
+/// Physical block device
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct PhysicalDisk {
+    /// Device name like sda or nvme0n1
+    pub name: String,
+    /// Device path like /dev/sda
+    pub path: String,
+    /// Device model
+    pub model: Option<String>,
+    /// Serial number
+    pub serial: Option<String>,
+    /// Firmware revision
+    pub firmware: Option<String>,
+    /// Size in bytes
+    pub size: u64,
+    /// True for spinning disks
+    pub rotational: bool,
+    /// True for removable devices like USB sticks
+    pub removable: bool,
+    /// Where it is mounted if the whole device has a filesystem without partitions
+    pub mount_point: Option<String>,
+    /// Partitions of the device
+    pub partitions: Vec<Partition>,
+    /// Extra details of NVMe devices
+    pub nvme: Option<NvmeDetails>,
+}
+
+/// NVMe device details. The health values come from the SMART log, which requires root permissions
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct NvmeDetails {
+    /// Controller name like nvme0
+    pub controller: String,
+    /// Amount of namespaces of the controller
+    pub namespaces: u32,
+    /// Estimated percentage of the endurance used. It can be over 100
+    pub percentage_used: Option<u8>,
+    /// Remaining spare capacity as percentage
+    pub available_spare: Option<u8>,
+    /// Spare capacity percentage under which the device warns
+    pub available_spare_threshold: Option<u8>,
+    /// Active critical warnings (spare_below_threshold, temperature, reliability_degraded, read_only...)
+    pub critical_warnings: Vec<String>,
+}
+
+/// Software RAID (md) array
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct RaidArray {
+    /// Array name like md0
+    pub name: String,
+    /// RAID level like raid1
+    pub level: String,
+    /// Array state (clean, active, inactive, read-auto...)
+    pub state: String,
+    /// True if some device is missing or failed
+    pub degraded: bool,
+    /// Amount of missing or failed devices
+    pub failed_devices: u32,
+    /// Member devices
+    pub members: Vec<String>,
+}
+
+/// LVM volume group
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct VolumeGroup {
+    /// Volume group name
+    pub name: String,
+    /// Active logical volumes
+    pub volumes: Vec<String>,
+    /// Physical volumes used by the active logical volumes
+    pub members: Vec<String>,
+}
+
+/// ZFS pool
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct ZfsPool {
+    /// Pool name
+    pub name: String,
+    /// Pool state (ONLINE, DEGRADED, FAULTED...)
+    pub state: String,
+    /// True if the pool is not online
+    pub degraded: bool,
+    /// Member devices (data, log, cache and spare vdevs) like sdb1
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub members: Vec<String>,
+}
+
+/// Storage built over several devices
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct StorageTopology {
+    /// Software RAID arrays
+    pub raid_arrays: Vec<RaidArray>,
+    /// LVM volume groups
+    pub volume_groups: Vec<VolumeGroup>,
+    /// ZFS pools
+    pub zfs_pools: Vec<ZfsPool>,
+}
+
+/// Disk IO usage since the last call
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct DiskStatus {
+    /// Device name like sda or sda1
+    pub name: String,
+    /// Bytes read per second
+    pub read_bytes_per_second: f64,
+    /// Bytes written per second
+    pub write_bytes_per_second: f64,
+    /// Average time of the read requests in milliseconds
+    pub read_latency: f64,
+    /// Average time of the write requests in milliseconds
+    pub write_latency: f64,
+    /// Requests being processed right now (queue depth)
+    pub in_flight: u64,
+    /// Percentage of time the device was busy
+    pub utilization: f64,
+}
+
+/// Network interface
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct NetworkInterface {
+    /// Interface name like eth0
+    pub name: String,
+    /// MAC address
+    pub mac: String,
+    /// Maximum transmission unit
+    pub mtu: u32,
+    /// Link state (up, down, unknown...)
+    pub state: String,
+    /// Negotiated link speed in Mb/s. None if unknown or the link is down
+    pub speed: Option<u32>,
+    /// Negotiated duplex (full, half). None if unknown
+    pub duplex: Option<String>,
+    /// Assigned IPv4 addresses
+    pub ipv4: Vec<String>,
+    /// Assigned IPv6 addresses with their prefix and scope
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ipv6_addresses: Vec<Ipv6Address>,
+    /// IPv6 is disabled in this interface (net.ipv6.conf.<interface>.disable_ipv6)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ipv6_disabled: bool,
+}
+
+/// IPv6 address of a network interface
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Ipv6Address {
+    /// Address like 2001:db8::1
+    pub address: String,
+    /// Prefix length, like 64
+    pub prefix_length: u8,
+    /// Scope: global, link, site or host (loopback)
+    pub scope: String,
+}
+
+/// State of the IP stack and traffic by protocol since boot
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct IpStatus {
+    /// IPv6 is disabled system-wide, with the ipv6.disable=1 boot parameter or net.ipv6.conf.all.disable_ipv6
+    pub ipv6_disabled: bool,
+    /// IPv4 bytes received since boot
+    pub ipv4_in_bytes: u64,
+    /// IPv4 bytes sent since boot
+    pub ipv4_out_bytes: u64,
+    /// IPv6 bytes received since boot
+    pub ipv6_in_bytes: u64,
+    /// IPv6 bytes sent since boot
+    pub ipv6_out_bytes: u64,
+}
+
+/// Network interface usage since the last call
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct NetworkStatus {
+    /// Interface name like eth0
+    pub name: String,
+    /// Bytes received per second
+    pub rx_bytes_per_second: f64,
+    /// Bytes sent per second
+    pub tx_bytes_per_second: f64,
+    /// Packets received per second
+    pub rx_packets_per_second: f64,
+    /// Packets sent per second
+    pub tx_packets_per_second: f64,
+    /// Receive errors since the last call
+    pub rx_errors: u64,
+    /// Transmit errors since the last call
+    pub tx_errors: u64,
+    /// Received packets dropped since the last call
+    pub rx_dropped: u64,
+    /// Outgoing packets dropped since the last call
+    pub tx_dropped: u64,
+    /// Collisions since the last call
+    pub collisions: u64,
+}
+
+/// Wireless link of a Wi-Fi interface
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct WifiLink {
+    /// Interface name like wlan0
+    pub interface: String,
+    /// Network name. None if not connected
+    pub ssid: Option<String>,
+    /// Signal strength in dBm
+    pub signal: Option<i32>,
+    /// Frequency in MHz
+    pub frequency: Option<u32>,
+    /// Channel number
+    pub channel: Option<u32>,
+    /// Transmission bitrate in Mb/s
+    pub bitrate: Option<f64>,
+}
+
+/// Summary of the sockets of the machine, like a lightweight netstat
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct SocketSummary {
+    /// Established TCP connections
+    pub tcp_established: u64,
+    /// TCP connections in TIME_WAIT state
+    pub tcp_time_wait: u64,
+    /// Listening TCP sockets
+    pub tcp_listen: u64,
+    /// All TCP sockets in any state
+    pub tcp_total: u64,
+    /// UDP sockets
+    pub udp: u64,
+    /// TCP segments sent since boot
+    pub tcp_out_segments: u64,
+    /// TCP segments retransmitted since boot
+    pub tcp_retransmitted_segments: u64,
+}
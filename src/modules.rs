@@ -0,0 +1,53 @@
+use anyhow::Result;
+use std::fs;
+use crate::model::KernelModule;
+use crate::host;
+
+const MODULES_PATH: &str = "/proc/modules";
+const SYS_MODULE_PATH: &str = "/sys/module";
+
+/// The kernel names modules with underscores, but modprobe accepts dashes too (v4l2-loopback)
+fn normalize(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+/// Line of /proc/modules like `nvidia_uvm 1437696 2 - Live 0x0000000000000000 (POE)`
+fn parse_module(line: &str) -> Option<KernelModule> {
+    let mut fields = line.split_whitespace();
+    let name = fields.next()?.to_string();
+    let size = fields.next()?.parse().ok()?;
+    let instances = fields.next()?.parse().unwrap_or_default();
+    // Modules using this one, comma ended, or - if none
+    let used_by = fields.next()?
+        .split(',')
+        .filter(|dependent| !dependent.is_empty() && *dependent != "-")
+        .map(|dependent| dependent.to_string())
+        .collect();
+    let state = fields.next().unwrap_or_default().to_string();
+    Some(KernelModule {
+        name,
+        size,
+        instances,
+        used_by,
+        state,
+    })
+}
+
+/// Loadable modules currently in the kernel. Built-in drivers are not listed
+pub fn kernel_modules() -> Result<Vec<KernelModule>> {
+    let content = fs::read_to_string(host::path(MODULES_PATH))?;
+    Ok(content.lines().filter_map(parse_module).collect())
+}
+
+/// If the module is loaded or built into the kernel
+pub fn module_loaded(name: &str) -> bool {
+    let name = normalize(name);
+    if name.is_empty() || name.contains('/') {
+        return false;
+    }
+    let loaded = kernel_modules()
+        .map(|modules| modules.iter().any(|module| module.name == name))
+        .unwrap_or(false);
+    // Built-in drivers only appear in sysfs
+    loaded || host::path(&format!("{}/{}", SYS_MODULE_PATH, name)).exists()
+}
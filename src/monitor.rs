@@ -1,46 +1,215 @@
 use anyhow::Result;
 use std::fs::File;
 use std::io::{self, BufRead};
-use std::time::SystemTime;
+use std::time::{Duration, Instant, UNIX_EPOCH};
 use std::collections::HashMap;
-use log::warn;
+use log::{debug, warn};
+use crate::model::{MemoryBreakdown, DiskStatus, NetworkStatus, CgroupStatus, PowerDomain, Process as ProcessModel, ProcessState};
+use crate::host;
+use crate::machine::ChildTracking;
+use crate::cgroup::CgroupStats;
+use crate::power::{self, RaplZone};
+
+/// Reference of the CPU usage percentages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuScale {
+    /// 100 is one core fully used, so the value goes up to 100 × the number of cores
+    PerCore,
+    /// 100 is all the cores of the machine fully used
+    Normalized,
+}
+
+/// How the CPU usage of the system and of the tracked processes is computed. The usage is the CPU time used
+/// between two calls divided by the time elapsed
+#[derive(Debug, Clone)]
+pub struct MonitorConfig {
+    /// Scale of `SystemStatus.cpu`. Normalized by default
+    pub system_scale: CpuScale,
+    /// Scale of `Process.cpu`. Per core by default, like `top`
+    pub process_scale: CpuScale,
+    /// Calls done sooner than this after the last sample return the last usage, because the kernel counts the
+    /// CPU time in ticks of 10ms and a shorter period only gives noise. `Machine::system_status` calls within it
+    /// return the last status without sampling anything
+    pub min_interval: Duration,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        MonitorConfig {
+            system_scale: CpuScale::Normalized,
+            process_scale: CpuScale::PerCore,
+            min_interval: Duration::from_millis(250),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Monitor {
+    config: MonitorConfig,
     last_cpu: Cpu,
-    last_processes: HashMap<i32, Process>
+    last_cpu_when: Option<Instant>,
+    last_cpu_usage: i32,
+    last_processes: HashMap<i32, TrackedProcess>,
+    groups: HashMap<String, TrackedGroup>,
+    last_disks: HashMap<String, DiskStats>,
+    last_disks_when: Instant,
+    last_networks: HashMap<String, NetworkStats>,
+    last_networks_when: Instant,
+    last_cgroups: HashMap<String, CgroupStats>,
+    #[cfg(all(feature = "containers", target_os = "linux"))]
+    last_containers: HashMap<String, CgroupStats>,
+    last_rapl: HashMap<String, RaplZone>,
+    last_rapl_when: Instant
 }
 
 impl Monitor {
-    pub fn new() -> Monitor {
+    pub fn new(config: MonitorConfig) -> Monitor {
         Monitor {
-            last_cpu: Cpu{values: vec![0;10]},
-            last_processes: HashMap::new()
+            config,
+            last_cpu: Cpu{values: vec![0;10], cores: 1},
+            // The first call reports the usage since boot
+            last_cpu_when: None,
+            last_cpu_usage: 0,
+            last_processes: HashMap::new(),
+            groups: HashMap::new(),
+            last_disks: HashMap::new(),
+            last_disks_when: host::instant(),
+            last_networks: HashMap::new(),
+            last_networks_when: host::instant(),
+            last_cgroups: HashMap::new(),
+            #[cfg(all(feature = "containers", target_os = "linux"))]
+            last_containers: HashMap::new(),
+            last_rapl: HashMap::new(),
+            last_rapl_when: host::instant()
         }
     }
 
-    pub fn next(&mut self) -> Result<(i32, i32)> {
-        let cpu = Cpu::from_file(File::open("/proc/stat")?)?;
-        let cpu_usage = cpu.usage(&self.last_cpu);
-        self.last_cpu = cpu;
-        let memory_usage = Memory::from_file(File::open("/proc/meminfo")?)?.usage();
-        Ok((cpu_usage, memory_usage))
+    pub fn next(&mut self) -> Result<(i32, Memory)> {
+        let now = host::instant();
+        if self.last_cpu_when.is_none_or(|when| now.duration_since(when) >= self.config.min_interval) {
+            let cpu = match File::open(host::path("/proc/stat")) {
+                Ok(file) => Some(Cpu::from_file(file)?),
+                // Android apps cannot read /proc/stat since Android 8, the CPU usage stays at 0
+                Err(e) if cfg!(target_os = "android") && e.kind() == io::ErrorKind::PermissionDenied => {
+                    debug!("Cannot read /proc/stat: {}", e);
+                    None
+                },
+                Err(e) => return Err(e.into())
+            };
+            // When no tick elapsed the last sample is kept as reference
+            if let Some((cpu, usage)) = cpu.and_then(|cpu| cpu.usage(&self.last_cpu).map(|usage| (cpu, usage))) {
+                self.last_cpu_usage = match self.config.system_scale {
+                    CpuScale::Normalized => usage,
+                    CpuScale::PerCore => usage * cpu.cores as i32
+                };
+                self.last_cpu = cpu;
+                self.last_cpu_when = Some(now);
+            }
+        }
+        let cpu_usage = self.last_cpu_usage;
+        let memory = Memory::from_file(File::open(host::path("/proc/meminfo"))?)?;
+        Ok((cpu_usage, memory))
     }
 
-    pub fn next_processes(&mut self) -> Vec<(i32,f64)> {
-        //let mut processes = HashMap::with_capacity(self.last_processes.len());
+    pub fn next_processes(&mut self) -> Vec<ProcessModel> {
+        let now = host::instant();
         let mut result = vec![];
         let mut to_untrack = vec![];
-        for (&pid, last_process) in &mut self.last_processes {
-            match Monitor::get_process(pid) {
+        // The process table is only scanned when some process is tracked with its children
+        let children = if self.last_processes.values().any(|tracked| tracked.children.is_some()) {
+            Monitor::children()
+        } else {
+            HashMap::new()
+        };
+        let boot_time = boot_time();
+        for (&pid, tracked) in &mut self.last_processes {
+            let current_process = Monitor::get_process(pid, boot_time).and_then(|process| {
+                if process.start.is_some() && tracked.last.start.is_some() && process.start != tracked.last.start {
+                    Err(anyhow::anyhow!("PID reused by another process"))
+                } else {
+                    Ok(process)
+                }
+            });
+            match current_process {
                 Ok(current_process) => {
-                    result.push((pid, current_process.usage(last_process)));
-                    
-                    last_process.total_time = current_process.total_time;
-                    last_process.when = current_process.when;
+                    // Too soon after the last sample, the last usage is reported and the sample kept as reference
+                    let sample = now.duration_since(tracked.last.when) >= self.config.min_interval;
+                    let first = result.len();
+                    match tracked.children {
+                        Some(mode) => {
+                            let mut entry = process_model(pid, &current_process, current_process.own_usage(&tracked.last),
+                                current_process.io_usage(Some(&tracked.last)), None);
+                            let mut descendants = HashMap::new();
+                            let mut per_child = vec![];
+                            for child in Monitor::descendants(pid, &children) {
+                                // The child could have exited after the scan
+                                let current_child = match Monitor::get_process(child, boot_time) {
+                                    Ok(process) => process,
+                                    Err(_) => continue
+                                };
+                                let last_child = tracked.descendants.get(&child);
+                                let cpu = last_child
+                                    .map(|last| current_child.own_usage(last))
+                                    .unwrap_or(0.0);
+                                let child_entry = process_model(child, &current_child, cpu, current_child.io_usage(last_child), Some(pid));
+                                match mode {
+                                    ChildTracking::Aggregate => {
+                                        entry.cpu += child_entry.cpu;
+                                        entry.memory += child_entry.memory;
+                                        entry.virtual_memory += child_entry.virtual_memory;
+                                        entry.shared_memory += child_entry.shared_memory;
+                                        entry.read_bytes_per_second = add(entry.read_bytes_per_second, child_entry.read_bytes_per_second);
+                                        entry.write_bytes_per_second = add(entry.write_bytes_per_second, child_entry.write_bytes_per_second);
+                                    },
+                                    ChildTracking::PerChild => per_child.push(child_entry)
+                                }
+                                entry.children += 1;
+                                descendants.insert(child, current_child);
+                            }
+                            // Children that are not descendants anymore could have been reparented
+                            let exited: Vec<i32> = tracked.descendants.iter()
+                                .filter(|(child, last_child)| !descendants.contains_key(child) && !Monitor::get_process(**child, boot_time)
+                                    .map(|process| process.start == last_child.start)
+                                    .unwrap_or(false))
+                                .map(|(&child, _)| child)
+                                .collect();
+                            match mode {
+                                ChildTracking::Aggregate => entry.cpu += current_process.cpu(
+                                    Monitor::waited_time(pid, &current_process, tracked, &descendants, &exited), &tracked.last),
+                                ChildTracking::PerChild => per_child.extend(exited.iter()
+                                    .map(|child| exited_model(*child, &tracked.descendants[child], Some(pid))))
+                            }
+                            if !sample {
+                                let mut last_descendants = std::mem::take(&mut tracked.descendants);
+                                descendants = descendants.into_iter()
+                                    .map(|(child, current)| (child, last_descendants.remove(&child).unwrap_or(current)))
+                                    .collect();
+                                // Their time is deducted from the one of their parent at the next sample
+                                if mode == ChildTracking::Aggregate {
+                                    descendants.extend(last_descendants.into_iter().filter(|(child, _)| exited.contains(child)));
+                                }
+                            }
+                            tracked.descendants = descendants;
+                            result.push(entry);
+                            result.extend(per_child);
+                        },
+                        None => result.push(process_model(pid, &current_process, current_process.usage(&tracked.last),
+                            current_process.io_usage(Some(&tracked.last)), None))
+                    }
+
+                    let entries = &mut result[first..];
+                    if sample {
+                        tracked.reported = entries.iter().map(|entry| (entry.pid, entry.cpu)).collect();
+                        tracked.last = current_process;
+                    } else {
+                        for entry in entries.iter_mut().filter(|entry| entry.state != ProcessState::Exited) {
+                            entry.cpu = tracked.reported.get(&entry.pid).copied().unwrap_or(0.0);
+                        }
+                    }
                 },
                 Err(err) => {
                     warn!("Cannot get process {}: {:?}. Will be removed", pid, err);
+                    result.push(exited_model(pid, &tracked.last, None));
                     to_untrack.push(pid);
                 }
             }
@@ -51,15 +220,233 @@ impl Monitor {
             self.untrack_process(pid);
         }
 
+        let mut exited_groups = vec![];
+        for (name, group) in &mut self.groups {
+            let sample = now.duration_since(group.when) >= self.config.min_interval;
+            let mut entry: Option<ProcessModel> = None;
+            let mut members = HashMap::new();
+            let mut pids: Vec<i32> = group.members.keys().copied().collect();
+            pids.sort();
+            for pid in pids {
+                let last = &group.members[&pid];
+                // Members that exited or whose PID was reused leave the group
+                let current = match Monitor::get_process(pid, boot_time) {
+                    Ok(current) if current.start.is_none() || current.start == last.start => current,
+                    _ => continue
+                };
+                let member = process_model(pid, &current, current.usage(last), current.io_usage(Some(last)), None);
+                match entry.as_mut() {
+                    None => entry = Some(member),
+                    Some(entry) => {
+                        entry.cpu += member.cpu;
+                        entry.memory += member.memory;
+                        entry.virtual_memory += member.virtual_memory;
+                        entry.shared_memory += member.shared_memory;
+                        entry.read_bytes_per_second = add(entry.read_bytes_per_second, member.read_bytes_per_second);
+                        entry.write_bytes_per_second = add(entry.write_bytes_per_second, member.write_bytes_per_second);
+                        entry.children += 1;
+                    }
+                }
+                members.insert(pid, current);
+            }
+            match entry {
+                Some(mut entry) => {
+                    entry.group = Some(name.clone());
+                    if sample {
+                        group.reported = entry.cpu;
+                        group.members = members;
+                        group.when = now;
+                    } else {
+                        // Too soon after the last sample, the exited members are removed but the others keep theirs
+                        entry.cpu = group.reported;
+                        group.members.retain(|pid, _| members.contains_key(pid));
+                    }
+                    result.push(entry);
+                },
+                None => {
+                    warn!("All the processes of group {} exited. Will be removed", name);
+                    if let Some((&pid, last)) = group.members.iter().min_by_key(|(pid, _)| **pid) {
+                        result.push(ProcessModel { group: Some(name.clone()), ..exited_model(pid, last, None) });
+                    }
+                    exited_groups.push(name.clone());
+                }
+            }
+        }
+        for name in exited_groups {
+            self.groups.remove(&name);
+        }
+
+        if self.config.process_scale == CpuScale::Normalized {
+            let cores = cores() as f64;
+            for process in &mut result {
+                process.cpu /= cores;
+            }
+        }
+        result
+    }
+
+    pub fn next_disks(&mut self) -> Result<Vec<DiskStatus>> {
+        let disks = DiskStats::from_file(File::open(host::path("/proc/diskstats"))?)?;
+        let now = host::instant();
+        let elapsed = now.duration_since(self.last_disks_when);
+        let mut result = disks.iter()
+            .map(|(name, disk)| disk.usage(name, self.last_disks.get(name), elapsed.as_secs_f64()))
+            .collect::<Vec<DiskStatus>>();
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        self.last_disks = disks;
+        self.last_disks_when = now;
+        Ok(result)
+    }
+
+    pub fn next_networks(&mut self) -> Result<Vec<NetworkStatus>> {
+        let networks = NetworkStats::from_file(File::open(host::path("/proc/net/dev"))?)?;
+        let now = host::instant();
+        let elapsed = now.duration_since(self.last_networks_when);
+        let mut result = networks.iter()
+            .map(|(name, network)| network.usage(name, self.last_networks.get(name), elapsed.as_secs_f64()))
+            .collect::<Vec<NetworkStatus>>();
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        self.last_networks = networks;
+        self.last_networks_when = now;
+        Ok(result)
+    }
+
+    pub fn next_power(&mut self) -> Vec<PowerDomain> {
+        let zones = power::rapl_zones();
+        let now = host::instant();
+        let elapsed = now.duration_since(self.last_rapl_when);
+        let result = zones.iter()
+            .map(|zone| zone.usage(self.last_rapl.get(&zone.zone), elapsed.as_secs_f64()))
+            .collect::<Vec<PowerDomain>>();
+        self.last_rapl = zones.into_iter().map(|zone| (zone.zone.clone(), zone)).collect();
+        self.last_rapl_when = now;
         result
     }
 
-    fn get_process(pid: i32) -> Result<Process>{
-        Ok(Process::from_file(File::open(format!("/proc/{}/stat", pid))?)?)
+    pub fn next_cgroups(&mut self) -> Vec<CgroupStatus> {
+        let mut result = vec![];
+        for (path, last) in &mut self.last_cgroups {
+            match CgroupStats::read(path) {
+                Ok(current) => {
+                    result.push(current.usage(path, last));
+                    *last = current;
+                },
+                // The cgroup could be recreated later (a service restarting), so it is kept
+                Err(err) => warn!("Cannot get cgroup {}: {:?}", path, err)
+            }
+        }
+        result.sort_by(|a, b| a.path.cmp(&b.path));
+        result
     }
 
-    pub fn track_process(&mut self, pid: i32) -> Result<()> {
-        self.last_processes.insert(pid, Monitor::get_process(pid)?);
+    /// Usage of the cgroups of the running containers. Unlike the tracked cgroups, the list is given on every call:
+    /// new containers report no usage the first time and the ones gone are forgotten
+    #[cfg(all(feature = "containers", target_os = "linux"))]
+    pub fn next_containers(&mut self, paths: &[String]) -> Vec<CgroupStatus> {
+        let mut current = HashMap::new();
+        let mut result = vec![];
+        for path in paths {
+            match CgroupStats::read(path) {
+                Ok(stats) => {
+                    result.push(stats.usage(path, self.last_containers.get(path).unwrap_or(&stats)));
+                    current.insert(path.clone(), stats);
+                },
+                // It has just stopped
+                Err(err) => debug!("Cannot get container cgroup {}: {:?}", path, err)
+            }
+        }
+        self.last_containers = current;
+        result
+    }
+
+    pub fn track_cgroup(&mut self, path: &str) -> Result<()> {
+        self.last_cgroups.insert(path.to_string(), CgroupStats::read(path)?);
+        Ok(())
+    }
+
+    pub fn untrack_cgroup(&mut self, path: &str) {
+        self.last_cgroups.remove(path);
+    }
+
+    fn get_process(pid: i32, boot_time: Option<u64>) -> Result<Process>{
+        let mut process = Process::from_file(File::open(host::path(&format!("/proc/{}/stat", pid)))?)?;
+        // Start is in ticks since boot (CLK_TCK 100 assumed, like for the CPU time)
+        process.start_time = boot_time.zip(process.start).map(|(boot, start)| boot + start / 100);
+        process.io = ProcessIo::read(pid)
+            .map_err(|err| debug!("Cannot read I/O of process {}: {:?}", pid, err))
+            .ok();
+        Ok(process)
+    }
+
+    /// Parent to children map of all the processes running
+    fn children() -> HashMap<i32, Vec<i32>> {
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+        let entries = match std::fs::read_dir(host::path("/proc")) {
+            Ok(entries) => entries,
+            Err(err) => {
+                warn!("Cannot list processes: {:?}", err);
+                return children;
+            }
+        };
+        for entry in entries.flatten() {
+            let pid = match entry.file_name().to_str().and_then(|name| name.parse::<i32>().ok()) {
+                Some(pid) => pid,
+                None => continue
+            };
+            if let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) {
+                if let Some(parent) = parent_pid(&stat) {
+                    children.entry(parent).or_default().push(pid);
+                }
+            }
+        }
+        children
+    }
+
+    /// Ticks of the children waited by the tree of a tracked process since the last sample, which are not in
+    /// the own time of any process. The tracked descendants that exited were already counted until their last
+    /// sample, so that time is deducted from their parent
+    fn waited_time(pid: i32, current: &Process, tracked: &TrackedProcess, descendants: &HashMap<i32, Process>, exited: &[i32]) -> i32 {
+        let children_time = |process: &Process| process.total_time - process.own_time;
+        let mut waited: HashMap<i32, i32> = HashMap::new();
+        waited.insert(pid, children_time(current) - children_time(&tracked.last));
+        for (child, current_child) in descendants {
+            if let Some(last_child) = tracked.descendants.get(child) {
+                waited.insert(*child, children_time(current_child) - children_time(last_child));
+            }
+        }
+        for child in exited {
+            let last_child = &tracked.descendants[child];
+            if let Some(time) = waited.get_mut(&last_child.parent) {
+                *time -= last_child.total_time;
+            }
+        }
+        // Negative when the child was waited by a process out of the tree (reparented) or not waited yet
+        waited.values().map(|time| (*time).max(0)).sum()
+    }
+
+    fn descendants(pid: i32, children: &HashMap<i32, Vec<i32>>) -> Vec<i32> {
+        let mut result = vec![];
+        let mut pending = vec![pid];
+        while let Some(parent) = pending.pop() {
+            for &child in children.get(&parent).into_iter().flatten() {
+                // Guard against loops in a table read while processes are changing
+                if child != pid && !result.contains(&child) {
+                    result.push(child);
+                    pending.push(child);
+                }
+            }
+        }
+        result.sort();
+        result
+    }
+
+    pub fn track_process(&mut self, pid: i32, children: Option<ChildTracking>) -> Result<()> {
+        self.last_processes.insert(pid, TrackedProcess {
+            last: Monitor::get_process(pid, boot_time())?,
+            children,
+            descendants: HashMap::new(),
+            reported: HashMap::new()
+        });
         Ok(())
 
     }
@@ -67,11 +454,32 @@ impl Monitor {
     pub fn untrack_process(&mut self, pid: i32) {
         self.last_processes.remove(&pid);
     }
+
+    pub fn track_group(&mut self, name: &str, pids: &[i32]) -> Result<()> {
+        if pids.is_empty() {
+            return Err(anyhow::anyhow!("Group {} has no processes", name));
+        }
+        let boot_time = boot_time();
+        let members = pids.iter()
+            .map(|&pid| Ok((pid, Monitor::get_process(pid, boot_time)?)))
+            .collect::<Result<HashMap<i32, Process>>>()?;
+        self.groups.insert(name.to_string(), TrackedGroup {
+            members,
+            when: host::instant(),
+            reported: 0.0
+        });
+        Ok(())
+    }
+
+    pub fn untrack_group(&mut self, name: &str) {
+        self.groups.remove(name);
+    }
 }
 
 #[derive(Debug)]
 struct Cpu {
-    values: Vec<u64>
+    values: Vec<u64>,
+    cores: usize
 }
 
 impl Cpu {
@@ -83,25 +491,228 @@ impl Cpu {
         let values: Result<Vec<u64>, _> = re[2..].iter()
             .map(|&e| e.parse::<u64>().map_err(|e| anyhow::anyhow!("Failed to parse CPU value '{}': {}", e, e)))
             .collect();
-        Ok(Cpu{values: values?})
+        // The aggregated line is followed by one line per core
+        let cores = lines.map_while(|line| line.ok()).take_while(|line| line.starts_with("cpu")).count();
+        Ok(Cpu{values: values?, cores: cores.max(1)})
     }
 
-    pub fn usage(&self, last: &Cpu) -> i32 {
+    /// Usage percentage of all the cores since the last sample. None if no tick elapsed
+    pub fn usage(&self, last: &Cpu) -> Option<i32> {
         let last_sum = last.values.iter().sum::<u64>();
         let current_sum = self.values.iter().sum::<u64>();
-        let delta = current_sum - last_sum;
-        let idle = self.values[3] - last.values[3];
-        let used = delta - idle;
+        let delta = current_sum.checked_sub(last_sum).filter(|delta| *delta > 0)?;
+        let idle = self.values[3].saturating_sub(last.values[3]);
+        let used = delta.saturating_sub(idle);
         let usage = 100 * used / delta;
-        usage as i32
+        Some(usage as i32)
+    }
+
+}
+
+#[derive(Debug)]
+struct TrackedProcess {
+    last: Process,
+    children: Option<ChildTracking>,
+    descendants: HashMap<i32, Process>,
+    // CPU usage of the last sample, by pid
+    reported: HashMap<i32, f64>,
+}
+
+/// Processes reported together as one entry
+#[derive(Debug)]
+struct TrackedGroup {
+    members: HashMap<i32, Process>,
+    // Time of the last sample and CPU usage reported then
+    when: Instant,
+    reported: f64,
+}
+
+/// Builds the reported process with the memory at this moment
+fn process_model(pid: i32, process: &Process, cpu: f64, io: (Option<f64>, Option<f64>), parent: Option<i32>) -> ProcessModel {
+    let memory = ProcessMemory::read(pid).unwrap_or_else(|err| {
+        warn!("Cannot get memory of process {}: {:?}", pid, err);
+        ProcessMemory::default()
+    });
+    let (open_files_soft_limit, open_files_hard_limit) = open_files_limits(pid);
+    let now = host::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    ProcessModel {
+        pid,
+        cpu,
+        memory: memory.resident,
+        virtual_memory: memory.size,
+        shared_memory: memory.shared,
+        name: process.name.clone(),
+        state: ProcessState::from_code(process.state),
+        nice: process.nice,
+        executable: executable(pid),
+        command_line: command_line(pid),
+        start_time: process.start_time,
+        uptime: process.start_time.map(|start| now.saturating_sub(start)),
+        read_bytes_per_second: io.0,
+        write_bytes_per_second: io.1,
+        open_files: open_files(pid),
+        open_files_soft_limit,
+        open_files_hard_limit,
+        graphics: vec![],
+        parent,
+        children: 0,
+        group: None
+    }
+}
+
+/// Last report of a process that does not exist anymore
+fn exited_model(pid: i32, last: &Process, parent: Option<i32>) -> ProcessModel {
+    ProcessModel {
+        pid,
+        name: last.name.clone(),
+        state: ProcessState::Exited,
+        nice: last.nice,
+        executable: None,
+        command_line: vec![],
+        cpu: 0.0,
+        memory: 0,
+        virtual_memory: 0,
+        shared_memory: 0,
+        start_time: last.start_time,
+        uptime: None,
+        read_bytes_per_second: None,
+        write_bytes_per_second: None,
+        open_files: None,
+        open_files_soft_limit: None,
+        open_files_hard_limit: None,
+        graphics: vec![],
+        parent,
+        children: 0,
+        group: None
+    }
+}
+
+fn executable(pid: i32) -> Option<String> {
+    std::fs::read_link(host::path(&format!("/proc/{}/exe", pid)))
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|err| debug!("Cannot read executable of process {}: {:?}", pid, err))
+        .ok()
+}
+
+/// Arguments from /proc/<pid>/cmdline, they are separated by NUL
+fn command_line(pid: i32) -> Vec<String> {
+    match std::fs::read(host::path(&format!("/proc/{}/cmdline", pid))) {
+        Ok(content) => content.split(|&c| c == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).to_string())
+            .collect(),
+        Err(err) => {
+            debug!("Cannot read command line of process {}: {:?}", pid, err);
+            vec![]
+        }
+    }
+}
+
+/// Number of cores, from the per core lines of /proc/stat
+pub(crate) fn cores() -> usize {
+    File::open(host::path("/proc/stat")).ok()
+        .and_then(|file| Cpu::from_file(file).ok())
+        .map(|cpu| cpu.cores)
+        .unwrap_or(1)
+}
+
+/// Unix timestamp of the system boot, from the btime line of /proc/stat
+fn boot_time() -> Option<u64> {
+    let stat = std::fs::read_to_string(host::path("/proc/stat"))
+        .map_err(|err| debug!("Cannot read /proc/stat: {:?}", err))
+        .ok()?;
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|value| value.trim().parse::<u64>().ok())
+}
+
+/// Sum of two values, unknown if any of them is unknown
+fn add(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    Some(a? + b?)
+}
+
+fn open_files(pid: i32) -> Option<u64> {
+    match std::fs::read_dir(host::path(&format!("/proc/{}/fd", pid))) {
+        Ok(entries) => Some(entries.count() as u64),
+        Err(err) => {
+            debug!("Cannot list file descriptors of process {}: {:?}", pid, err);
+            None
+        }
     }
+}
 
+/// Soft and hard limits of the "Max open files" line in /proc/<pid>/limits
+fn open_files_limits(pid: i32) -> (Option<u64>, Option<u64>) {
+    let limits = match std::fs::read_to_string(host::path(&format!("/proc/{}/limits", pid))) {
+        Ok(limits) => limits,
+        Err(err) => {
+            debug!("Cannot read limits of process {}: {:?}", pid, err);
+            return (None, None);
+        }
+    };
+    limits.lines()
+        .find_map(|line| line.strip_prefix("Max open files"))
+        .map(|values| {
+            let mut values = values.split_whitespace().map(|value| value.parse::<u64>().ok());
+            (values.next().flatten(), values.next().flatten())
+        })
+        .unwrap_or((None, None))
+}
+
+/// Parent PID from the content of /proc/<pid>/stat. The name can contain spaces, so the fields are read after it
+fn parent_pid(stat: &str) -> Option<i32> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(1)?.parse::<i32>().ok()
 }
 
 #[derive(Debug)]
 struct Process {
     pub total_time: i32,
-    pub when: SystemTime,
+    /// Time used by the process itself, without its waited children
+    pub own_time: i32,
+    pub io: Option<ProcessIo>,
+    pub name: String,
+    /// State letter (R, S, D, Z...)
+    pub state: char,
+    pub parent: i32,
+    pub nice: i32,
+    /// Ticks since boot when the process started
+    pub start: Option<u64>,
+    /// Unix timestamp when the process started
+    pub start_time: Option<u64>,
+    pub when: Instant,
+}
+
+/// Storage I/O counters from /proc/<pid>/io
+#[derive(Debug)]
+struct ProcessIo {
+    read_bytes: u64,
+    write_bytes: u64
+}
+
+impl ProcessIo {
+    pub fn read(pid: i32) -> Result<ProcessIo> {
+        ProcessIo::from_file(File::open(host::path(&format!("/proc/{}/io", pid)))?)
+    }
+
+    pub fn from_file(file: impl std::io::Read) -> Result<ProcessIo> {
+        let mut read_bytes = None;
+        let mut write_bytes = None;
+        for line in io::BufReader::new(file).lines() {
+            let line = line?;
+            if let Some((key, value)) = line.split_once(':') {
+                match key {
+                    "read_bytes" => read_bytes = value.trim().parse::<u64>().ok(),
+                    "write_bytes" => write_bytes = value.trim().parse::<u64>().ok(),
+                    _ => {}
+                }
+            }
+        }
+        Ok(ProcessIo {
+            read_bytes: read_bytes.ok_or_else(|| anyhow::anyhow!("No read_bytes found in process io file"))?,
+            write_bytes: write_bytes.ok_or_else(|| anyhow::anyhow!("No write_bytes found in process io file"))?
+        })
+    }
 }
 
 
@@ -111,11 +722,16 @@ impl Process {
         let mut lines = io::BufReader::new(file).lines();
         let line = lines.next()
             .ok_or_else(|| anyhow::anyhow!("No lines found in process stat file"))??;
-        let params = line.split(" ").collect::<Vec<&str>>();
+        // The name is between parentheses and can contain spaces, so it is split apart from the other fields
+        let (head, tail) = line.rsplit_once(')')
+            .ok_or_else(|| anyhow::anyhow!("No process name found in process stat file"))?;
+        let (pid, name) = head.split_once(" (")
+            .ok_or_else(|| anyhow::anyhow!("No process name found in process stat file"))?;
+        let params = [pid, name].into_iter().chain(tail.split_whitespace()).collect::<Vec<&str>>();
         
         // Ensure we have enough parameters before parsing
-        if params.len() < 18 {
-            return Err(anyhow::anyhow!("Process stat file has insufficient parameters (expected at least 18, got {})", params.len()));
+        if params.len() < 19 {
+            return Err(anyhow::anyhow!("Process stat file has insufficient parameters (expected at least 19, got {})", params.len()));
         }
         
         let total_time: i32 = params[13..18].iter()
@@ -124,33 +740,125 @@ impl Process {
             .iter()
             .sum();
         
+        let own_time: i32 = params[13..15].iter()
+            .map(|e| e.parse::<i32>().map_err(|e| anyhow::anyhow!("Failed to parse process time value '{}': {}", e, e)))
+            .collect::<Result<Vec<i32>, _>>()?
+            .iter()
+            .sum();
+        
         Ok(Process{
             total_time,
-            when: SystemTime::now()
+            own_time,
+            io: None,
+            name: name.to_string(),
+            state: params[2].chars().next().unwrap_or('?'),
+            parent: params[3].parse::<i32>().unwrap_or_default(),
+            nice: params[18].parse::<i32>().unwrap_or_default(),
+            start: params.get(21).and_then(|e| e.parse::<u64>().ok()),
+            start_time: None,
+            when: host::instant()
         })
     }
 
     pub fn usage(&self, last: &Process) -> f64 {
-        let computing_time = (self.total_time - last.total_time) as f64;
-        // I use unwrap here because I can warrantee that now is higher than last.
-        // I'm assuming that CLK_TCK is 100, this is why I multiply seconds by 100
-        let elapsed_time = (SystemTime::now().duration_since(last.when).unwrap().as_secs()*100) as f64;
+        self.cpu(self.total_time - last.total_time, last)
+    }
+
+    /// Usage without the time of waited children. Used when the children are tracked too, so their time
+    /// is not counted twice
+    pub fn own_usage(&self, last: &Process) -> f64 {
+        self.cpu(self.own_time - last.own_time, last)
+    }
+
+    /// Bytes read and written per second. A process without previous sample (just found) reports 0
+    pub fn io_usage(&self, last: Option<&Process>) -> (Option<f64>, Option<f64>) {
+        let current = match &self.io {
+            Some(current) => current,
+            None => return (None, None)
+        };
+        let (last_io, last_when) = match last {
+            Some(last) => match &last.io {
+                Some(last_io) => (last_io, last.when),
+                None => return (None, None)
+            },
+            None => return (Some(0.0), Some(0.0))
+        };
+        let elapsed = self.when.duration_since(last_when).as_secs_f64();
+        if elapsed <= 0.0 {
+            return (Some(0.0), Some(0.0));
+        }
+        let rate = |current: u64, last: u64| Some(current.saturating_sub(last) as f64 / elapsed);
+        (rate(current.read_bytes, last_io.read_bytes), rate(current.write_bytes, last_io.write_bytes))
+    }
+
+    fn cpu(&self, computing_time: i32, last: &Process) -> f64 {
+        let computing_time = computing_time as f64;
+        // I'm assuming that CLK_TCK is 100, so the elapsed time is counted in ticks of 10ms
+        let elapsed_time = (host::instant().duration_since(last.when).as_millis() / 10) as f64;
+        if elapsed_time <= 0.0 {
+            return 0.0;
+        }
         // Return it as percentaje
-        let usage = 100.0 * (computing_time / elapsed_time);
-        usage
+        100.0 * (computing_time / elapsed_time)
     }
 
 }
 
 
+/// Process memory in bytes from /proc/<pid>/statm
+#[derive(Debug, Default)]
+struct ProcessMemory {
+    size: u64,
+    resident: u64,
+    shared: u64
+}
+
+impl ProcessMemory {
+    pub fn read(pid: i32) -> Result<ProcessMemory> {
+        ProcessMemory::from_file(File::open(host::path(&format!("/proc/{}/statm", pid)))?)
+    }
+
+    pub fn from_file(file: impl std::io::Read) -> Result<ProcessMemory> {
+        let mut lines = io::BufReader::new(file).lines();
+        let line = lines.next()
+            .ok_or_else(|| anyhow::anyhow!("No lines found in process statm file"))??;
+        let values = line.split_whitespace()
+            .take(3)
+            .map(|e| e.parse::<u64>().map_err(|err| anyhow::anyhow!("Failed to parse process memory value '{}': {}", e, err)))
+            .collect::<Result<Vec<u64>>>()?;
+        if values.len() < 3 {
+            return Err(anyhow::anyhow!("Process statm file has insufficient values (expected 3, got {})", values.len()));
+        }
+        // Values are in pages
+        let page_size = page_size();
+        Ok(ProcessMemory {
+            size: values[0] * page_size,
+            resident: values[1] * page_size,
+            shared: values[2] * page_size
+        })
+    }
+}
+
+#[cfg(unix)]
+fn page_size() -> u64 {
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 { size as u64 } else { 4096 }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> u64 {
+    4096
+}
 
 
 pub struct Memory {
     total: i32,
     free: i32,
+    available: Option<i32>,
     buffers: i32,
     cached: i32,
-    reclaimable: i32
+    reclaimable: i32,
+    dirty: i32
 }
 
 fn memory_value(raw: &str) -> Result<i32> {
@@ -164,7 +872,7 @@ fn memory_value(raw: &str) -> Result<i32> {
 impl Memory {
     pub fn from_file(file: impl std::io::Read) -> Result<Memory> {
         let mut m = Memory {
-            total:0, free: 0, buffers: 0, cached: 0, reclaimable: 0
+            total:0, free: 0, available: None, buffers: 0, cached: 0, reclaimable: 0, dirty: 0
         };
 
         for line in io::BufReader::new(file).lines() {
@@ -172,9 +880,11 @@ impl Memory {
                 match field {
                     "MemTotal" => m.total = memory_value(value)?,
                     "MemFree" => m.free = memory_value(value)?,
+                    "MemAvailable" => m.available = Some(memory_value(value)?),
                     "Buffers" => m.buffers = memory_value(value)?,
                     "Cached" => m.cached = memory_value(value)?,
                     "SReclaimable" => m.reclaimable = memory_value(value)?,
+                    "Dirty" => m.dirty = memory_value(value)?,
                     _ => continue
                 };
             }
@@ -187,5 +897,343 @@ impl Memory {
     pub fn usage(&self) -> i32 {
         self.total - self.free - self.buffers - self.cached - self.reclaimable
     }
+
+    pub fn breakdown(&self) -> MemoryBreakdown {
+        // Values are in kB
+        let bytes = |value: i32| value.max(0) as u64 * 1024;
+        let cached = self.cached + self.reclaimable;
+        // Old kernels don't provide MemAvailable, so it is estimated as free does
+        let available = self.available.unwrap_or(self.free + self.buffers + cached);
+        MemoryBreakdown {
+            total: bytes(self.total),
+            used: bytes(self.total - available),
+            free: bytes(self.free),
+            available: bytes(available),
+            buffers: bytes(self.buffers),
+            cached: bytes(cached),
+            dirty: bytes(self.dirty),
+        }
+    }
 }
 
+
+
+#[derive(Debug, Default)]
+struct DiskStats {
+    reads: u64,
+    read_sectors: u64,
+    read_time: u64,
+    writes: u64,
+    written_sectors: u64,
+    write_time: u64,
+    in_flight: u64,
+    io_time: u64
+}
+
+impl DiskStats {
+    pub fn from_file(file: impl std::io::Read) -> Result<HashMap<String, DiskStats>> {
+        let mut disks = HashMap::new();
+        for line in io::BufReader::new(file).lines() {
+            let line = line?;
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            if fields.len() < 14 {
+                continue;
+            }
+            let name = fields[2];
+            // Virtual devices without real IO
+            if name.starts_with("loop") || name.starts_with("ram") {
+                continue;
+            }
+            let values = fields[3..14].iter()
+                .map(|e| e.parse::<u64>().map_err(|err| anyhow::anyhow!("Failed to parse disk stat value '{}': {}", e, err)))
+                .collect::<Result<Vec<u64>>>()?;
+            disks.insert(name.to_string(), DiskStats {
+                reads: values[0],
+                read_sectors: values[2],
+                read_time: values[3],
+                writes: values[4],
+                written_sectors: values[6],
+                write_time: values[7],
+                in_flight: values[8],
+                io_time: values[9]
+            });
+        }
+        Ok(disks)
+    }
+
+    pub fn usage(&self, name: &str, last: Option<&DiskStats>, elapsed: f64) -> DiskStatus {
+        let mut status = DiskStatus {
+            name: name.to_string(),
+            read_bytes_per_second: 0.0,
+            write_bytes_per_second: 0.0,
+            read_latency: 0.0,
+            write_latency: 0.0,
+            in_flight: self.in_flight,
+            utilization: 0.0
+        };
+        // The first sample has nothing to compare with
+        let last = match last {
+            Some(last) if elapsed > 0.0 => last,
+            _ => return status
+        };
+        let per_second = |current: u64, last: u64| current.saturating_sub(last) as f64 / elapsed;
+        let average = |time: u64, last_time: u64, count: u64, last_count: u64| {
+            let count = count.saturating_sub(last_count);
+            if count > 0 { time.saturating_sub(last_time) as f64 / count as f64 } else { 0.0 }
+        };
+        // Sectors are always 512 bytes here
+        status.read_bytes_per_second = per_second(self.read_sectors, last.read_sectors) * 512.0;
+        status.write_bytes_per_second = per_second(self.written_sectors, last.written_sectors) * 512.0;
+        status.read_latency = average(self.read_time, last.read_time, self.reads, last.reads);
+        status.write_latency = average(self.write_time, last.write_time, self.writes, last.writes);
+        // io_time is in milliseconds
+        status.utilization = (per_second(self.io_time, last.io_time) / 10.0).min(100.0);
+        status
+    }
+}
+
+
+#[derive(Debug)]
+struct NetworkStats {
+    rx_bytes: u64,
+    rx_packets: u64,
+    rx_errors: u64,
+    rx_dropped: u64,
+    tx_bytes: u64,
+    tx_packets: u64,
+    tx_errors: u64,
+    tx_dropped: u64,
+    collisions: u64
+}
+
+impl NetworkStats {
+    pub fn from_file(file: impl std::io::Read) -> Result<HashMap<String, NetworkStats>> {
+        let mut networks = HashMap::new();
+        // The first two lines are headers
+        for line in io::BufReader::new(file).lines().skip(2) {
+            let line = line?;
+            let (name, values) = match line.split_once(':') {
+                Some((name, values)) => (name.trim(), values),
+                None => continue
+            };
+            if name == "lo" {
+                continue;
+            }
+            let values = values.split_whitespace()
+                .map(|e| e.parse::<u64>().map_err(|err| anyhow::anyhow!("Failed to parse network stat value '{}': {}", e, err)))
+                .collect::<Result<Vec<u64>>>()?;
+            if values.len() < 16 {
+                return Err(anyhow::anyhow!("Network stats of {} have insufficient values (expected 16, got {})", name, values.len()));
+            }
+            networks.insert(name.to_string(), NetworkStats {
+                rx_bytes: values[0],
+                rx_packets: values[1],
+                rx_errors: values[2],
+                rx_dropped: values[3],
+                tx_bytes: values[8],
+                tx_packets: values[9],
+                tx_errors: values[10],
+                tx_dropped: values[11],
+                collisions: values[13]
+            });
+        }
+        Ok(networks)
+    }
+
+    pub fn usage(&self, name: &str, last: Option<&NetworkStats>, elapsed: f64) -> NetworkStatus {
+        let mut status = NetworkStatus {
+            name: name.to_string(),
+            rx_bytes_per_second: 0.0,
+            tx_bytes_per_second: 0.0,
+            rx_packets_per_second: 0.0,
+            tx_packets_per_second: 0.0,
+            rx_errors: 0,
+            tx_errors: 0,
+            rx_dropped: 0,
+            tx_dropped: 0,
+            collisions: 0
+        };
+        // The first sample has nothing to compare with
+        let last = match last {
+            Some(last) if elapsed > 0.0 => last,
+            _ => return status
+        };
+        // Counters are reset if the interface is recreated, so they may go backwards
+        let per_second = |current: u64, last: u64| current.saturating_sub(last) as f64 / elapsed;
+        status.rx_bytes_per_second = per_second(self.rx_bytes, last.rx_bytes);
+        status.tx_bytes_per_second = per_second(self.tx_bytes, last.tx_bytes);
+        status.rx_packets_per_second = per_second(self.rx_packets, last.rx_packets);
+        status.tx_packets_per_second = per_second(self.tx_packets, last.tx_packets);
+        status.rx_errors = self.rx_errors.saturating_sub(last.rx_errors);
+        status.tx_errors = self.tx_errors.saturating_sub(last.tx_errors);
+        status.rx_dropped = self.rx_dropped.saturating_sub(last.rx_dropped);
+        status.tx_dropped = self.tx_dropped.saturating_sub(last.tx_dropped);
+        status.collisions = self.collisions.saturating_sub(last.collisions);
+        status
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use super::*;
+    use crate::testkit::FakeTree;
+
+    fn close(value: f64, expected: f64) -> bool {
+        (value - expected).abs() <= expected.abs() * 0.01 + 0.01
+    }
+
+    #[test]
+    fn cpu_usage_between_samples() {
+        let mut tree = FakeTree::new(1).unwrap();
+        let mut monitor = Monitor::new(MonitorConfig::default());
+        monitor.next().unwrap();
+        tree.tick(Duration::from_secs(10), 30).unwrap();
+        assert_eq!(monitor.next().unwrap().0, 30);
+
+        let mut per_core = Monitor::new(MonitorConfig { system_scale: CpuScale::PerCore, ..MonitorConfig::default() });
+        per_core.next().unwrap();
+        tree.tick(Duration::from_secs(10), 50).unwrap();
+        // 4 CPUs at 50%
+        assert_eq!(per_core.next().unwrap().0, 200);
+    }
+
+    #[test]
+    fn cpu_usage_kept_without_ticks() {
+        let mut tree = FakeTree::new(1).unwrap();
+        let mut monitor = Monitor::new(MonitorConfig::default());
+        monitor.next().unwrap();
+        tree.tick(Duration::from_secs(10), 40).unwrap();
+        assert_eq!(monitor.next().unwrap().0, 40);
+        // Too soon, the last usage is reported
+        assert_eq!(monitor.next().unwrap().0, 40);
+    }
+
+    #[test]
+    fn process_usage_between_samples() {
+        let mut tree = FakeTree::new(1).unwrap();
+        let pid = tree.spawn_process(25.0).unwrap();
+        let mut monitor = Monitor::new(MonitorConfig::default());
+        monitor.track_process(pid, None).unwrap();
+        tree.add_process_io(pid, 10 * 1024 * 1024, 1024 * 1024).unwrap();
+        tree.set_process_nice(pid, 5).unwrap();
+        tree.tick(Duration::from_secs(10), 50).unwrap();
+
+        let processes = monitor.next_processes();
+        assert_eq!(processes.len(), 1);
+        let process = &processes[0];
+        assert_eq!(process.pid, pid);
+        assert_eq!(process.name, format!("fake-{}", pid));
+        assert_eq!(process.nice, 5);
+        assert_eq!(process.state, ProcessState::Running);
+        assert!(close(process.cpu, 25.0), "cpu {}", process.cpu);
+        assert!(close(process.read_bytes_per_second.unwrap(), 1024.0 * 1024.0));
+        assert!(close(process.write_bytes_per_second.unwrap(), 1024.0 * 1024.0 / 10.0));
+        assert_eq!(process.open_files, Some(3));
+        assert_eq!(process.open_files_soft_limit, Some(1024));
+        assert_eq!(process.command_line, vec!["/usr/bin/fake", "--id", &pid.to_string()]);
+    }
+
+    #[test]
+    fn aggregated_children_usage() {
+        let mut tree = FakeTree::new(1).unwrap();
+        let parent = tree.spawn_process(10.0).unwrap();
+        let child = tree.spawn_child(parent, 20.0).unwrap();
+        tree.spawn_child(child, 30.0).unwrap();
+        let mut monitor = Monitor::new(MonitorConfig::default());
+        monitor.track_process(parent, Some(ChildTracking::Aggregate)).unwrap();
+        // The children are found on the first sample
+        monitor.next_processes();
+        tree.tick(Duration::from_secs(10), 50).unwrap();
+
+        let processes = monitor.next_processes();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].children, 2);
+        assert!(close(processes[0].cpu, 60.0), "cpu {}", processes[0].cpu);
+    }
+
+    #[test]
+    fn per_child_usage() {
+        let mut tree = FakeTree::new(1).unwrap();
+        let parent = tree.spawn_process(10.0).unwrap();
+        let child = tree.spawn_child(parent, 20.0).unwrap();
+        let mut monitor = Monitor::new(MonitorConfig::default());
+        monitor.track_process(parent, Some(ChildTracking::PerChild)).unwrap();
+        monitor.next_processes();
+        tree.tick(Duration::from_secs(10), 50).unwrap();
+
+        let processes = monitor.next_processes();
+        assert_eq!(processes.len(), 2);
+        let child = processes.iter().find(|process| process.pid == child).unwrap();
+        assert_eq!(child.parent, Some(parent));
+        assert!(close(child.cpu, 20.0), "cpu {}", child.cpu);
+        let parent = processes.iter().find(|process| process.pid == parent).unwrap();
+        assert!(close(parent.cpu, 10.0), "cpu {}", parent.cpu);
+    }
+
+    #[test]
+    fn process_stat_with_spaces_in_the_name() {
+        let stat = "42 (Web Content) S 1 42 42 0 -1 4194560 0 0 0 0 150 50 7 3 20 -2 1 0 12345 0 0\n";
+        let process = Process::from_file(stat.as_bytes()).unwrap();
+        assert_eq!(process.name, "Web Content");
+        assert_eq!(process.state, 'S');
+        assert_eq!(process.parent, 1);
+        // utime and stime, without the time of the waited children
+        assert_eq!(process.own_time, 200);
+        assert_eq!(process.nice, -2);
+        assert_eq!(process.start, Some(12345));
+        assert!(Process::from_file("42 (short) S 1\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn disk_rates_between_samples() {
+        let mut tree = FakeTree::new(1).unwrap();
+        tree.write("/proc/diskstats",
+            "   7       0 loop0 10 0 80 0 0 0 0 0 0 0 0 0 0 0 0\n\
+             \x20  8       0 sda 100 0 2000 50 200 0 4000 100 0 300 150 0 0 0 0\n").unwrap();
+        let mut monitor = Monitor::new(MonitorConfig::default());
+        let first = monitor.next_disks().unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].name, "sda");
+        assert_eq!(first[0].read_bytes_per_second, 0.0);
+
+        // 10MiB read in 50 reads of 5ms and 1MiB written in 100 writes of 2ms, busy half of the time
+        tree.write("/proc/diskstats",
+            "   7       0 loop0 10 0 80 0 0 0 0 0 0 0 0 0 0 0 0\n\
+             \x20  8       0 sda 150 0 22480 300 300 0 6048 300 3 5300 700 0 0 0 0\n").unwrap();
+        tree.tick(Duration::from_secs(10), 0).unwrap();
+        let disk = &monitor.next_disks().unwrap()[0];
+        assert!(close(disk.read_bytes_per_second, 1024.0 * 1024.0), "read {}", disk.read_bytes_per_second);
+        assert!(close(disk.write_bytes_per_second, 1024.0 * 1024.0 / 10.0));
+        assert_eq!(disk.read_latency, 5.0);
+        assert_eq!(disk.write_latency, 2.0);
+        assert_eq!(disk.in_flight, 3);
+        assert!(close(disk.utilization, 50.0), "utilization {}", disk.utilization);
+    }
+
+    #[test]
+    fn network_rates_between_samples() {
+        let header = "Inter-|   Receive                                                |  Transmit\n \
+            face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n";
+        let mut tree = FakeTree::new(1).unwrap();
+        tree.write("/proc/net/dev", &format!("{}    lo: 500 5 0 0 0 0 0 0 500 5 0 0 0 0 0 0\n  eth0: 1000 10 1 2 0 0 0 0 2000 20 3 4 0 5 0 0\n", header)).unwrap();
+        let mut monitor = Monitor::new(MonitorConfig::default());
+        let first = monitor.next_networks().unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].rx_errors, 0);
+
+        tree.write("/proc/net/dev", &format!("{}    lo: 900 9 0 0 0 0 0 0 900 9 0 0 0 0 0 0\n  eth0: 11000 110 2 2 0 0 0 0 7000 70 3 6 0 5 0 0\n", header)).unwrap();
+        tree.tick(Duration::from_secs(10), 0).unwrap();
+        let network = &monitor.next_networks().unwrap()[0];
+        assert_eq!(network.name, "eth0");
+        assert!(close(network.rx_bytes_per_second, 1000.0));
+        assert!(close(network.tx_bytes_per_second, 500.0));
+        assert!(close(network.rx_packets_per_second, 10.0));
+        assert!(close(network.tx_packets_per_second, 5.0));
+        assert_eq!((network.rx_errors, network.rx_dropped, network.tx_errors, network.tx_dropped, network.collisions), (1, 0, 0, 2, 0));
+
+        // The interface was recreated and its counters started again
+        tree.write("/proc/net/dev", &format!("{}  eth0: 10 1 0 0 0 0 0 0 10 1 0 0 0 0 0 0\n", header)).unwrap();
+        tree.tick(Duration::from_secs(10), 0).unwrap();
+        assert_eq!(monitor.next_networks().unwrap()[0].rx_bytes_per_second, 0.0);
+    }
+}
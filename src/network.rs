@@ -0,0 +1,310 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, Ipv6Addr, UdpSocket};
+use log::debug;
+use crate::model::{WakeOnLan, NetworkInterface, Ipv6Address, IpStatus, SocketSummary};
+use crate::host;
+
+const NET_PATH: &str = "/sys/class/net";
+const WAKE_MODES: [(u32, &str); 7] = [
+    (1 << 0, "phy"),
+    (1 << 1, "unicast"),
+    (1 << 2, "multicast"),
+    (1 << 3, "broadcast"),
+    (1 << 4, "arp"),
+    (1 << 5, "magic"),
+    (1 << 6, "magicsecure"),
+];
+
+/// Names of the network interfaces, loopback excluded
+pub fn interfaces() -> Result<Vec<String>> {
+    let mut names = fs::read_dir(host::path(NET_PATH))?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name != "lo")
+        .collect::<Vec<String>>();
+    names.sort();
+    Ok(names)
+}
+
+/// Addresses assigned to every interface, with their prefix length
+#[cfg(unix)]
+fn addresses() -> Result<HashMap<String, Vec<(IpAddr, u8)>>> {
+    use std::ffi::CStr;
+    use std::net::Ipv4Addr;
+
+    let mut result: HashMap<String, Vec<(IpAddr, u8)>> = HashMap::new();
+    unsafe {
+        let mut addresses: *mut libc::ifaddrs = std::ptr::null_mut();
+        if libc::getifaddrs(&mut addresses) != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let mut current = addresses;
+        while !current.is_null() {
+            let entry = &*current;
+            current = entry.ifa_next;
+            if entry.ifa_addr.is_null() {
+                continue;
+            }
+            // The netmask has the same family as the address
+            let address = match (*entry.ifa_addr).sa_family as i32 {
+                libc::AF_INET => {
+                    let address = &*(entry.ifa_addr as *const libc::sockaddr_in);
+                    let prefix = entry.ifa_netmask.as_ref()
+                        .map(|mask| (*(mask as *const libc::sockaddr as *const libc::sockaddr_in)).sin_addr.s_addr.count_ones())
+                        .unwrap_or(0);
+                    (IpAddr::V4(Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr))), prefix as u8)
+                },
+                libc::AF_INET6 => {
+                    let address = &*(entry.ifa_addr as *const libc::sockaddr_in6);
+                    let prefix = entry.ifa_netmask.as_ref()
+                        .map(|mask| (*(mask as *const libc::sockaddr as *const libc::sockaddr_in6)).sin6_addr.s6_addr.iter()
+                            .map(|byte| byte.count_ones())
+                            .sum())
+                        .unwrap_or(0);
+                    (IpAddr::V6(Ipv6Addr::from(address.sin6_addr.s6_addr)), prefix as u8)
+                },
+                _ => continue
+            };
+            let name = CStr::from_ptr(entry.ifa_name).to_string_lossy().to_string();
+            result.entry(name).or_default().push(address);
+        }
+        libc::freeifaddrs(addresses);
+    }
+    Ok(result)
+}
+
+#[cfg(not(unix))]
+fn addresses() -> Result<HashMap<String, Vec<(IpAddr, u8)>>> {
+    Err(anyhow::anyhow!("Interface addresses are not supported in this platform"))
+}
+
+fn read_attribute(interface: &str, attribute: &str) -> Option<String> {
+    fs::read_to_string(host::path(&format!("{}/{}/{}", NET_PATH, interface, attribute))).ok()
+        .map(|value| value.trim().to_string())
+}
+
+/// Scope of an IPv6 address, like the kernel assigns it
+fn ipv6_scope(address: &Ipv6Addr) -> &'static str {
+    let segment = address.segments()[0];
+    if address.is_loopback() {
+        "host"
+    } else if segment & 0xffc0 == 0xfe80 {
+        "link"
+    } else if segment & 0xffc0 == 0xfec0 {
+        "site"
+    } else {
+        "global"
+    }
+}
+
+/// Network interfaces with their addresses, loopback excluded
+pub fn network_interfaces() -> Result<Vec<NetworkInterface>> {
+    let addresses = addresses().unwrap_or_else(|e| {
+        debug!("Cannot get interface addresses: {}", e);
+        HashMap::new()
+    });
+    let mut result = Vec::new();
+    for name in interfaces()? {
+        let assigned = addresses.get(&name).cloned().unwrap_or_default();
+        result.push(NetworkInterface {
+            mac: read_attribute(&name, "address").unwrap_or_default(),
+            mtu: read_attribute(&name, "mtu").and_then(|mtu| mtu.parse::<u32>().ok()).unwrap_or(0),
+            state: read_attribute(&name, "operstate").unwrap_or_else(|| "unknown".to_string()),
+            // Virtual interfaces and links down report -1 or fail to read
+            speed: read_attribute(&name, "speed").and_then(|speed| speed.parse::<u32>().ok()).filter(|speed| *speed > 0),
+            duplex: read_attribute(&name, "duplex").filter(|duplex| duplex != "unknown"),
+            ipv4: assigned.iter().filter(|(a, _)| a.is_ipv4()).map(|(a, _)| a.to_string()).collect(),
+            ipv6_addresses: assigned.iter()
+                .filter_map(|(address, prefix_length)| match address {
+                    IpAddr::V6(address) => Some(Ipv6Address {
+                        address: address.to_string(),
+                        prefix_length: *prefix_length,
+                        scope: ipv6_scope(address).to_string(),
+                    }),
+                    IpAddr::V4(_) => None
+                })
+                .collect(),
+            ipv6_disabled: ipv6_disabled(&name),
+            name,
+        });
+    }
+    Ok(result)
+}
+
+/// Parses a MAC address like 02:fc:00:00:00:01 or 02-fc-00-00-00-01
+pub fn parse_mac(raw: &str) -> Result<[u8; 6]> {
+    let bytes = raw.trim().split([':', '-'])
+        .map(|b| u8::from_str_radix(b, 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| anyhow::anyhow!("Invalid MAC address {}", raw))?;
+    bytes.try_into().map_err(|_| anyhow::anyhow!("Invalid MAC address {}", raw))
+}
+
+fn wake_modes(flags: u32) -> Vec<String> {
+    WAKE_MODES.iter()
+        .filter(|(flag, _)| flags & flag != 0)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+struct EthtoolWolinfo {
+    cmd: u32,
+    supported: u32,
+    wolopts: u32,
+    sopass: [u8; 6],
+}
+
+/// Supported and enabled Wake-on-LAN flags using the ethtool ioctl
+#[cfg(target_os = "linux")]
+fn ethtool_wol(interface: &str) -> Result<(u32, u32)> {
+    const ETHTOOL_GWOL: u32 = 0x00000005;
+    if interface.len() >= libc::IFNAMSIZ {
+        return Err(anyhow::anyhow!("Interface name too long: {}", interface));
+    }
+    let mut wol = EthtoolWolinfo { cmd: ETHTOOL_GWOL, supported: 0, wolopts: 0, sopass: [0; 6] };
+    // The ifreq only holds the interface name and a pointer to the ethtool request
+    unsafe {
+        let mut request: libc::ifreq = std::mem::zeroed();
+        for (i, byte) in interface.bytes().enumerate() {
+            request.ifr_name[i] = byte as libc::c_char;
+        }
+        request.ifr_ifru.ifru_data = &mut wol as *mut EthtoolWolinfo as *mut libc::c_char;
+        let socket = libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0);
+        if socket < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let result = libc::ioctl(socket, libc::SIOCETHTOOL as _, &mut request);
+        let error = std::io::Error::last_os_error();
+        libc::close(socket);
+        if result < 0 {
+            return Err(error.into());
+        }
+    }
+    Ok((wol.supported, wol.wolopts))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn ethtool_wol(_interface: &str) -> Result<(u32, u32)> {
+    Err(anyhow::anyhow!("Wake-on-LAN information is only available on Linux"))
+}
+
+pub fn wake_on_lan() -> Result<Vec<WakeOnLan>> {
+    let mut result = Vec::new();
+    for interface in interfaces()? {
+        let (supported, enabled) = match ethtool_wol(&interface) {
+            Ok(flags) => flags,
+            Err(e) => {
+                debug!("Cannot get Wake-on-LAN of {}: {}", interface, e);
+                (0, 0)
+            }
+        };
+        let mac = read_attribute(&interface, "address").unwrap_or_default();
+        result.push(WakeOnLan {
+            interface,
+            mac,
+            supported: wake_modes(supported),
+            enabled: wake_modes(enabled),
+        });
+    }
+    Ok(result)
+}
+
+/// Sends the Wake-on-LAN magic packet: 6 bytes 0xFF followed by the MAC address 16 times
+pub fn send_magic_packet(mac: &str, address: &str) -> Result<()> {
+    let mac = parse_mac(mac)?;
+    let mut packet = vec![0xFFu8; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(&mac);
+    }
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.set_broadcast(true)?;
+    socket.send_to(&packet, address)?;
+    Ok(())
+}
+
+/// Values of a /proc/net/snmp section. Every section has a line with the names and another with the values
+pub fn snmp_values(snmp: &str, section: &str) -> HashMap<String, i64> {
+    let prefix = format!("{}:", section);
+    let lines = snmp.lines().filter(|line| line.starts_with(&prefix)).collect::<Vec<&str>>();
+    match lines.as_slice() {
+        [names, values, ..] => names.split_whitespace().skip(1)
+            .zip(values.split_whitespace().skip(1))
+            .filter_map(|(name, value)| Some((name.to_string(), value.parse::<i64>().ok()?)))
+            .collect(),
+        _ => HashMap::new()
+    }
+}
+
+/// Counts the sockets of a /proc/net/{tcp,udp} table by state (hex code in the 4th column)
+fn socket_states(table: &str, counts: &mut HashMap<String, u64>) {
+    let raw = match fs::read_to_string(host::path(&format!("/proc/net/{}", table))) {
+        Ok(raw) => raw,
+        Err(e) => {
+            debug!("Cannot read {} sockets: {}", table, e);
+            return;
+        }
+    };
+    for line in raw.lines().skip(1) {
+        if let Some(state) = line.split_whitespace().nth(3) {
+            *counts.entry(state.to_string()).or_default() += 1;
+        }
+    }
+}
+
+pub fn socket_summary() -> Result<SocketSummary> {
+    const ESTABLISHED: &str = "01";
+    const TIME_WAIT: &str = "06";
+    const LISTEN: &str = "0A";
+
+    let mut tcp = HashMap::new();
+    socket_states("tcp", &mut tcp);
+    socket_states("tcp6", &mut tcp);
+    let mut udp = HashMap::new();
+    socket_states("udp", &mut udp);
+    socket_states("udp6", &mut udp);
+
+    let snmp = fs::read_to_string(host::path("/proc/net/snmp"))?;
+    let counters = snmp_values(&snmp, "Tcp");
+    let counter = |name: &str| counters.get(name).copied().unwrap_or(0).max(0) as u64;
+    Ok(SocketSummary {
+        tcp_established: tcp.get(ESTABLISHED).copied().unwrap_or(0),
+        tcp_time_wait: tcp.get(TIME_WAIT).copied().unwrap_or(0),
+        tcp_listen: tcp.get(LISTEN).copied().unwrap_or(0),
+        tcp_total: tcp.values().sum(),
+        udp: udp.values().sum(),
+        tcp_out_segments: counter("OutSegs"),
+        tcp_retransmitted_segments: counter("RetransSegs"),
+    })
+}
+
+/// True if IPv6 is disabled in the interface. With ipv6.disable=1 the kernel has no IPv6 settings at all
+fn ipv6_disabled(interface: &str) -> bool {
+    match fs::read_to_string(host::path(&format!("/proc/sys/net/ipv6/conf/{}/disable_ipv6", interface))) {
+        Ok(disabled) => disabled.trim() == "1",
+        Err(_) => host::path("/proc/sys/net").exists() && !host::path("/proc/sys/net/ipv6").exists()
+    }
+}
+
+pub fn ip_status() -> Result<IpStatus> {
+    let netstat = fs::read_to_string(host::path("/proc/net/netstat"))?;
+    let ipv4 = snmp_values(&netstat, "IpExt");
+    let ipv4_counter = |name: &str| ipv4.get(name).copied().unwrap_or(0).max(0) as u64;
+    // Lines like "Ip6InOctets   1234". It does not exist without IPv6
+    let snmp6 = fs::read_to_string(host::path("/proc/net/snmp6")).unwrap_or_default();
+    let ipv6_counter = |name: &str| snmp6.lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(counter, _)| *counter == name)
+        .and_then(|(_, value)| value.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+    Ok(IpStatus {
+        ipv6_disabled: ipv6_disabled("all"),
+        ipv4_in_bytes: ipv4_counter("InOctets"),
+        ipv4_out_bytes: ipv4_counter("OutOctets"),
+        ipv6_in_bytes: ipv6_counter("Ip6InOctets"),
+        ipv6_out_bytes: ipv6_counter("Ip6OutOctets"),
+    })
+}
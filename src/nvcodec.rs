@@ -0,0 +1,150 @@
+use log::debug;
+use nvml_wrapper::Device;
+use nvml_wrapper::enum_wrappers::device::{Brand, EncoderType};
+use crate::model::VideoCodecs;
+
+/// First compute capability of NVENC with AV1 encoding (Ada Lovelace)
+const AV1_ENCODER: (i32, i32) = (8, 9);
+
+/// Concurrent encoding sessions allowed by the driver on consumer cards. None for professional and data center
+/// cards, which have no limit
+fn max_encoder_sessions(brand: Option<Brand>, driver_version: &str) -> Option<u32> {
+    if !matches!(brand, Some(Brand::GeForce) | Some(Brand::Titan)) {
+        return None;
+    }
+    let major = driver_version.split('.').next().and_then(|major| major.parse::<u32>().ok()).unwrap_or(0);
+    // The limit was raised to 5 sessions in driver 530 and to 8 in 550
+    Some(match major {
+        550.. => 8,
+        530.. => 5,
+        _ => 3
+    })
+}
+
+/// NVDEC decoders asked to the Video Codec SDK runtime (libnvcuvid, installed with the driver) loaded at runtime
+#[cfg(target_os = "linux")]
+mod nvcuvid {
+    use anyhow::{anyhow, Result};
+    use std::ffi::{CString, c_char, c_int, c_uint, c_void};
+    use std::ptr;
+    use crate::dl::Library;
+
+    type Context = *mut c_void;
+    type Init = unsafe extern "C" fn(c_uint) -> c_int;
+    type DeviceGetByPciBusId = unsafe extern "C" fn(*mut c_int, *const c_char) -> c_int;
+    type PrimaryCtxRetain = unsafe extern "C" fn(*mut Context, c_int) -> c_int;
+    type PrimaryCtxRelease = unsafe extern "C" fn(c_int) -> c_int;
+    type CtxPushCurrent = unsafe extern "C" fn(Context) -> c_int;
+    type CtxPopCurrent = unsafe extern "C" fn(*mut Context) -> c_int;
+    type GetDecoderCaps = unsafe extern "C" fn(*mut DecodeCaps) -> c_int;
+
+    // cudaVideoCodec and cudaVideoChromaFormat values (cuviddec.h)
+    const CODECS: [(&str, c_int); 4] = [("H264", 4), ("HEVC", 8), ("VP9", 10), ("AV1", 11)];
+    const CHROMA_420: c_int = 1;
+
+    /// CUVIDDECODECAPS. Only the input fields and `is_supported` are used
+    #[repr(C)]
+    #[derive(Default)]
+    struct DecodeCaps {
+        codec: c_int,
+        chroma_format: c_int,
+        bit_depth_minus_8: c_uint,
+        reserved1: [c_uint; 3],
+        is_supported: u8,
+        nvdecs: u8,
+        output_format_mask: u16,
+        max_width: c_uint,
+        max_height: c_uint,
+        max_mb_count: c_uint,
+        min_width: u16,
+        min_height: u16,
+        reserved3: [c_uint; 11],
+    }
+
+    /// Codecs the card at the PCI bus id can decode (8 bits 4:2:0 streams). The capabilities can only be asked
+    /// with a CUDA context, so the primary context of the card is retained meanwhile
+    pub fn decoders(bus_id: &str) -> Result<Vec<String>> {
+        let cuda = Library::open("libcuda.so.1")?;
+        let cuvid = Library::open("libnvcuvid.so.1")?;
+        let missing = |name| anyhow!("{} not found", name);
+        let check = |result: c_int, name: &str| match result {
+            0 => Ok(()),
+            error => Err(anyhow!("{} failed with error {}", name, error))
+        };
+        let bus_id = CString::new(bus_id)?;
+        unsafe {
+            let init: Init = cuda.function("cuInit").ok_or_else(|| missing("cuInit"))?;
+            let device_by_bus_id: DeviceGetByPciBusId = cuda.function("cuDeviceGetByPCIBusId")
+                .ok_or_else(|| missing("cuDeviceGetByPCIBusId"))?;
+            let retain: PrimaryCtxRetain = cuda.function("cuDevicePrimaryCtxRetain")
+                .ok_or_else(|| missing("cuDevicePrimaryCtxRetain"))?;
+            let release: PrimaryCtxRelease = cuda.function("cuDevicePrimaryCtxRelease_v2")
+                .ok_or_else(|| missing("cuDevicePrimaryCtxRelease_v2"))?;
+            let push: CtxPushCurrent = cuda.function("cuCtxPushCurrent_v2").ok_or_else(|| missing("cuCtxPushCurrent_v2"))?;
+            let pop: CtxPopCurrent = cuda.function("cuCtxPopCurrent_v2").ok_or_else(|| missing("cuCtxPopCurrent_v2"))?;
+            let decoder_caps: GetDecoderCaps = cuvid.function("cuvidGetDecoderCaps")
+                .ok_or_else(|| missing("cuvidGetDecoderCaps"))?;
+
+            check(init(0), "cuInit")?;
+            let mut device: c_int = 0;
+            check(device_by_bus_id(&mut device, bus_id.as_ptr()), "cuDeviceGetByPCIBusId")?;
+            let mut context: Context = ptr::null_mut();
+            check(retain(&mut context, device), "cuDevicePrimaryCtxRetain")?;
+            let decoders = check(push(context), "cuCtxPushCurrent").map(|_| {
+                let decoders = CODECS.iter()
+                    .filter(|(_, codec)| {
+                        let mut caps = DecodeCaps { codec: *codec, chroma_format: CHROMA_420, ..Default::default() };
+                        decoder_caps(&mut caps) == 0 && caps.is_supported != 0
+                    })
+                    .map(|(name, _)| name.to_string())
+                    .collect();
+                pop(&mut ptr::null_mut());
+                decoders
+            });
+            release(device);
+            decoders
+        }
+    }
+}
+
+/// Codecs the encoders and decoders of an Nvidia card can handle. The encoders are asked to NVML and the decoders
+/// to NVDEC, empty if libnvcuvid cannot be loaded. AV1 encoding, which NVML cannot be asked for, is derived from
+/// the CUDA compute capability
+pub fn video_codecs(device: &Device, id: String, driver_version: &str) -> VideoCodecs {
+    let capability = device.cuda_compute_capability()
+        .map(|capability| (capability.major, capability.minor))
+        .map_err(|e| debug!("Failed to get CUDA compute capability of {}: {}", id, e))
+        .unwrap_or((0, 0));
+    // Data center cards like the A100 or H100 have no NVENC at all
+    let h264 = device.encoder_capacity(EncoderType::H264).is_ok();
+    let hevc = device.encoder_capacity(EncoderType::HEVC).is_ok();
+    let mut encode = vec![];
+    if h264 {
+        encode.push("H264".to_string());
+    }
+    if hevc {
+        encode.push("HEVC".to_string());
+    }
+    if h264 && capability >= AV1_ENCODER {
+        encode.push("AV1".to_string());
+    }
+    #[cfg(target_os = "linux")]
+    let decode = match device.pci_info() {
+        Ok(pci) => nvcuvid::decoders(&pci.bus_id)
+            .map_err(|e| debug!("Failed to get NVDEC decoders of {}: {}", id, e))
+            .unwrap_or_default(),
+        Err(e) => {
+            debug!("Failed to get PCI bus id of {}: {}", id, e);
+            vec![]
+        }
+    };
+    #[cfg(not(target_os = "linux"))]
+    let decode = vec![];
+    VideoCodecs {
+        encoder_sessions: device.encoder_stats().map(|stats| stats.session_count).unwrap_or(0),
+        max_encoder_sessions: if encode.is_empty() { Some(0) } else { max_encoder_sessions(device.brand().ok(), driver_version) },
+        id,
+        encode,
+        decode,
+    }
+}
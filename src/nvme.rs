@@ -0,0 +1,130 @@
+use anyhow::Result;
+use std::fs;
+use log::debug;
+use crate::model::NvmeDetails;
+use crate::host;
+
+const NVME_CLASS_PATH: &str = "/sys/class/nvme";
+const CRITICAL_WARNINGS: [(u8, &str); 6] = [
+    (1 << 0, "spare_below_threshold"),
+    (1 << 1, "temperature"),
+    (1 << 2, "reliability_degraded"),
+    (1 << 3, "read_only"),
+    (1 << 4, "volatile_memory_backup_failed"),
+    (1 << 5, "persistent_memory_read_only"),
+];
+
+/// First bytes of the SMART / Health log page
+struct SmartLog {
+    critical_warning: u8,
+    available_spare: u8,
+    available_spare_threshold: u8,
+    percentage_used: u8,
+}
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Default)]
+struct NvmeAdminCmd {
+    opcode: u8,
+    flags: u8,
+    rsvd1: u16,
+    nsid: u32,
+    cdw2: u32,
+    cdw3: u32,
+    metadata: u64,
+    addr: u64,
+    metadata_len: u32,
+    data_len: u32,
+    cdw10: u32,
+    cdw11: u32,
+    cdw12: u32,
+    cdw13: u32,
+    cdw14: u32,
+    cdw15: u32,
+    timeout_ms: u32,
+    result: u32,
+}
+
+/// Reads the SMART log page using an admin command. It requires root permissions
+#[cfg(target_os = "linux")]
+fn smart_log(controller: &str) -> Result<SmartLog> {
+    use std::os::unix::io::AsRawFd;
+    // _IOWR('N', 0x41, struct nvme_admin_cmd)
+    const NVME_IOCTL_ADMIN_CMD: u64 = 0xC0484E41;
+    const GET_LOG_PAGE: u8 = 0x02;
+    const SMART_LOG: u32 = 0x02;
+    const LOG_SIZE: usize = 512;
+
+    let device = fs::File::open(host::path(&format!("/dev/{}", controller)))?;
+    let mut log = vec![0u8; LOG_SIZE];
+    let mut command = NvmeAdminCmd {
+        opcode: GET_LOG_PAGE,
+        nsid: 0xFFFFFFFF,
+        addr: log.as_mut_ptr() as u64,
+        data_len: LOG_SIZE as u32,
+        // Number of dwords minus one in the upper half, log id in the lower
+        cdw10: (((LOG_SIZE / 4) as u32 - 1) << 16) | SMART_LOG,
+        ..Default::default()
+    };
+    let result = unsafe { libc::ioctl(device.as_raw_fd(), NVME_IOCTL_ADMIN_CMD as _, &mut command) };
+    if result != 0 {
+        return Err(anyhow::anyhow!("NVMe admin command failed: {}", std::io::Error::last_os_error()));
+    }
+    Ok(SmartLog {
+        critical_warning: log[0],
+        available_spare: log[3],
+        available_spare_threshold: log[4],
+        percentage_used: log[5],
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn smart_log(_controller: &str) -> Result<SmartLog> {
+    Err(anyhow::anyhow!("NVMe SMART log is only available on Linux"))
+}
+
+/// Controller of a namespace block device (nvme0 for nvme0n1)
+fn controller(name: &str) -> Option<String> {
+    let device = fs::canonicalize(host::path(&format!("/sys/block/{}/device", name))).ok()?;
+    let controller = device.file_name()?.to_string_lossy().to_string();
+    if controller.starts_with("nvme") {
+        Some(controller)
+    } else {
+        None
+    }
+}
+
+/// NVMe details of a block device. None if it is not a NVMe device
+pub fn details(name: &str) -> Option<NvmeDetails> {
+    if !name.starts_with("nvme") {
+        return None;
+    }
+    let controller = controller(name)?;
+    let namespaces = fs::read_dir(host::path(NVME_CLASS_PATH).join(&controller))
+        .map(|entries| entries.flatten()
+            .filter(|entry| {
+                // Namespaces are named like nvme0n1 (or nvme0c0n1 with multipath)
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.strip_prefix(&controller).map(|rest| rest.starts_with('n') || rest.starts_with('c')).unwrap_or(false)
+            })
+            .count() as u32)
+        .unwrap_or(0);
+
+    let smart = smart_log(&controller)
+        .map_err(|e| debug!("Cannot read SMART log of {}: {}", controller, e))
+        .ok();
+    Some(NvmeDetails {
+        namespaces,
+        percentage_used: smart.as_ref().map(|s| s.percentage_used),
+        available_spare: smart.as_ref().map(|s| s.available_spare),
+        available_spare_threshold: smart.as_ref().map(|s| s.available_spare_threshold),
+        critical_warnings: smart.as_ref()
+            .map(|s| CRITICAL_WARNINGS.iter()
+                .filter(|(flag, _)| s.critical_warning & flag != 0)
+                .map(|(_, name)| name.to_string())
+                .collect())
+            .unwrap_or_default(),
+        controller,
+    })
+}
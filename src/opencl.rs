@@ -0,0 +1,150 @@
+use anyhow::Result;
+use crate::model::OpenClDevice;
+
+#[cfg(unix)]
+mod icd {
+    use anyhow::Result;
+    use std::ffi::{c_char, c_void};
+    use crate::dl::Library;
+    use crate::model::OpenClDevice;
+
+    type Id = *mut c_void;
+    type GetPlatformIds = unsafe extern "C" fn(u32, *mut Id, *mut u32) -> i32;
+    type GetInfo = unsafe extern "C" fn(Id, u32, usize, *mut c_void, *mut usize) -> i32;
+    type GetDeviceIds = unsafe extern "C" fn(Id, u64, u32, *mut Id, *mut u32) -> i32;
+
+    // Parameters of clGetPlatformInfo and clGetDeviceInfo (cl.h)
+    const PLATFORM_VERSION: u32 = 0x0901;
+    const PLATFORM_NAME: u32 = 0x0902;
+    const PLATFORM_VENDOR: u32 = 0x0903;
+    const DEVICE_TYPE: u32 = 0x1000;
+    const DEVICE_MAX_COMPUTE_UNITS: u32 = 0x1002;
+    const DEVICE_MAX_WORK_GROUP_SIZE: u32 = 0x1004;
+    const DEVICE_MAX_CLOCK_FREQUENCY: u32 = 0x100C;
+    const DEVICE_MAX_MEM_ALLOC_SIZE: u32 = 0x1010;
+    const DEVICE_GLOBAL_MEM_SIZE: u32 = 0x101F;
+    const DEVICE_LOCAL_MEM_SIZE: u32 = 0x1023;
+    const DEVICE_NAME: u32 = 0x102B;
+    const DEVICE_VENDOR: u32 = 0x102C;
+    const DRIVER_VERSION: u32 = 0x102D;
+    const DEVICE_VERSION: u32 = 0x102F;
+    const DEVICE_TYPE_ALL: u64 = 0xFFFFFFFF;
+    const DEVICE_TYPES: [(u64, &str); 4] = [(1 << 1, "CPU"), (1 << 2, "GPU"), (1 << 3, "Accelerator"), (1 << 4, "Custom")];
+
+    #[cfg(target_os = "macos")]
+    const LIBRARY: &str = "/System/Library/Frameworks/OpenCL.framework/OpenCL";
+    #[cfg(not(target_os = "macos"))]
+    const LIBRARY: &str = "libOpenCL.so.1";
+
+    /// The ICD loader and its info functions
+    struct OpenCl {
+        get_platform_ids: GetPlatformIds,
+        get_platform_info: GetInfo,
+        get_device_ids: GetDeviceIds,
+        get_device_info: GetInfo,
+        // Keeps the library loaded while the functions are used
+        _library: Library,
+    }
+
+    impl OpenCl {
+        fn load() -> Result<OpenCl> {
+            let library = Library::open(LIBRARY)?;
+            let missing = |name| anyhow::anyhow!("{} not found in {}", name, LIBRARY);
+            unsafe {
+                Ok(OpenCl {
+                    get_platform_ids: library.function("clGetPlatformIDs").ok_or_else(|| missing("clGetPlatformIDs"))?,
+                    get_platform_info: library.function("clGetPlatformInfo").ok_or_else(|| missing("clGetPlatformInfo"))?,
+                    get_device_ids: library.function("clGetDeviceIDs").ok_or_else(|| missing("clGetDeviceIDs"))?,
+                    get_device_info: library.function("clGetDeviceInfo").ok_or_else(|| missing("clGetDeviceInfo"))?,
+                    _library: library,
+                })
+            }
+        }
+
+        /// Ids listed by a clGet*IDs function, called first for the count
+        fn ids(list: impl Fn(u32, *mut Id, *mut u32) -> i32) -> Vec<Id> {
+            let mut count = 0;
+            if list(0, std::ptr::null_mut(), &mut count) != 0 || count == 0 {
+                return vec![];
+            }
+            let mut ids = vec![std::ptr::null_mut(); count as usize];
+            if list(count, ids.as_mut_ptr(), &mut count) != 0 {
+                return vec![];
+            }
+            ids.truncate(count as usize);
+            ids
+        }
+
+        fn text(get_info: GetInfo, id: Id, param: u32) -> String {
+            let mut size = 0;
+            unsafe {
+                if get_info(id, param, 0, std::ptr::null_mut(), &mut size) != 0 || size == 0 {
+                    return String::new();
+                }
+                let mut value = vec![0 as c_char; size];
+                if get_info(id, param, size, value.as_mut_ptr() as *mut c_void, std::ptr::null_mut()) != 0 {
+                    return String::new();
+                }
+                std::ffi::CStr::from_ptr(value.as_ptr()).to_string_lossy().trim().to_string()
+            }
+        }
+
+        /// Numeric device info of the size of `T`, like cl_uint, cl_ulong or size_t
+        fn number<T: Default>(&self, device: Id, param: u32) -> T {
+            let mut value = T::default();
+            let result = unsafe {
+                (self.get_device_info)(device, param, std::mem::size_of::<T>(), &mut value as *mut T as *mut c_void, std::ptr::null_mut())
+            };
+            if result == 0 { value } else { T::default() }
+        }
+
+        fn devices(&self) -> Vec<OpenClDevice> {
+            let mut devices = vec![];
+            let platforms = OpenCl::ids(|count, ids, total| unsafe { (self.get_platform_ids)(count, ids, total) });
+            for platform in platforms {
+                let platform_name = OpenCl::text(self.get_platform_info, platform, PLATFORM_NAME);
+                let platform_vendor = OpenCl::text(self.get_platform_info, platform, PLATFORM_VENDOR);
+                let platform_version = OpenCl::text(self.get_platform_info, platform, PLATFORM_VERSION);
+                let ids = OpenCl::ids(|count, ids, total| unsafe { (self.get_device_ids)(platform, DEVICE_TYPE_ALL, count, ids, total) });
+                for device in ids {
+                    let device_type = self.number::<u64>(device, DEVICE_TYPE);
+                    devices.push(OpenClDevice {
+                        platform: platform_name.clone(),
+                        platform_vendor: platform_vendor.clone(),
+                        platform_version: platform_version.clone(),
+                        name: OpenCl::text(self.get_device_info, device, DEVICE_NAME),
+                        vendor: OpenCl::text(self.get_device_info, device, DEVICE_VENDOR),
+                        device_type: DEVICE_TYPES.iter()
+                            .find(|(bit, _)| device_type & bit != 0)
+                            .map(|(_, name)| name.to_string())
+                            .unwrap_or_else(|| "Unknown".to_string()),
+                        version: OpenCl::text(self.get_device_info, device, DEVICE_VERSION),
+                        driver_version: OpenCl::text(self.get_device_info, device, DRIVER_VERSION),
+                        compute_units: self.number::<u32>(device, DEVICE_MAX_COMPUTE_UNITS),
+                        max_work_group_size: self.number::<usize>(device, DEVICE_MAX_WORK_GROUP_SIZE) as u64,
+                        max_clock_frequency: self.number::<u32>(device, DEVICE_MAX_CLOCK_FREQUENCY),
+                        global_memory: self.number::<u64>(device, DEVICE_GLOBAL_MEM_SIZE),
+                        local_memory: self.number::<u64>(device, DEVICE_LOCAL_MEM_SIZE),
+                        max_allocation: self.number::<u64>(device, DEVICE_MAX_MEM_ALLOC_SIZE),
+                    });
+                }
+            }
+            devices
+        }
+    }
+
+    pub fn devices() -> Result<Vec<OpenClDevice>> {
+        Ok(OpenCl::load()?.devices())
+    }
+}
+
+/// Devices of all the OpenCL platforms, through the ICD loader loaded at runtime
+#[cfg(unix)]
+pub fn opencl_devices() -> Result<Vec<OpenClDevice>> {
+    icd::devices()
+}
+
+#[cfg(not(unix))]
+pub fn opencl_devices() -> Result<Vec<OpenClDevice>> {
+    Err(anyhow::anyhow!("OpenCL discovery is only supported on Unix"))
+}
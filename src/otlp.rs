@@ -0,0 +1,96 @@
+//! OpenTelemetry metrics. Enable it with the `otlp` feature.
+//!
+//! The readings are registered as observable gauges of an `opentelemetry` meter, so the meter provider of the
+//! application collects them along with its own metrics and sends them with its exporter (OTLP, Prometheus,
+//! stdout...). The metric names follow the semantic conventions (`system.cpu.utilization`, `system.memory.usage`,
+//! `system.filesystem.usage`, `hw.gpu.utilization`...)
+//!
+//! Example
+//! ```no_run
+//! use machine_info::Machine;
+//! use machine_info::otlp;
+//! use std::sync::Arc;
+//!
+//! // Set up the meter provider with an OTLP exporter first, the global one does nothing by default
+//! let meter = opentelemetry::global::meter("agent");
+//! otlp::register(&meter, Arc::new(Machine::new()));
+//! ```
+use log::debug;
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::Meter;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::Machine;
+use crate::model::{Snapshot, GraphicsUsage};
+
+/// The gauges are observed one after another in a collection, they share the snapshot taken by the first one
+const SNAPSHOT_MAX_AGE: Duration = Duration::from_secs(1);
+
+/// Value and attributes of the data points of a gauge
+type Points = Vec<(f64, Vec<KeyValue>)>;
+
+/// Snapshot of the machine shared by the gauge callbacks
+struct Readings {
+    machine: Arc<Machine>,
+    last: Mutex<Option<(Instant, Snapshot)>>,
+}
+
+impl Readings {
+    fn points(&self, points: fn(&Snapshot) -> Points) -> Points {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        if !matches!(&*last, Some((when, _)) if when.elapsed() < SNAPSHOT_MAX_AGE) {
+            *last = match self.machine.snapshot() {
+                Ok(snapshot) => Some((Instant::now(), snapshot)),
+                Err(err) => {
+                    debug!("Cannot take a snapshot for the OpenTelemetry gauges: {:?}", err);
+                    None
+                }
+            };
+        }
+        last.as_ref().map(|(_, snapshot)| points(snapshot)).unwrap_or_default()
+    }
+}
+
+/// One data point per graphic card. Cards where the field could not be read are skipped
+fn per_card(snapshot: &Snapshot, field: &str, value: fn(&GraphicsUsage) -> f64) -> Points {
+    snapshot.graphics.iter()
+        .filter(|card| card.is_read(field))
+        .map(|card| (value(card), vec![KeyValue::new("hw.id", card.id.clone())]))
+        .collect()
+}
+
+/// Registers the gauges of the machine readings in the meter. The machine is read when the meter provider collects
+/// the metrics, once for all the gauges
+pub fn register(meter: &Meter, machine: Arc<Machine>) {
+    let readings = Arc::new(Readings { machine, last: Mutex::new(None) });
+    let gauge = |name: &'static str, unit: &'static str, points: fn(&Snapshot) -> Points| {
+        let readings = readings.clone();
+        meter.f64_observable_gauge(name)
+            .with_unit(unit)
+            .with_callback(move |observer| {
+                for (value, attributes) in readings.points(points) {
+                    observer.observe(value, &attributes);
+                }
+            })
+            .build();
+    };
+    gauge("system.cpu.utilization", "1", |snapshot| vec![(snapshot.status.cpu as f64 / 100.0, vec![])]);
+    gauge("system.memory.usage", "By", |snapshot| {
+        let memory = &snapshot.status.memory_breakdown;
+        [("used", memory.used), ("free", memory.free), ("cached", memory.cached), ("buffers", memory.buffers)]
+            .into_iter()
+            .map(|(state, bytes)| (bytes as f64, vec![KeyValue::new("system.memory.state", state)]))
+            .collect()
+    });
+    gauge("system.filesystem.usage", "By", |snapshot| snapshot.info.disks.iter()
+        .flat_map(|disk| [("used", disk.size.saturating_sub(disk.available)), ("free", disk.available)].into_iter()
+            .map(|(state, bytes)| (bytes as f64, vec![
+                KeyValue::new("system.device", disk.name.clone()),
+                KeyValue::new("system.filesystem.mountpoint", disk.mount_point.clone()),
+                KeyValue::new("system.filesystem.state", state),
+            ])))
+        .collect());
+    gauge("hw.gpu.utilization", "1", |snapshot| per_card(snapshot, "utilization", |card| card.gpu as f64 / 100.0));
+    gauge("hw.gpu.memory.usage", "By", |snapshot| per_card(snapshot, "memory", |card| card.memory_used as f64));
+    gauge("hw.gpu.temperature", "Cel", |snapshot| per_card(snapshot, "temperature", |card| card.temperature as f64));
+}
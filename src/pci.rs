@@ -0,0 +1,100 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use crate::model::{PciDevice, PassthroughStatus};
+use crate::sensors::read_text;
+use crate::host;
+
+const PCI_DEVICES_PATH: &str = "/sys/bus/pci/devices";
+const IOMMU_GROUPS_PATH: &str = "/sys/kernel/iommu_groups";
+const VFIO_DRIVER_PATH: &str = "/sys/bus/pci/drivers/vfio-pci";
+
+/// PCI base class names (PCI Code and ID Assignment Specification)
+const CLASSES: [&str; 20] = [
+    "Unclassified device", "Mass storage controller", "Network controller", "Display controller",
+    "Multimedia controller", "Memory controller", "Bridge", "Communication controller",
+    "Generic system peripheral", "Input device controller", "Docking station", "Processor",
+    "Serial bus controller", "Wireless controller", "Intelligent controller", "Satellite communications controller",
+    "Encryption controller", "Signal processing controller", "Processing accelerators", "Non-Essential Instrumentation",
+];
+
+/// Reads a sysfs hexadecimal value like 0x10de
+fn read_hex(path: &Path) -> Option<u32> {
+    let value = read_text(path)?;
+    u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+fn class_name(class: u32) -> String {
+    match class >> 16 {
+        0x40 => "Coprocessor".to_string(),
+        base => CLASSES.get(base as usize).unwrap_or(&"Unassigned class").to_string()
+    }
+}
+
+/// PCI address as the kernel writes it, like 0000:65:00.0. NVML uses a domain of 8 digits in upper case
+/// (00000000:65:00.0) and some tools omit the domain (65:00.0)
+pub fn normalize_address(address: &str) -> String {
+    let address = address.trim().to_lowercase();
+    match address.split_once(':') {
+        Some((domain, rest)) if rest.contains(':') => match u32::from_str_radix(domain, 16) {
+            Ok(domain) => format!("{:04x}:{}", domain, rest),
+            Err(_) => address
+        },
+        _ => format!("0000:{}", address)
+    }
+}
+
+/// Devices of the PCI bus, sorted by address
+pub fn pci_devices() -> Result<Vec<PciDevice>> {
+    let mut devices = vec![];
+    for entry in fs::read_dir(host::path(PCI_DEVICES_PATH))?.flatten() {
+        let dir = entry.path();
+        let (vendor_id, device_id) = match (read_hex(&dir.join("vendor")), read_hex(&dir.join("device"))) {
+            (Some(vendor), Some(device)) => (vendor as u16, device as u16),
+            _ => continue
+        };
+        let class = read_hex(&dir.join("class")).unwrap_or(0);
+        let number = |name: &str| read_text(&dir.join(name)).and_then(|value| value.parse::<u32>().ok());
+        // Links named after their target, like iommu_group -> ../../kernel/iommu_groups/12
+        let link_name = |name: &str| fs::read_link(dir.join(name)).ok()
+            .and_then(|target| target.file_name().map(|name| name.to_string_lossy().to_string()));
+        devices.push(PciDevice {
+            address: entry.file_name().to_string_lossy().to_string(),
+            vendor_id,
+            device_id,
+            subsystem_vendor_id: read_hex(&dir.join("subsystem_vendor")).map(|id| id as u16),
+            subsystem_device_id: read_hex(&dir.join("subsystem_device")).map(|id| id as u16),
+            class,
+            class_name: class_name(class),
+            // The driver is a link to /sys/bus/pci/drivers/<name>
+            driver: link_name("driver"),
+            iommu_group: link_name("iommu_group").and_then(|group| group.parse().ok()),
+            sriov_total_vfs: number("sriov_totalvfs"),
+            sriov_num_vfs: number("sriov_numvfs"),
+            physical_function: link_name("physfn"),
+        });
+    }
+    devices.sort_by(|a, b| a.address.cmp(&b.address));
+    Ok(devices)
+}
+
+/// IOMMU and VFIO state with the devices ready for passthrough
+pub fn passthrough_status() -> Result<PassthroughStatus> {
+    let devices = pci_devices()?;
+    let iommu_groups = fs::read_dir(host::path(IOMMU_GROUPS_PATH))
+        .map(|groups| groups.count() as u32)
+        .unwrap_or(0);
+    Ok(PassthroughStatus {
+        iommu_enabled: iommu_groups > 0,
+        iommu_groups,
+        vfio_loaded: host::path(VFIO_DRIVER_PATH).exists(),
+        vfio_devices: devices.iter()
+            .filter(|device| device.driver.as_deref() == Some("vfio-pci"))
+            .map(|device| device.address.clone())
+            .collect(),
+        sriov_devices: devices.iter()
+            .filter(|device| device.sriov_total_vfs.unwrap_or(0) > 0)
+            .map(|device| device.address.clone())
+            .collect(),
+    })
+}
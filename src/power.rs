@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+use log::debug;
+use crate::memory::read_number;
+use crate::model::{Battery, PowerAdapter, PowerSupplyStatus, PowerDomain};
+use crate::sensors::{class_entries, read_text};
+
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+const POWERCAP_PATH: &str = "/sys/class/powercap";
+
+/// Value of a power supply attribute. Energy is in µWh, charge in µAh, power in µW and current in µA
+fn attribute(dir: &Path, name: &str) -> Option<u64> {
+    read_number(&dir.join(name)).ok()
+}
+
+/// Batteries of the machine. Batteries of peripherals (mouse, keyboard...) are not included
+pub fn batteries() -> Vec<Battery> {
+    supplies_of_type("Battery").into_iter()
+        .map(|(name, dir)| battery(name, &dir))
+        .collect()
+}
+
+/// AC adapters, USB power and UPS, to know if the machine is running on battery
+pub fn power_supply() -> PowerSupplyStatus {
+    let adapters = supplies_of_type("Mains").into_iter()
+        .chain(supplies_of_type("USB"))
+        .chain(supplies_of_type("USB_PD"))
+        .chain(supplies_of_type("USB_C"))
+        .map(|(name, dir)| PowerAdapter {
+            kind: read_text(&dir.join("type")).unwrap_or_else(|| "Unknown".to_string()),
+            online: attribute(&dir, "online").map(|online| online > 0).unwrap_or(false),
+            power: adapter_power(&dir),
+            name,
+        })
+        .collect::<Vec<PowerAdapter>>();
+    let ups = supplies_of_type("UPS").into_iter()
+        .map(|(name, dir)| battery(name, &dir))
+        .collect::<Vec<Battery>>();
+    let discharging = batteries().iter().chain(ups.iter()).any(|battery| battery.status == "Discharging");
+    PowerSupplyStatus {
+        on_battery: discharging && !adapters.iter().any(|adapter| adapter.online),
+        adapters,
+        ups,
+    }
+}
+
+/// Power supplies of the given type. Devices with scope Device (peripherals) are skipped
+fn supplies_of_type(kind: &str) -> Vec<(String, PathBuf)> {
+    let supplies = match class_entries(POWER_SUPPLY_PATH, "") {
+        Ok(supplies) => supplies,
+        Err(err) => {
+            debug!("Cannot list power supplies: {:?}", err);
+            return vec![];
+        }
+    };
+    supplies.into_iter()
+        .filter(|(_, dir)| read_text(&dir.join("type")).as_deref() == Some(kind)
+            && read_text(&dir.join("scope")).as_deref() != Some("Device"))
+        .collect()
+}
+
+/// Maximum power of the adapter in watts. USB PD reports the negotiated voltage and current
+fn adapter_power(dir: &Path) -> Option<f64> {
+    if let Some(power) = attribute(dir, "power_max").or_else(|| attribute(dir, "power_now")) {
+        return Some(power as f64 / 1_000_000.0);
+    }
+    let voltage = attribute(dir, "voltage_max").or_else(|| attribute(dir, "voltage_now"))?;
+    let current = attribute(dir, "current_max").or_else(|| attribute(dir, "current_now"))?;
+    Some(voltage as f64 / 1_000_000.0 * current as f64 / 1_000_000.0)
+}
+
+/// Battery (or UPS) values of a power supply
+fn battery(name: String, dir: &Path) -> Battery {
+    let status = read_text(&dir.join("status")).unwrap_or_else(|| "Unknown".to_string());
+    // Some drivers report energy (µWh and µW) and others charge (µAh and µA)
+    let (now, full, design, rate) = match attribute(dir, "energy_now") {
+        Some(energy) => (Some(energy), attribute(dir, "energy_full"), attribute(dir, "energy_full_design"), attribute(dir, "power_now")),
+        None => (attribute(dir, "charge_now"), attribute(dir, "charge_full"), attribute(dir, "charge_full_design"), attribute(dir, "current_now"))
+    };
+    let rate = rate.filter(|rate| *rate > 0);
+    // The amount over the rate is in hours
+    let seconds = |amount: u64, rate: u64| amount as f64 / rate as f64 * 3600.0;
+    let time_to_empty = attribute(dir, "time_to_empty_now").or_else(|| match (status.as_str(), now, rate) {
+        ("Discharging", Some(now), Some(rate)) => Some(seconds(now, rate) as u64),
+        _ => None
+    });
+    let time_to_full = attribute(dir, "time_to_full_now").or_else(|| match (status.as_str(), now, full, rate) {
+        ("Charging", Some(now), Some(full), Some(rate)) => Some(seconds(full.saturating_sub(now), rate) as u64),
+        _ => None
+    });
+    Battery {
+        name,
+        model: read_text(&dir.join("model_name")),
+        capacity: attribute(dir, "capacity").map(|capacity| capacity.min(100) as u8)
+            .or_else(|| now.zip(full).filter(|(_, full)| *full > 0).map(|(now, full)| (now * 100 / full).min(100) as u8)),
+        status,
+        time_to_empty,
+        time_to_full,
+        cycle_count: attribute(dir, "cycle_count").filter(|cycles| *cycles > 0),
+        health: full.zip(design).filter(|(_, design)| *design > 0)
+            .map(|(full, design)| full as f64 * 100.0 / design as f64),
+    }
+}
+
+/// Energy counter of a RAPL zone (package, core, uncore, dram...)
+#[derive(Debug)]
+pub struct RaplZone {
+    pub zone: String,
+    pub name: String,
+    /// Energy in µJ since an unknown point. It wraps at `max_energy`
+    pub energy: u64,
+    pub max_energy: u64,
+}
+
+/// RAPL zones from the powercap interface. Intel and AMD CPUs expose them as intel-rapl:N (packages) and
+/// intel-rapl:N:M (subzones). Reading the counters usually requires root
+pub fn rapl_zones() -> Vec<RaplZone> {
+    let zones = match class_entries(POWERCAP_PATH, "intel-rapl:") {
+        Ok(zones) => zones,
+        Err(err) => {
+            debug!("Cannot list powercap zones: {:?}", err);
+            return vec![];
+        }
+    };
+    zones.into_iter()
+        .filter_map(|(zone, dir)| {
+            let energy = match attribute(&dir, "energy_uj") {
+                Some(energy) => energy,
+                None => {
+                    debug!("Cannot read energy of RAPL zone {}", zone);
+                    return None;
+                }
+            };
+            Some(RaplZone {
+                name: read_text(&dir.join("name")).unwrap_or_else(|| zone.clone()),
+                max_energy: attribute(&dir, "max_energy_range_uj").unwrap_or(u64::MAX),
+                zone,
+                energy,
+            })
+        })
+        .collect()
+}
+
+impl RaplZone {
+    /// Power in watts since the last sample
+    pub fn usage(&self, last: Option<&RaplZone>, elapsed: f64) -> PowerDomain {
+        let consumed = match last {
+            // The counter wraps around at the maximum range
+            Some(last) if self.energy < last.energy => self.max_energy.saturating_sub(last.energy) + self.energy,
+            Some(last) => self.energy - last.energy,
+            None => 0
+        };
+        PowerDomain {
+            zone: self.zone.clone(),
+            name: self.name.clone(),
+            energy: self.energy,
+            power: if elapsed > 0.0 { consumed as f64 / 1_000_000.0 / elapsed } else { 0.0 },
+        }
+    }
+}
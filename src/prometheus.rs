@@ -0,0 +1,144 @@
+//! Prometheus text exposition format of the collected metrics. Enable it with the `prometheus` feature.
+//!
+//! Example
+//! ```
+//! use machine_info::Machine;
+//! use machine_info::prometheus;
+//!
+//! let m = Machine::new();
+//! let snapshot = m.snapshot().unwrap();
+//! println!("{}", prometheus::encode(&snapshot));
+//! ```
+use anyhow::Result;
+use std::fmt::Write;
+use crate::Machine;
+use crate::model::{Snapshot, GraphicsUsage, Process};
+
+const PREFIX: &str = "machine";
+
+/// Name, help and value of a metric family with one sample per item (card, process...)
+type Metric<T> = (&'static str, &'static str, fn(&T) -> Option<f64>);
+
+/// Text exposition writer. All the samples of a metric family must be written right after its header
+struct Exposition(String);
+
+impl Exposition {
+    fn family(&mut self, name: &str, kind: &str, help: &str) -> &mut Exposition {
+        let _ = writeln!(self.0, "# HELP {}_{} {}", PREFIX, name, help);
+        let _ = writeln!(self.0, "# TYPE {}_{} {}", PREFIX, name, kind);
+        self
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) -> &mut Exposition {
+        let _ = write!(self.0, "{}_{}", PREFIX, name);
+        if !labels.is_empty() {
+            let labels = labels.iter()
+                .map(|(label, value)| format!("{}=\"{}\"", label, escape(value)))
+                .collect::<Vec<String>>();
+            let _ = write!(self.0, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.0, " {}", value);
+        self
+    }
+
+    /// Family with a single sample without labels
+    fn gauge(&mut self, name: &str, help: &str, value: f64) -> &mut Exposition {
+        self.family(name, "gauge", help).sample(name, &[], value)
+    }
+}
+
+/// Label values escape backslashes, double quotes and line feeds
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Encodes the metrics of a snapshot: global CPU and memory, disks, graphic cards and tracked processes
+pub fn encode(snapshot: &Snapshot) -> String {
+    let mut out = Exposition(String::new());
+    let status = &snapshot.status;
+    let memory = &status.memory_breakdown;
+    out.gauge("cpu_usage_percent", "CPU used as percentage", status.cpu as f64)
+        .gauge("memory_total_bytes", "Total memory", memory.total as f64)
+        .gauge("memory_used_bytes", "Memory used by applications", memory.used as f64)
+        .gauge("memory_available_bytes", "Memory available for new applications", memory.available as f64)
+        .gauge("memory_cached_bytes", "Page cache", memory.cached as f64)
+        .gauge("memory_buffers_bytes", "Block device buffers", memory.buffers as f64)
+        .gauge("uptime_seconds", "Seconds since the system booted", status.uptime as f64)
+        .gauge("degraded", "1 if the sampling is throttled because of high load", status.degraded as u8 as f64);
+
+    let disks = &snapshot.info.disks;
+    out.family("disk_size_bytes", "gauge", "Size of the filesystem");
+    for disk in disks {
+        out.sample("disk_size_bytes", &[("name", &disk.name), ("mount_point", &disk.mount_point)], disk.size as f64);
+    }
+    out.family("disk_available_bytes", "gauge", "Space available in the filesystem");
+    for disk in disks {
+        out.sample("disk_available_bytes", &[("name", &disk.name), ("mount_point", &disk.mount_point)], disk.available as f64);
+    }
+
+    let gpu_metrics: [Metric<GraphicsUsage>; 6] = [
+        ("gpu_usage_percent", "GPU utilization as percentage", |card| card.is_read("utilization").then_some(card.gpu as f64)),
+        ("gpu_memory_usage_percent", "GPU memory controller utilization as percentage",
+            |card| card.is_read("utilization").then_some(card.memory_usage as f64)),
+        ("gpu_memory_used_bytes", "GPU memory used", |card| card.is_read("memory").then_some(card.memory_used as f64)),
+        ("gpu_encoder_usage_percent", "GPU encoder utilization as percentage", |card| card.is_read("encoder").then_some(card.encoder as f64)),
+        ("gpu_decoder_usage_percent", "GPU decoder utilization as percentage", |card| card.is_read("decoder").then_some(card.decoder as f64)),
+        ("gpu_temperature_celsius", "GPU temperature", |card| card.is_read("temperature").then_some(card.temperature as f64)),
+    ];
+    for (name, help, value) in gpu_metrics {
+        out.family(name, "gauge", help);
+        for card in &snapshot.graphics {
+            if let Some(value) = value(card) {
+                out.sample(name, &[("id", &card.id)], value);
+            }
+        }
+    }
+
+    let process_metrics: [Metric<Process>; 6] = [
+        ("process_cpu_percent", "CPU used by the process as percentage of one core", |process| Some(process.cpu)),
+        ("process_memory_bytes", "Resident memory of the process", |process| Some(process.memory as f64)),
+        ("process_virtual_memory_bytes", "Virtual memory of the process", |process| Some(process.virtual_memory as f64)),
+        ("process_read_bytes_per_second", "Bytes read from storage per second", |process| process.read_bytes_per_second),
+        ("process_write_bytes_per_second", "Bytes written to storage per second", |process| process.write_bytes_per_second),
+        ("process_open_files", "Open file descriptors", |process| process.open_files.map(|files| files as f64)),
+    ];
+    // A PID can be reported more than once (tracked alone, as a child of a tracked process or as the first member
+    // of a group), so the group and the parent are labels too
+    let process_labels: Vec<Vec<(&str, String)>> = snapshot.processes.iter()
+        .map(|process| {
+            let mut labels = vec![("pid", process.pid.to_string()), ("name", process.name.clone())];
+            if let Some(group) = &process.group {
+                labels.push(("group", group.clone()));
+            }
+            if let Some(parent) = process.parent {
+                labels.push(("parent", parent.to_string()));
+            }
+            labels
+        })
+        .collect();
+    for (name, help, value) in process_metrics {
+        out.family(name, "gauge", help);
+        for (process, labels) in snapshot.processes.iter().zip(&process_labels) {
+            if let Some(value) = value(process) {
+                let labels: Vec<(&str, &str)> = labels.iter().map(|(label, value)| (*label, value.as_str())).collect();
+                out.sample(name, &labels, value);
+            }
+        }
+    }
+    out.0
+}
+
+impl Machine {
+    /// Takes a snapshot and encodes it in the Prometheus text exposition format, ready to be served on /metrics
+    /// Example
+    /// ```
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// m.track_process(std::process::id() as i32).unwrap();
+    /// let metrics = m.prometheus_metrics().unwrap();
+    /// assert!(metrics.contains("machine_process_cpu_percent{pid="));
+    /// ```
+    pub fn prometheus_metrics(&self) -> Result<String> {
+        Ok(encode(&self.snapshot()?))
+    }
+}
@@ -0,0 +1,142 @@
+//! Python bindings. Enable it with the `python` feature and build the extension module with
+//! [maturin](https://www.maturin.rs), the `pyproject.toml` of the repository already selects the features:
+//!
+//! ```text
+//! maturin develop --release
+//! ```
+//!
+//! The readings are returned as dicts and lists with the same keys as the serde serialization of the models.
+//! The GIL is released while sampling, so other Python threads keep running.
+//!
+//! Example
+//! ```python
+//! import machine_info
+//! import os
+//!
+//! m = machine_info.Machine()
+//! print(m.system_info()["processor"]["brand"])
+//! m.track_process(os.getpid())
+//! for card in m.graphics_status():
+//!     print(card["id"], card["gpu"], card["memoryUsage"])
+//! ```
+use pyo3::prelude::*;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyList, PyString};
+use serde::Serialize;
+use serde_json::Value;
+use crate::Machine as RustMachine;
+
+fn error(e: impl std::fmt::Display) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+fn to_python<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(value) => PyBool::new(py, *value).to_owned().into_any(),
+        Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(value), _) => value.into_pyobject(py)?.into_any(),
+            (None, Some(value)) => value.into_pyobject(py)?.into_any(),
+            _ => PyFloat::new(py, number.as_f64().unwrap_or_default()).into_any()
+        },
+        Value::String(value) => PyString::new(py, value).into_any(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(to_python(py, item)?)?;
+            }
+            list.into_any()
+        },
+        Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, value) in fields {
+                dict.set_item(key, to_python(py, value)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+/// Model as Python dicts and lists
+fn convert<'py, T: Serialize>(py: Python<'py>, value: &T) -> PyResult<Bound<'py, PyAny>> {
+    to_python(py, &serde_json::to_value(value).map_err(error)?)
+}
+
+/// `Machine` for Python
+#[pyclass(name = "Machine", frozen)]
+pub struct Machine(RustMachine);
+
+#[pymethods]
+impl Machine {
+    #[new]
+    fn new(py: Python<'_>) -> Machine {
+        Machine(py.allow_threads(RustMachine::new))
+    }
+
+    fn system_info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        convert(py, &py.allow_threads(|| self.0.system_info()))
+    }
+
+    fn system_status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        convert(py, &py.allow_threads(|| self.0.system_status()).map_err(error)?)
+    }
+
+    fn graphics_status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        convert(py, &py.allow_threads(|| self.0.graphics_status()))
+    }
+
+    fn processes_status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        convert(py, &py.allow_threads(|| self.0.processes_status()))
+    }
+
+    fn snapshot<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        convert(py, &py.allow_threads(|| self.0.snapshot()).map_err(error)?)
+    }
+
+    fn thermal_status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        convert(py, &py.allow_threads(|| self.0.thermal_status()))
+    }
+
+    fn disks_status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        convert(py, &py.allow_threads(|| self.0.disks_status()).map_err(error)?)
+    }
+
+    fn network_status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        convert(py, &py.allow_threads(|| self.0.network_status()).map_err(error)?)
+    }
+
+    fn track_process(&self, pid: i32) -> PyResult<()> {
+        self.0.track_process(pid).map_err(error)
+    }
+
+    fn untrack_process(&self, pid: i32) {
+        self.0.untrack_process(pid)
+    }
+
+    fn track_group(&self, name: &str, pids: Vec<i32>) -> PyResult<()> {
+        self.0.track_group(name, &pids).map_err(error)
+    }
+
+    fn untrack_group(&self, name: &str) {
+        self.0.untrack_group(name)
+    }
+
+    fn track_cgroup(&self, path: &str) -> PyResult<()> {
+        self.0.track_cgroup(path).map_err(error)
+    }
+
+    fn untrack_cgroup(&self, path: &str) {
+        self.0.untrack_cgroup(path)
+    }
+
+    fn cgroups_status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        convert(py, &py.allow_threads(|| self.0.cgroups_status()))
+    }
+}
+
+/// Python module `machine_info`
+#[pymodule]
+fn machine_info(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Machine>()?;
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::fs;
+use crate::model::Quota;
+#[cfg(target_os = "linux")]
+use crate::model::QuotaKind;
+#[cfg(target_os = "linux")]
+use crate::{host, storage};
+
+/// Names by id of a colon separated database like /etc/passwd (name:x:id:...) or /etc/projid (name:id)
+#[cfg(target_os = "linux")]
+fn names(path: &str, id_field: usize) -> HashMap<u32, String> {
+    fs::read_to_string(host::path(path)).unwrap_or_default()
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            Some((fields.get(id_field)?.parse().ok()?, fields.first()?.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod quotactl {
+    use anyhow::{anyhow, Result};
+    use log::debug;
+    use std::ffi::CString;
+    use crate::model::QuotaKind;
+
+    const Q_GETNEXTQUOTA: i32 = 0x800009;
+    /// Block limits are in units of 1 KiB
+    pub const QIF_DQBLKSIZE: u64 = 1024;
+
+    /// struct if_nextdqblk
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct NextDqblk {
+        pub bhardlimit: u64,
+        pub bsoftlimit: u64,
+        pub curspace: u64,
+        pub ihardlimit: u64,
+        pub isoftlimit: u64,
+        pub curinodes: u64,
+        pub btime: u64,
+        pub itime: u64,
+        pub valid: u32,
+        pub id: u32,
+    }
+
+    /// Quotas of all the ids of a kind with Q_GETNEXTQUOTA (Linux 4.6), which works for both the VFS quotas (ext4)
+    /// and XFS. Empty if that kind of quota is not enabled
+    pub fn quotas(device: &str, kind: QuotaKind) -> Result<Vec<NextDqblk>> {
+        let quota_type = match kind {
+            QuotaKind::User => 0,
+            QuotaKind::Group => 1,
+            QuotaKind::Project => 2,
+        };
+        let device = CString::new(device)?;
+        let mut quotas = vec![];
+        let mut id: u32 = 0;
+        loop {
+            let mut quota = NextDqblk::default();
+            let result = unsafe {
+                libc::quotactl((Q_GETNEXTQUOTA << 8) | quota_type, device.as_ptr(), id as i32,
+                    &mut quota as *mut NextDqblk as *mut libc::c_char)
+            };
+            if result < 0 {
+                let error = std::io::Error::last_os_error();
+                return match error.raw_os_error() {
+                    // No more ids
+                    Some(libc::ENOENT) => Ok(quotas),
+                    Some(libc::EPERM) | Some(libc::EACCES) =>
+                        Err(anyhow!("Reading the quotas of other users requires root permissions")),
+                    // Quotas not enabled or not supported by the filesystem
+                    _ if quotas.is_empty() => {
+                        debug!("No {:?} quotas in {:?}: {}", kind, device, error);
+                        Ok(quotas)
+                    },
+                    _ => Err(error.into()),
+                };
+            }
+            let next = quota.id.checked_add(1);
+            quotas.push(quota);
+            match next {
+                Some(next) => id = next,
+                None => return Ok(quotas),
+            }
+        }
+    }
+}
+
+/// Quotas of the filesystem mounted at the given mount point
+#[cfg(target_os = "linux")]
+pub fn quotas(mount_point: &str) -> Result<Vec<Quota>> {
+    let mounts = storage::mounts();
+    let mount = storage::mount(&mounts, mount_point)
+        .ok_or_else(|| anyhow::anyhow!("Nothing mounted at {}", mount_point))?;
+    let device = host::path(&mount.device).to_string_lossy().to_string();
+    // Zero means no limit
+    let limit = |value: u64| Some(value).filter(|value| *value > 0);
+    let mut result = vec![];
+    for (kind, names) in [
+        (QuotaKind::User, names("/etc/passwd", 2)),
+        (QuotaKind::Group, names("/etc/group", 2)),
+        (QuotaKind::Project, names("/etc/projid", 1)),
+    ] {
+        for quota in quotactl::quotas(&device, kind)? {
+            result.push(Quota {
+                kind,
+                id: quota.id,
+                name: names.get(&quota.id).cloned(),
+                used: quota.curspace,
+                soft_limit: limit(quota.bsoftlimit * quotactl::QIF_DQBLKSIZE),
+                hard_limit: limit(quota.bhardlimit * quotactl::QIF_DQBLKSIZE),
+                files: quota.curinodes,
+                files_soft_limit: limit(quota.isoftlimit),
+                files_hard_limit: limit(quota.ihardlimit),
+            });
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn quotas(_mount_point: &str) -> Result<Vec<Quota>> {
+    Err(anyhow::anyhow!("Quotas are only supported on Linux"))
+}
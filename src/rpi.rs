@@ -0,0 +1,126 @@
+//! Raspberry Pi firmware telemetry. Enable it with the `rpi` feature.
+//!
+//! The values are queried to the VideoCore firmware through the mailbox interface (`/dev/vcio`), like `vcgencmd`
+//! does. When the mailbox cannot be opened, the temperature and throttling flags are read from sysfs
+use anyhow::Result;
+use log::debug;
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use crate::Machine;
+use crate::model::{RpiStatus, RpiThrottling};
+use crate::sensors::read_text;
+use crate::host;
+
+const MAILBOX_PATH: &str = "/dev/vcio";
+const MODEL_PATH: &str = "/sys/firmware/devicetree/base/model";
+const THROTTLED_PATH: &str = "/sys/devices/platform/soc/soc:firmware/get_throttled";
+const SOC_THERMAL_PATH: &str = "/sys/class/thermal/thermal_zone0/temp";
+
+// Property tags of the firmware mailbox
+const TAG_GET_VC_MEMORY: u32 = 0x0001_0006;
+const TAG_GET_VOLTAGE: u32 = 0x0003_0003;
+const TAG_GET_TEMPERATURE: u32 = 0x0003_0006;
+const TAG_GET_THROTTLED: u32 = 0x0003_0046;
+const VOLTAGE_CORE: u32 = 1;
+const RESPONSE_SUCCESS: u32 = 0x8000_0000;
+
+/// Message buffer of the mailbox. The firmware requires it to be 16 bytes aligned
+#[repr(C, align(16))]
+struct Message([u32; 8]);
+
+/// Opened firmware mailbox
+struct Mailbox(File);
+
+impl Mailbox {
+    fn open() -> Result<Mailbox> {
+        Ok(Mailbox(File::options().read(true).write(true).open(host::path(MAILBOX_PATH))?))
+    }
+
+    /// Sends a property tag with up to 2 values and returns the 2 values of the response
+    fn property(&self, tag: u32, values: [u32; 2]) -> Result<[u32; 2]> {
+        // _IOWR(100, 0, char *)
+        let request = (3 << 30) | ((std::mem::size_of::<*mut u8>() as u32) << 16) | (100 << 8);
+        let mut message = Message([
+            std::mem::size_of::<Message>() as u32, 0,
+            tag, 8, 0, values[0], values[1],
+            0
+        ]);
+        let result = unsafe { libc::ioctl(self.0.as_raw_fd(), request as _, message.0.as_mut_ptr()) };
+        if result < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        if message.0[1] != RESPONSE_SUCCESS {
+            return Err(anyhow::anyhow!("Mailbox tag {:#x} failed with {:#x}", tag, message.0[1]));
+        }
+        Ok([message.0[5], message.0[6]])
+    }
+}
+
+fn throttling(raw: u32) -> RpiThrottling {
+    let bit = |index: u32| raw & (1 << index) != 0;
+    RpiThrottling {
+        raw,
+        under_voltage: bit(0),
+        frequency_capped: bit(1),
+        throttled: bit(2),
+        soft_temperature_limit: bit(3),
+        under_voltage_occurred: bit(16),
+        frequency_capped_occurred: bit(17),
+        throttled_occurred: bit(18),
+        soft_temperature_limit_occurred: bit(19),
+    }
+}
+
+/// Telemetry from sysfs, for users that cannot open the mailbox
+fn sysfs_status() -> RpiStatus {
+    RpiStatus {
+        soc_temperature: read_text(&host::path(SOC_THERMAL_PATH))
+            .and_then(|temperature| temperature.parse::<i64>().ok())
+            .map(|temperature| temperature as f64 / 1000.0),
+        throttling: read_text(&host::path(THROTTLED_PATH))
+            .and_then(|raw| u32::from_str_radix(raw.trim_start_matches("0x"), 16).ok())
+            .map(throttling),
+        core_voltage: None,
+        gpu_memory: None,
+    }
+}
+
+impl Machine {
+    /// Raspberry Pi firmware telemetry, like `vcgencmd measure_temp`, `get_throttled`, `measure_volts core` and
+    /// `get_mem gpu`. It fails on other machines
+    /// Example
+    /// ```no_run
+    /// use machine_info::Machine;
+    /// let m = Machine::new();
+    /// let status = m.rpi_status().unwrap();
+    /// if status.throttling.map(|t| t.under_voltage).unwrap_or(false) {
+    ///   println!("Under-voltage detected, check the power supply");
+    /// }
+    /// ```
+    pub fn rpi_status(&self) -> Result<RpiStatus> {
+        let model = read_text(&host::path(MODEL_PATH)).unwrap_or_default();
+        if !model.starts_with("Raspberry Pi") {
+            return Err(anyhow::anyhow!("Not a Raspberry Pi"));
+        }
+        let mut status = sysfs_status();
+        let mailbox = match Mailbox::open() {
+            Ok(mailbox) => mailbox,
+            Err(err) => {
+                debug!("Cannot open the firmware mailbox: {:?}", err);
+                return Ok(status);
+            }
+        };
+        // Temperature in thousandths of a degree, voltage in microvolts
+        if let Ok([_, temperature]) = mailbox.property(TAG_GET_TEMPERATURE, [0, 0]) {
+            status.soc_temperature = Some(temperature as f64 / 1000.0);
+        }
+        if let Ok([raw, _]) = mailbox.property(TAG_GET_THROTTLED, [0, 0]) {
+            status.throttling = Some(throttling(raw));
+        }
+        status.core_voltage = mailbox.property(TAG_GET_VOLTAGE, [VOLTAGE_CORE, 0]).ok()
+            .map(|[_, voltage]| voltage as f64 / 1_000_000.0);
+        status.gpu_memory = mailbox.property(TAG_GET_VC_MEMORY, [0, 0]).ok()
+            .map(|[_, size]| size as u64);
+        Ok(status)
+    }
+}
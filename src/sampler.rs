@@ -0,0 +1,117 @@
+//! Background sampling. A thread samples the machine at a fixed interval and delivers the samples to the subscribers
+use log::debug;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use crate::Machine;
+use crate::model::Sample;
+use crate::host;
+
+/// Samples kept for a subscriber that is not receiving them. Newer samples are dropped when it is full
+const SUBSCRIBER_BUFFER: usize = 16;
+
+/// Samples the system status, graphics status and processes status in a background thread, so you do not need
+/// your own sleep loop around `Machine`. The thread stops when the sampler is dropped
+/// Example
+/// ```
+/// use machine_info::Sampler;
+/// use std::time::Duration;
+///
+/// let sampler = Sampler::start(Duration::from_millis(100));
+/// let samples = sampler.subscribe();
+/// let sample = samples.recv().unwrap();
+/// println!("{}% {:?}", sample.status.cpu, sample.graphics);
+/// ```
+pub struct Sampler {
+    subscribers: Arc<Mutex<Vec<mpsc::SyncSender<Sample>>>>,
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Sampler {
+    /// Starts sampling a new `Machine` every `interval`
+    pub fn start(interval: Duration) -> Sampler {
+        Sampler::with(Machine::new, interval)
+    }
+
+    /// Same as `start` but the machine is created by the given function, to configure it and track processes
+    /// Example
+    /// ```
+    /// use machine_info::{Machine, Sampler};
+    /// use std::time::Duration;
+    ///
+    /// let pid = std::process::id() as i32;
+    /// let sampler = Sampler::with(move || {
+    ///     let mut machine = Machine::new();
+    ///     machine.track_process(pid).unwrap();
+    ///     machine
+    /// }, Duration::from_millis(100));
+    /// let samples = sampler.subscribe();
+    /// assert_eq!(samples.recv().unwrap().processes[0].pid, pid);
+    /// ```
+    pub fn with<F: FnOnce() -> Machine + Send + 'static>(create: F, interval: Duration) -> Sampler {
+        let subscribers: Arc<Mutex<Vec<mpsc::SyncSender<Sample>>>> = Arc::new(Mutex::new(vec![]));
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = {
+            let subscribers = subscribers.clone();
+            thread::spawn(move || {
+                let machine = create();
+                loop {
+                    let started = Instant::now();
+                    match machine.system_status() {
+                        Ok(status) => {
+                            let sample = Sample {
+                                timestamp: host::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+                                status,
+                                graphics: machine.graphics_status(),
+                                processes: machine.processes_status(),
+                            };
+                            // Subscribers that dropped their receiver are removed, the slow ones miss this sample
+                            subscribers.lock().unwrap_or_else(|e| e.into_inner())
+                                .retain(|subscriber| !matches!(subscriber.try_send(sample.clone()),
+                                    Err(mpsc::TrySendError::Disconnected(_))));
+                        },
+                        Err(err) => debug!("Cannot sample the system status: {:?}", err)
+                    }
+                    // Waiting for the stop signal is the sleep between samples
+                    match stopped.recv_timeout(interval.saturating_sub(started.elapsed())) {
+                        Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                        _ => break
+                    }
+                }
+            })
+        };
+        Sampler {
+            subscribers,
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// New channel receiving every sample taken from now on. Up to 16 samples wait to be received, the ones taken
+    /// while it is full are dropped so a stalled subscriber does not grow the memory
+    pub fn subscribe(&self) -> mpsc::Receiver<Sample> {
+        let (sender, receiver) = mpsc::sync_channel(SUBSCRIBER_BUFFER);
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner()).push(sender);
+        receiver
+    }
+
+    /// Stops the sampling and waits for the thread to finish. Subscribers receive the pending samples and then
+    /// their channel is closed
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
@@ -0,0 +1,71 @@
+use std::fs;
+use crate::model::{SecurityModule, SecurityMode};
+use crate::sensors::read_text;
+use crate::host;
+
+const LSM_PATH: &str = "/sys/kernel/security/lsm";
+const SELINUX_ENFORCE_PATH: &str = "/sys/fs/selinux/enforce";
+const SELINUX_CONFIG_PATH: &str = "/etc/selinux/config";
+const APPARMOR_ENABLED_PATH: &str = "/sys/module/apparmor/parameters/enabled";
+const APPARMOR_PROFILES_PATH: &str = "/sys/kernel/security/apparmor/profiles";
+
+/// Active modules, like lockdown,capability,yama,apparmor. None if securityfs is not mounted
+fn active_modules() -> Option<Vec<String>> {
+    read_text(&host::path(LSM_PATH))
+        .map(|lsm| lsm.split(',').map(|name| name.trim().to_string()).collect())
+}
+
+fn selinux(active: &Option<Vec<String>>) -> Option<SecurityModule> {
+    let enforce = read_text(&host::path(SELINUX_ENFORCE_PATH));
+    let is_active = match active {
+        Some(modules) => modules.iter().any(|name| name == "selinux"),
+        None => enforce.is_some()
+    };
+    if !is_active {
+        return None;
+    }
+    // Loaded policy, like targeted or mls
+    let policy = fs::read_to_string(host::path(SELINUX_CONFIG_PATH)).ok()
+        .and_then(|config| config.lines()
+            .find_map(|line| line.trim().strip_prefix("SELINUXTYPE="))
+            .map(|policy| policy.trim().to_string()));
+    Some(SecurityModule {
+        name: "selinux".to_string(),
+        mode: match enforce.as_deref() {
+            Some("1") => Some(SecurityMode::Enforcing),
+            Some("0") => Some(SecurityMode::Permissive),
+            _ => None
+        },
+        policy,
+    })
+}
+
+fn apparmor(active: &Option<Vec<String>>) -> Option<SecurityModule> {
+    let is_active = match active {
+        Some(modules) => modules.iter().any(|name| name == "apparmor"),
+        None => read_text(&host::path(APPARMOR_ENABLED_PATH)).as_deref() == Some("Y")
+    };
+    if !is_active {
+        return None;
+    }
+    // Profiles like `/usr/sbin/cupsd (enforce)`, only readable by root. Enforcing if any profile is enforced
+    let mode = fs::read_to_string(host::path(APPARMOR_PROFILES_PATH)).ok()
+        .map(|profiles| {
+            if profiles.lines().any(|profile| profile.ends_with("(enforce)")) {
+                SecurityMode::Enforcing
+            } else {
+                SecurityMode::Permissive
+            }
+        });
+    Some(SecurityModule {
+        name: "apparmor".to_string(),
+        mode,
+        policy: None,
+    })
+}
+
+/// SELinux and AppArmor if they are active
+pub fn security_modules() -> Vec<SecurityModule> {
+    let active = active_modules();
+    [selinux(&active), apparmor(&active)].into_iter().flatten().collect()
+}
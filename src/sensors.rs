@@ -0,0 +1,172 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use log::debug;
+use crate::memory::read_number;
+use crate::model::{ThermalSensor, Fan};
+use crate::host;
+
+pub const HWMON_PATH: &str = "/sys/class/hwmon";
+const THERMAL_PATH: &str = "/sys/class/thermal";
+
+/// Reads a sysfs file containing a single line of text
+pub fn read_text(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Reads a sysfs temperature in millidegrees as degrees
+fn read_temperature(path: &Path) -> Option<f64> {
+    fs::read_to_string(path).ok()?.trim().parse::<i64>().ok().map(|value| value as f64 / 1000.0)
+}
+
+/// Entries of a sysfs class directory with the given prefix, sorted by their number (hwmon2 before hwmon10)
+/// and then by name
+pub fn class_entries(class: &str, prefix: &str) -> Result<Vec<(String, PathBuf)>> {
+    let mut entries = fs::read_dir(host::path(class))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            name.starts_with(prefix).then(|| (name, entry.path()))
+        })
+        .collect::<Vec<(String, PathBuf)>>();
+    entries.sort_by_key(|(name, _)| (name[prefix.len()..].parse::<u64>().unwrap_or(u64::MAX), name.clone()));
+    Ok(entries)
+}
+
+/// Indexes of the `<prefix>N_<suffix>` files of a hwmon device, like temp1_input and temp2_input
+pub fn hwmon_indexes(dir: &Path, prefix: &str, suffix: &str) -> Vec<u32> {
+    let mut indexes = fs::read_dir(dir).into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            name.strip_prefix(prefix)?.strip_suffix(suffix)?.parse::<u32>().ok()
+        })
+        .collect::<Vec<u32>>();
+    indexes.sort();
+    indexes
+}
+
+/// Temperature sensors of the hwmon devices (CPU, chipset, NVMe...)
+fn hwmon_sensors() -> Vec<ThermalSensor> {
+    let devices = match class_entries(HWMON_PATH, "hwmon") {
+        Ok(devices) => devices,
+        Err(err) => {
+            debug!("Cannot list hwmon devices: {:?}", err);
+            return vec![];
+        }
+    };
+    let mut sensors = vec![];
+    for (name, dir) in devices {
+        let chip = read_text(&dir.join("name")).unwrap_or_else(|| name.clone());
+        for index in hwmon_indexes(&dir, "temp", "_input") {
+            let temperature = match read_temperature(&dir.join(format!("temp{}_input", index))) {
+                Some(temperature) => temperature,
+                // Sensors without a reading (disconnected probes) fail with ENXIO or ENODATA
+                None => continue
+            };
+            sensors.push(ThermalSensor {
+                source: format!("{}/temp{}", name, index),
+                chip: chip.clone(),
+                label: read_text(&dir.join(format!("temp{}_label", index))).unwrap_or_else(|| format!("temp{}", index)),
+                temperature,
+                high: read_temperature(&dir.join(format!("temp{}_max", index))),
+                critical: read_temperature(&dir.join(format!("temp{}_crit", index))),
+            });
+        }
+    }
+    sensors
+}
+
+/// Thermal zones of the kernel thermal framework (ACPI, SoC sensors...)
+pub fn thermal_zones() -> Vec<ThermalSensor> {
+    let zones = match class_entries(THERMAL_PATH, "thermal_zone") {
+        Ok(zones) => zones,
+        Err(err) => {
+            debug!("Cannot list thermal zones: {:?}", err);
+            return vec![];
+        }
+    };
+    let mut sensors = vec![];
+    for (name, dir) in zones {
+        let temperature = match read_temperature(&dir.join("temp")) {
+            Some(temperature) => temperature,
+            None => continue
+        };
+        // Some Android vendor zones report degrees instead of millidegrees
+        #[cfg(target_os = "android")]
+        let temperature = if temperature.abs() < 1.0 { temperature * 1000.0 } else { temperature };
+        let zone_type = read_text(&dir.join("type")).unwrap_or_else(|| name.clone());
+        // Trip points are numbered from 0, the first ones that are passive (throttling) and critical are used
+        let mut high = None;
+        let mut critical = None;
+        for index in 0.. {
+            let trip_type = match read_text(&dir.join(format!("trip_point_{}_type", index))) {
+                Some(trip_type) => trip_type,
+                None => break
+            };
+            let trip = read_temperature(&dir.join(format!("trip_point_{}_temp", index)));
+            match trip_type.as_str() {
+                "passive" | "hot" if high.is_none() => high = trip,
+                "critical" if critical.is_none() => critical = trip,
+                _ => {}
+            }
+        }
+        sensors.push(ThermalSensor {
+            source: name,
+            chip: zone_type.clone(),
+            label: zone_type,
+            temperature,
+            high,
+            critical,
+        });
+    }
+    sensors
+}
+
+/// All the temperature sensors found in hwmon and the thermal zones. The same physical sensor can be
+/// exposed by both
+pub fn thermal_sensors() -> Vec<ThermalSensor> {
+    let mut sensors = hwmon_sensors();
+    sensors.extend(thermal_zones());
+    sensors
+}
+
+
+/// Fans of the hwmon devices with their speed and PWM target
+pub fn fans() -> Vec<Fan> {
+    let devices = match class_entries(HWMON_PATH, "hwmon") {
+        Ok(devices) => devices,
+        Err(err) => {
+            debug!("Cannot list hwmon devices: {:?}", err);
+            return vec![];
+        }
+    };
+    let mut fans = vec![];
+    for (name, dir) in devices {
+        let chip = read_text(&dir.join("name")).unwrap_or_else(|| name.clone());
+        for index in hwmon_indexes(&dir, "fan", "_input") {
+            // PWM outputs are usually numbered like the fans they drive, but it is not guaranteed
+            let pwm = read_number(&dir.join(format!("pwm{}", index))).ok();
+            let mode = read_number(&dir.join(format!("pwm{}_enable", index))).ok().map(|mode| match mode {
+                0 => "full".to_string(),
+                1 => "manual".to_string(),
+                _ => "automatic".to_string()
+            });
+            fans.push(Fan {
+                source: format!("{}/fan{}", name, index),
+                hwmon: name.clone(),
+                index,
+                chip: chip.clone(),
+                label: read_text(&dir.join(format!("fan{}_label", index))).unwrap_or_else(|| format!("fan{}", index)),
+                speed: read_number(&dir.join(format!("fan{}_input", index))).ok(),
+                min_speed: read_number(&dir.join(format!("fan{}_min", index))).ok(),
+                duty: pwm.map(|pwm| (pwm.min(255) * 100 / 255) as u8),
+                mode,
+            });
+        }
+    }
+    fans
+}
@@ -0,0 +1,525 @@
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use log::debug;
+use sysinfo::Disks;
+use crate::memory::{read_number, selected_option};
+use crate::model::{PhysicalDisk, Partition, ZramStats, Disk as DiskModel};
+use crate::host;
+use crate::nvme;
+
+const BLOCK_PATH: &str = "/sys/class/block";
+pub(crate) const SYS_BLOCK_PATH: &str = "/sys/block";
+// Sizes in sysfs are always in 512 bytes sectors
+const SECTOR_SIZE: u64 = 512;
+
+const PSEUDO_FILESYSTEMS: [&str; 20] = [
+    "tmpfs", "devtmpfs", "ramfs", "proc", "sysfs", "devpts", "cgroup", "cgroup2", "securityfs", "debugfs",
+    "tracefs", "configfs", "fusectl", "mqueue", "hugetlbfs", "pstore", "bpf", "efivarfs", "autofs", "nsfs",
+];
+const OVERLAY_FILESYSTEMS: [&str; 3] = ["overlay", "aufs", "squashfs"];
+const MEMORY_FILESYSTEMS: [&str; 2] = ["tmpfs", "ramfs"];
+// RAM block devices: compressed (zram) and plain (brd)
+const MEMORY_DEVICES: [&str; 2] = ["zram", "ram"];
+
+/// Which disks are excluded from the disks list. On machines with many snaps or containers the list can have
+/// hundreds of useless entries
+#[derive(Debug, Clone, Default)]
+pub struct DiskFilter {
+    /// Excludes virtual filesystems like tmpfs, proc or cgroup
+    pub exclude_pseudo: bool,
+    /// Excludes filesystems in loop devices, like snaps
+    pub exclude_loop: bool,
+    /// Excludes overlay filesystems used by containers
+    pub exclude_overlay: bool,
+    /// Excludes bind mounts and repeated mounts of the same filesystem
+    pub exclude_bind_mounts: bool,
+    /// Excludes filesystems in RAM (tmpfs, ramfs, zram...), which are not storage capacity
+    pub exclude_memory_backed: bool,
+    /// Excludes these filesystem types
+    pub exclude_filesystems: Vec<String>,
+    /// Excludes the mount points under any of these paths (whole path components)
+    pub exclude_mount_points: Vec<String>,
+}
+
+impl DiskFilter {
+    /// Excludes pseudo, loop, overlay filesystems and bind mounts
+    pub fn recommended() -> DiskFilter {
+        DiskFilter {
+            exclude_pseudo: true,
+            exclude_loop: true,
+            exclude_overlay: true,
+            exclude_bind_mounts: true,
+            ..Default::default()
+        }
+    }
+
+    /// Checks that only need the mount, done before reading anything about the device
+    fn excludes(&self, name: &str, fs: &str, mount_point: &str, bind_mounts: &HashSet<String>) -> bool {
+        if self.exclude_bind_mounts && bind_mounts.contains(mount_point) {
+            return true;
+        }
+        (self.exclude_pseudo && (PSEUDO_FILESYSTEMS.contains(&fs) || fs.starts_with("fuse.")))
+            || (self.exclude_loop && name.starts_with("/dev/loop"))
+            || (self.exclude_overlay && OVERLAY_FILESYSTEMS.contains(&fs))
+            || self.exclude_filesystems.iter().any(|f| f == fs)
+            // Whole components, so /home does not exclude /homeassistant
+            || self.exclude_mount_points.iter().any(|m| Path::new(mount_point).starts_with(m))
+    }
+}
+
+/// Entry of /proc/mounts
+pub struct Mount {
+    pub device: String,
+    pub mount_point: String,
+    pub options: String,
+}
+
+impl Mount {
+    /// True if the mount has the given flag like ro or noexec
+    pub fn has_option(&self, option: &str) -> bool {
+        self.options.split(',').any(|o| o == option)
+    }
+}
+
+/// /proc/mounts escapes spaces and other characters as octal like \040
+fn unescape(raw: &str) -> String {
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let code = chars.by_ref().take(3).collect::<String>();
+            match u8::from_str_radix(&code, 8) {
+                Ok(byte) => result.push(byte as char),
+                Err(_) => {
+                    result.push(c);
+                    result.push_str(&code);
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+pub fn mounts() -> Vec<Mount> {
+    let raw = match fs::read_to_string(host::path("/proc/mounts")) {
+        Ok(raw) => raw,
+        Err(e) => {
+            debug!("Cannot read mounts: {}", e);
+            return vec![];
+        }
+    };
+    raw.lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some(Mount {
+                device: unescape(parts.next()?),
+                mount_point: unescape(parts.next()?),
+                // Skip the filesystem type
+                options: parts.nth(1)?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Entry of /proc/self/mountinfo
+struct MountInfo {
+    device: String,
+    root: String,
+    mount_point: String,
+    /// The root is the btrfs subvolume itself (subvol= option), like / on /@ or /home on /@home
+    subvolume: bool,
+}
+
+fn mount_info(line: &str) -> Option<MountInfo> {
+    // id parent major:minor root mount_point options [optional fields] - fstype source super_options
+    let (mount, filesystem) = line.split_once(" - ")?;
+    let mut parts = mount.split_whitespace().skip(2);
+    let device = parts.next()?.to_string();
+    let root = unescape(parts.next()?);
+    let mount_point = unescape(parts.next()?);
+    let mut filesystem = filesystem.split_whitespace();
+    let fs = filesystem.next()?;
+    let subvolume = fs == "btrfs" && filesystem.nth(1).unwrap_or_default()
+        .split(',')
+        .any(|option| option.strip_prefix("subvol=") == Some(root.as_str()));
+    Some(MountInfo { device, root, mount_point, subvolume })
+}
+
+/// Mount points of bind mounts: a directory of a filesystem that is also mounted from a parent directory, or the
+/// same filesystem mounted again. btrfs subvolumes are not bind mounts even though their root is not /
+fn bind_mounts() -> HashSet<String> {
+    let raw = match fs::read_to_string(host::path("/proc/self/mountinfo")) {
+        Ok(raw) => raw,
+        Err(e) => {
+            debug!("Cannot read mountinfo: {}", e);
+            return HashSet::new();
+        }
+    };
+    let mounts = raw.lines().filter_map(mount_info).collect::<Vec<MountInfo>>();
+    mounts.iter().enumerate()
+        .filter(|(index, mount)| !mount.subvolume && mounts.iter().enumerate().any(|(other_index, other)| {
+            other.device == mount.device && (
+                (other.root != mount.root && Path::new(&mount.root).starts_with(&other.root))
+                    || (other.root == mount.root && other_index < *index))
+        }))
+        .map(|(_, mount)| mount.mount_point.clone())
+        .collect()
+}
+
+/// Mount of the given mount point. When several filesystems are mounted in the same place, the last one is the visible one
+pub fn mount<'a>(mounts: &'a [Mount], mount_point: &str) -> Option<&'a Mount> {
+    mounts.iter().rev().find(|m| m.mount_point == mount_point)
+}
+
+/// Kernel name of a block device (sda1, dm-0...) from its path. Symlinks like /dev/mapper/root are resolved
+pub fn block_name(device: &str) -> Option<String> {
+    if !device.starts_with("/dev/") {
+        return None;
+    }
+    let path = host::path(device);
+    let resolved = fs::canonicalize(&path).unwrap_or(path);
+    let name = resolved.file_name()?.to_string_lossy().to_string();
+    if host::path(BLOCK_PATH).join(&name).exists() {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Physical device holding the given block device. Partitions point to their disk and device mapper
+/// volumes (LVM, LUKS...) to the device below them
+pub fn physical_device(name: &str) -> Option<String> {
+    let sys = host::path(BLOCK_PATH).join(name);
+    if sys.join("partition").exists() {
+        // /sys/class/block/sda1 links to .../block/sda/sda1
+        let resolved = fs::canonicalize(&sys).ok()?;
+        return Some(resolved.parent()?.file_name()?.to_string_lossy().to_string());
+    }
+    let slave = fs::read_dir(sys.join("slaves")).ok()
+        .and_then(|mut slaves| slaves.next())
+        .and_then(|slave| slave.ok())
+        .map(|slave| slave.file_name().to_string_lossy().to_string());
+    match slave {
+        Some(slave) => physical_device(&slave),
+        None if sys.exists() => Some(name.to_string()),
+        None => None
+    }
+}
+
+/// Content of a sysfs attribute without the line feed. None if it is empty
+pub(crate) fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Model, serial number and firmware revision of a physical device
+pub struct Identity {
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub firmware: Option<String>,
+}
+
+/// Value of a udev property (E:KEY=value lines) of the device database
+pub(crate) fn udev_property(udev: &str, key: &str) -> Option<String> {
+    udev.lines()
+        .filter_map(|line| line.strip_prefix("E:"))
+        .filter_map(|line| line.split_once('='))
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// udev database entry of a block device, from its sysfs directory
+pub(crate) fn udev_data(sys: &Path) -> String {
+    read_trimmed(&sys.join("dev"))
+        .and_then(|dev| fs::read_to_string(host::path(&format!("/run/udev/data/b{}", dev))).ok())
+        .unwrap_or_default()
+}
+
+/// Reads the identity from sysfs. The attributes depend on the driver (SCSI, NVMe, virtio...), so udev
+/// database is used when sysfs does not have them
+pub fn identity(name: &str) -> Identity {
+    let sys = host::path(SYS_BLOCK_PATH).join(name);
+    let device = sys.join("device");
+    let udev = udev_data(&sys);
+    Identity {
+        model: read_trimmed(&device.join("model"))
+            .or_else(|| udev_property(&udev, "ID_MODEL")),
+        serial: read_trimmed(&device.join("serial"))
+            .or_else(|| read_trimmed(&sys.join("serial")))
+            .or_else(|| udev_property(&udev, "ID_SERIAL_SHORT")),
+        firmware: read_trimmed(&device.join("firmware_rev"))
+            .or_else(|| read_trimmed(&device.join("rev")))
+            .or_else(|| udev_property(&udev, "ID_REVISION")),
+    }
+}
+
+/// Encryption of a device mapper target, from its UUID like CRYPT-LUKS2-<uuid>-<name>. Integrity and verity
+/// targets are not encryption
+fn crypt_target(sys: &Path) -> Option<String> {
+    let uuid = read_trimmed(&sys.join("dm/uuid"))?;
+    let kind = uuid.strip_prefix("CRYPT-")?.split('-').next()?;
+    match kind {
+        "VERITY" | "INTEGRITY" | "SUBDEV" => None,
+        "PLAIN" => Some("dm-crypt".to_string()),
+        "BITLK" => Some("BitLocker".to_string()),
+        kind => Some(kind.to_string()),
+    }
+}
+
+/// Encryption of a block device or of any device below it, so a LVM volume on top of LUKS is encrypted too
+pub fn encryption(name: &str) -> Option<String> {
+    let sys = host::path(BLOCK_PATH).join(name);
+    crypt_target(&sys).or_else(|| fs::read_dir(sys.join("slaves")).ok()?
+        .flatten()
+        .find_map(|slave| encryption(&slave.file_name().to_string_lossy())))
+}
+
+/// Encryption of a partition. The udev database knows the LUKS and BitLocker headers even if the partition is
+/// not opened, otherwise the crypt devices on top of it are checked
+fn partition_encryption(name: &str) -> Option<String> {
+    let sys = host::path(BLOCK_PATH).join(name);
+    let udev = udev_data(&sys);
+    match udev_property(&udev, "ID_FS_TYPE").as_deref() {
+        Some("crypto_LUKS") => return Some(match udev_property(&udev, "ID_FS_VERSION") {
+            Some(version) => format!("LUKS{}", version),
+            None => "LUKS".to_string()
+        }),
+        Some("BitLocker") => return Some("BitLocker".to_string()),
+        _ => {}
+    }
+    fs::read_dir(sys.join("holders")).ok()?
+        .flatten()
+        .find_map(|holder| crypt_target(&holder.path()))
+}
+
+/// Compression stats of a zram device
+fn zram_stats(name: &str) -> Option<ZramStats> {
+    if !name.starts_with("zram") {
+        return None;
+    }
+    let sys = host::path(SYS_BLOCK_PATH).join(name);
+    // orig_data_size compr_data_size mem_used_total mem_limit mem_used_max same_pages...
+    let stats: Vec<u64> = read_trimmed(&sys.join("mm_stat"))?
+        .split_whitespace()
+        .filter_map(|value| value.parse().ok())
+        .collect();
+    if stats.len() < 4 {
+        return None;
+    }
+    Some(ZramStats {
+        device: name.to_string(),
+        algorithm: read_trimmed(&sys.join("comp_algorithm")).and_then(|algorithms| selected_option(&algorithms)),
+        disk_size: read_number(&sys.join("disksize")).unwrap_or(0),
+        original_size: stats[0],
+        compressed_size: stats[1],
+        memory_used: stats[2],
+        memory_limit: Some(stats[3]).filter(|limit| *limit > 0),
+        compression_ratio: Some(stats[1]).filter(|compressed| *compressed > 0)
+            .map(|compressed| stats[0] as f64 / compressed as f64),
+    })
+}
+
+fn physical_disk(path: &Path, name: String, mounts: &[Mount]) -> Result<PhysicalDisk> {
+    let mount_point = |name: &str| mounts.iter()
+        .find(|m| block_name(&m.device).as_deref() == Some(name))
+        .map(|m| m.mount_point.clone());
+
+    let mut partitions = Vec::new();
+    for entry in fs::read_dir(path)?.flatten() {
+        if !entry.path().join("partition").exists() {
+            continue;
+        }
+        let partition_name = entry.file_name().to_string_lossy().to_string();
+        partitions.push(Partition {
+            size: read_number(&entry.path().join("size"))? * SECTOR_SIZE,
+            mount_point: mount_point(&partition_name),
+            encryption: partition_encryption(&partition_name),
+            name: partition_name,
+        });
+    }
+    partitions.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let identity = identity(&name);
+    Ok(PhysicalDisk {
+        path: format!("/dev/{}", name),
+        model: identity.model,
+        serial: identity.serial,
+        firmware: identity.firmware,
+        size: read_number(&path.join("size"))? * SECTOR_SIZE,
+        rotational: read_number(&path.join("queue/rotational")).map(|r| r == 1).unwrap_or(false),
+        removable: read_number(&path.join("removable")).map(|r| r == 1).unwrap_or(false),
+        mount_point: mount_point(&name),
+        partitions,
+        nvme: nvme::details(&name),
+        name,
+    })
+}
+
+pub fn physical_disks() -> Result<Vec<PhysicalDisk>> {
+    let mounts = mounts();
+    let mut disks = Vec::new();
+    for entry in fs::read_dir(host::path(SYS_BLOCK_PATH))?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Virtual devices are not disks
+        if name.starts_with("loop") || name.starts_with("ram") || name.starts_with("dm-") || name.starts_with("md") {
+            continue;
+        }
+        match physical_disk(&entry.path(), name, &mounts) {
+            Ok(disk) if disk.size > 0 => disks.push(disk),
+            Ok(_) => continue,
+            Err(e) => debug!("Failed to read block device {:?}: {}", entry.path(), e)
+        }
+    }
+    disks.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(disks)
+}
+
+/// Builds the disks list adding the information sysinfo does not provide
+pub fn disks(disks: &Disks, filter: &DiskFilter) -> Vec<DiskModel> {
+    let mounts = mounts();
+    let bind_mounts = if filter.exclude_bind_mounts { bind_mounts() } else { HashSet::new() };
+    #[cfg(windows)]
+    let bitlocker = crate::windows::bitlocker_volumes();
+    let mut result = Vec::new();
+    for disk in disks.list() {
+        // Handle potential errors when converting disk names and file systems
+        let disk_name = disk.name().to_str().unwrap_or("Unknown").to_string();
+        let fs = disk.file_system().to_string_lossy().to_string();
+        let mount_point = disk.mount_point().to_str().unwrap_or("Unknown").to_string();
+        if filter.excludes(&disk_name, &fs, &mount_point, &bind_mounts) {
+            debug!("Disk {} mounted in {} excluded by the filter", disk_name, mount_point);
+            continue;
+        }
+        let block = block_name(&disk_name);
+        let parent_device = block.as_deref().and_then(physical_device);
+        let memory_backed = MEMORY_FILESYSTEMS.contains(&fs.as_str()) || parent_device.as_deref()
+            .map(|device| MEMORY_DEVICES.iter().any(|prefix| device.starts_with(prefix)))
+            .unwrap_or(false);
+        if filter.exclude_memory_backed && memory_backed {
+            debug!("Disk {} mounted in {} excluded by the filter", disk_name, mount_point);
+            continue;
+        }
+        let identity = parent_device.as_deref().map(identity);
+        let (model, serial, firmware) = match identity {
+            Some(identity) => (identity.model, identity.serial, identity.firmware),
+            None => (None, None, None)
+        };
+        let mount = mount(&mounts, &mount_point);
+        let zram = parent_device.as_deref().and_then(zram_stats);
+        #[cfg(not(windows))]
+        let encryption = block.as_deref().and_then(encryption);
+        #[cfg(windows)]
+        let encryption = bitlocker.contains(&mount_point.trim_end_matches('\\').to_uppercase())
+            .then(|| "BitLocker".to_string());
+
+        let model = DiskModel{
+            parent_device,
+            model,
+            serial,
+            firmware,
+            mount_options: mount.map(|m| m.options.clone()).unwrap_or_default(),
+            read_only: mount.map(|m| m.has_option("ro")).unwrap_or(disk.is_read_only()),
+            noexec: mount.map(|m| m.has_option("noexec")).unwrap_or(false),
+            nosuid: mount.map(|m| m.has_option("nosuid")).unwrap_or(false),
+            encryption,
+            memory_backed,
+            zram,
+            name: disk_name,
+            fs,
+            storage_type: match disk.kind() {
+                sysinfo::DiskKind::HDD => "HDD".to_string(),
+                sysinfo::DiskKind::SSD => "SSD".to_string(),
+                _ => "Unknown".to_string()
+            },
+            available: disk.available_space(),
+            size: disk.total_space(),
+            mount_point
+        };
+        result.push(model);
+    }
+    result
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use super::*;
+    use crate::testkit::FakeTree;
+
+    #[test]
+    fn escaped_mounts() {
+        let tree = FakeTree::new(1).unwrap();
+        tree.write("/proc/mounts", "/dev/sda2 / ext4 rw,relatime 0 0\n\
+            /dev/sdb1 /media/My\\040Disk vfat ro,nosuid,noexec 0 0\n\
+            tmpfs /tmp tmpfs rw 0 0\n\
+            /dev/sdc1 /tmp xfs rw,noatime 0 0\n").unwrap();
+        let mounts = mounts();
+        assert_eq!(mounts.len(), 4);
+        let media = mount(&mounts, "/media/My Disk").unwrap();
+        assert_eq!(media.device, "/dev/sdb1");
+        assert!(media.has_option("ro") && media.has_option("noexec") && !media.has_option("rw"));
+        // The last mount on the same mount point hides the others
+        assert_eq!(mount(&mounts, "/tmp").unwrap().device, "/dev/sdc1");
+    }
+
+    #[test]
+    fn bind_mounts_and_btrfs_subvolumes() {
+        let tree = FakeTree::new(1).unwrap();
+        tree.write("/proc/self/mountinfo", "22 1 0:21 / /proc rw,nosuid - proc proc rw\n\
+            30 1 8:2 / / rw,relatime shared:1 - ext4 /dev/sda2 rw\n\
+            31 30 8:2 /var/lib/docker /srv/docker rw,relatime shared:1 - ext4 /dev/sda2 rw\n\
+            32 30 8:2 / /mnt/again rw,relatime shared:1 - ext4 /dev/sda2 rw\n\
+            40 30 0:35 /@ /data rw,relatime shared:2 - btrfs /dev/nvme0n1p2 rw,ssd,subvolid=256,subvol=/@\n\
+            41 40 0:35 /@home /data/home rw,relatime shared:3 - btrfs /dev/nvme0n1p2 rw,ssd,subvolid=257,subvol=/@home\n\
+            42 30 0:35 /@home/alice /home/alice rw,relatime shared:3 - btrfs /dev/nvme0n1p2 rw,ssd,subvolid=257,subvol=/@home\n\
+            50 30 8:17 / /media/with\\040space rw - vfat /dev/sdb1 rw\n").unwrap();
+        let bind_mounts = bind_mounts();
+        let mut expected = ["/srv/docker", "/mnt/again", "/home/alice"].map(String::from).to_vec();
+        let mut found = bind_mounts.into_iter().collect::<Vec<String>>();
+        expected.sort();
+        found.sort();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn physical_device_of_partitions_and_mapped_volumes() {
+        let tree = FakeTree::new(1).unwrap();
+        tree.write("/sys/devices/pci0000:00/block/sda/sda2/partition", "2").unwrap();
+        tree.write("/sys/devices/virtual/block/dm-0/dm/uuid", "CRYPT-LUKS2-0123456789abcdef-luks-root").unwrap();
+        tree.write("/sys/devices/virtual/block/dm-1/dm/uuid", "LVM-abcdef").unwrap();
+        fs::create_dir_all(tree.root().join("sys/class/block")).unwrap();
+        let link = |target: &str, link: &str| std::os::unix::fs::symlink(tree.root().join(target), tree.root().join(link)).unwrap();
+        link("sys/devices/pci0000:00/block/sda", "sys/class/block/sda");
+        link("sys/devices/pci0000:00/block/sda/sda2", "sys/class/block/sda2");
+        link("sys/devices/virtual/block/dm-0", "sys/class/block/dm-0");
+        link("sys/devices/virtual/block/dm-1", "sys/class/block/dm-1");
+        // LVM volume dm-1 on top of the LUKS volume dm-0 on top of sda2
+        tree.write("/sys/devices/virtual/block/dm-0/slaves/sda2", "").unwrap();
+        tree.write("/sys/devices/virtual/block/dm-1/slaves/dm-0", "").unwrap();
+
+        assert_eq!(physical_device("sda2").as_deref(), Some("sda"));
+        assert_eq!(physical_device("dm-1").as_deref(), Some("sda"));
+        assert_eq!(physical_device("nvme9n1"), None);
+        assert_eq!(encryption("dm-1").as_deref(), Some("LUKS2"));
+        assert_eq!(encryption("sda2"), None);
+    }
+
+    #[test]
+    fn recommended_filter() {
+        let filter = DiskFilter { exclude_mount_points: vec!["/home".to_string()], ..DiskFilter::recommended() };
+        let bind_mounts = HashSet::from(["/srv/docker".to_string()]);
+        assert!(filter.excludes("tmpfs", "tmpfs", "/run", &bind_mounts));
+        assert!(filter.excludes("/dev/loop3", "squashfs", "/snap/core/1", &bind_mounts));
+        assert!(filter.excludes("overlay", "overlay", "/var/lib/docker/overlay2/1/merged", &bind_mounts));
+        assert!(filter.excludes("/dev/sda2", "ext4", "/srv/docker", &bind_mounts));
+        assert!(filter.excludes("/dev/sda3", "ext4", "/home/alice", &bind_mounts));
+        assert!(!filter.excludes("/dev/sda4", "ext4", "/homeassistant", &bind_mounts));
+        assert!(!filter.excludes("/dev/sda2", "ext4", "/", &bind_mounts));
+    }
+}
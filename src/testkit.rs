@@ -0,0 +1,351 @@
+//! Test support for applications built on this crate. Enable it with the `testkit` feature.
+//!
+//! A [`FakeTree`] replaces the host `/proc` and `/sys` files read by this crate with a reproducible fake tree
+//! in a temporary directory, and moves the crate clock, so the CPU, memory and processes usage reported by
+//! `Machine` is deterministic. Chaos hooks simulate processes dying, corrupted files and GPU loss.
+//! Data collected through `sysinfo` (like `system_info().disks`) is not faked.
+//!
+//! Example
+//! ```
+//! use machine_info::{Machine, ProcessState};
+//! use machine_info::testkit::FakeTree;
+//! use std::time::Duration;
+//!
+//! let mut tree = FakeTree::new(42).unwrap();
+//! let pid = tree.spawn_process(50.0).unwrap();
+//!
+//! let m = Machine::new();
+//! m.system_status().unwrap();
+//! m.track_process(pid).unwrap();
+//!
+//! tree.tick(Duration::from_secs(10), 75).unwrap();
+//! assert_eq!(m.system_status().unwrap().cpu, 75);
+//! assert_eq!(m.processes_status()[0].cpu, 50.0);
+//!
+//! tree.kill_process(pid).unwrap();
+//! assert_eq!(m.processes_status()[0].state, ProcessState::Exited);
+//! assert!(m.processes_status().is_empty());
+//! ```
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, UNIX_EPOCH};
+use crate::host::{self, ROOT, CLOCK_OFFSET_MS, GPU_LOST};
+
+// Only one fake tree can be installed at the same time, this lock serializes the tests using it
+static LOCK: Mutex<()> = Mutex::new(());
+static TREES: AtomicUsize = AtomicUsize::new(0);
+// The crate assumes 100 ticks per second
+const TICKS_PER_SECOND: f64 = 100.0;
+const FIRST_PID: i32 = 1000;
+
+/// Clock used by the crate. It only moves forward, until the fake tree is dropped and it is set back to the real one
+pub struct FakeClock;
+
+impl FakeClock {
+    /// Moves the crate clock forward
+    pub fn advance(duration: Duration) {
+        CLOCK_OFFSET_MS.fetch_add(duration.as_millis() as u64, Ordering::SeqCst);
+    }
+
+    /// Time the crate clock is ahead of the real one
+    pub fn offset() -> Duration {
+        Duration::from_millis(CLOCK_OFFSET_MS.load(Ordering::SeqCst))
+    }
+}
+
+struct FakeProcess {
+    name: String,
+    parent: i32,
+    load: f64,
+    ticks: f64,
+    nice: i32,
+    // Ticks since boot when the process was started
+    start: u64,
+    read_bytes: u64,
+    write_bytes: u64,
+}
+
+/// Fake host files. It is installed when created and removed when dropped, along with the offset of the clock
+pub struct FakeTree {
+    root: PathBuf,
+    seed: u64,
+    cpus: usize,
+    // user, nice, system, idle, iowait, irq, softirq, steal, guest, guest_nice
+    cpu_ticks: [u64; 10],
+    memory_total: u64,
+    memory_available: u64,
+    // Unix timestamp, one hour before the tree was created
+    boot_time: u64,
+    processes: BTreeMap<i32, FakeProcess>,
+    next_pid: i32,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl FakeTree {
+    /// Creates and installs a fake tree with 4 CPUs and 16GB of memory. The seed makes the random
+    /// processes reproducible
+    pub fn new(seed: u64) -> Result<FakeTree> {
+        let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let root = std::env::temp_dir().join(format!("machine-info-testkit-{}-{}",
+            std::process::id(), TREES.fetch_add(1, Ordering::SeqCst)));
+        fs::create_dir_all(&root)?;
+        let tree = FakeTree {
+            root,
+            seed,
+            cpus: 4,
+            // Some idle time since boot, so the first sample has something to compare with
+            cpu_ticks: [0, 0, 0, 400, 0, 0, 0, 0, 0, 0],
+            memory_total: 16 * 1024 * 1024,
+            memory_available: 8 * 1024 * 1024,
+            boot_time: host::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs().saturating_sub(3600),
+            processes: BTreeMap::new(),
+            next_pid: FIRST_PID,
+            _lock: lock,
+        };
+        tree.write_cpu()?;
+        tree.write_memory()?;
+        *ROOT.write().unwrap_or_else(|e| e.into_inner()) = Some(tree.root.clone());
+        GPU_LOST.store(false, Ordering::SeqCst);
+        Ok(tree)
+    }
+
+    /// Directory where the fake tree lives
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Changes the amount of CPUs
+    pub fn set_cpus(&mut self, cpus: usize) -> Result<()> {
+        self.cpus = cpus.max(1);
+        self.write_cpu()
+    }
+
+    /// Changes the total and available memory in kB
+    pub fn set_memory(&mut self, total: u64, available: u64) -> Result<()> {
+        self.memory_total = total;
+        self.memory_available = available.min(total);
+        self.write_memory()
+    }
+
+    /// Writes any other file in the tree, like `/sys/class/hwmon/hwmon0/temp1_input`
+    pub fn write(&self, path: &str, content: &str) -> Result<()> {
+        let path = self.root.join(path.trim_start_matches('/'));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Removes a file or directory of the tree, like a disappearing device
+    pub fn remove(&self, path: &str) -> Result<()> {
+        let path = self.root.join(path.trim_start_matches('/'));
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        } else {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Chaos hook: replaces the content of a file with garbage so the parsers fail
+    pub fn corrupt(&self, path: &str) -> Result<()> {
+        self.write(path, "\u{0}garbage\n")
+    }
+
+    /// Chaos hook: makes the Nvidia GPUs disappear (or come back)
+    pub fn set_gpu_lost(&self, lost: bool) {
+        GPU_LOST.store(lost, Ordering::SeqCst);
+    }
+
+    /// Creates a process using the given CPU percentage (relative to one core). Returns its PID
+    pub fn spawn_process(&mut self, load: f64) -> Result<i32> {
+        self.spawn(1, load)
+    }
+
+    /// Creates a child of a fake process using the given CPU percentage. Returns its PID
+    pub fn spawn_child(&mut self, parent: i32, load: f64) -> Result<i32> {
+        if !self.processes.contains_key(&parent) {
+            return Err(anyhow::anyhow!("Unknown fake process {}", parent));
+        }
+        self.spawn(parent, load)
+    }
+
+    /// Creates processes with random loads between 0 and 100%. The same seed creates the same processes
+    pub fn spawn_random_processes(&mut self, count: usize) -> Result<Vec<i32>> {
+        let mut pids = Vec::with_capacity(count);
+        for _ in 0..count {
+            let load = (self.random() % 1001) as f64 / 10.0;
+            pids.push(self.spawn_process(load)?);
+        }
+        Ok(pids)
+    }
+
+    /// Changes the CPU percentage used by a process
+    pub fn set_process_load(&mut self, pid: i32, load: f64) -> Result<()> {
+        let process = self.processes.get_mut(&pid).ok_or_else(|| anyhow::anyhow!("Unknown fake process {}", pid))?;
+        process.load = load;
+        Ok(())
+    }
+
+    /// Changes the nice value of a process
+    pub fn set_process_nice(&mut self, pid: i32, nice: i32) -> Result<()> {
+        let process = self.processes.get_mut(&pid).ok_or_else(|| anyhow::anyhow!("Unknown fake process {}", pid))?;
+        process.nice = nice.clamp(-20, 19);
+        self.write_process(pid)
+    }
+
+    /// Adds bytes read from and written to storage by a process
+    pub fn add_process_io(&mut self, pid: i32, read_bytes: u64, write_bytes: u64) -> Result<()> {
+        let process = self.processes.get_mut(&pid).ok_or_else(|| anyhow::anyhow!("Unknown fake process {}", pid))?;
+        process.read_bytes += read_bytes;
+        process.write_bytes += write_bytes;
+        self.write_process(pid)
+    }
+
+    /// Chaos hook: the process dies and its /proc entry disappears. Its children are reparented to init
+    pub fn kill_process(&mut self, pid: i32) -> Result<()> {
+        self.processes.remove(&pid).ok_or_else(|| anyhow::anyhow!("Unknown fake process {}", pid))?;
+        let orphans = self.processes.iter()
+            .filter(|(_, process)| process.parent == pid)
+            .map(|(&child, _)| child)
+            .collect::<Vec<i32>>();
+        for child in orphans {
+            if let Some(process) = self.processes.get_mut(&child) {
+                process.parent = 1;
+            }
+            self.write_process(child)?;
+        }
+        self.remove(&format!("/proc/{}", pid))
+    }
+
+    /// Moves the clock forward and updates the counters as if the system used `cpu` percent
+    /// during that time, and every process its own load
+    pub fn tick(&mut self, duration: Duration, cpu: u8) -> Result<()> {
+        let total = (duration.as_secs_f64() * TICKS_PER_SECOND) as u64 * self.cpus as u64;
+        let used = total * cpu.min(100) as u64 / 100;
+        self.cpu_ticks[0] += used;
+        self.cpu_ticks[3] += total - used;
+        self.write_cpu()?;
+
+        let pids = self.processes.keys().copied().collect::<Vec<i32>>();
+        for pid in pids {
+            if let Some(process) = self.processes.get_mut(&pid) {
+                process.ticks += process.load * duration.as_secs_f64();
+            }
+            self.write_process(pid)?;
+        }
+        FakeClock::advance(duration);
+        Ok(())
+    }
+
+    fn spawn(&mut self, parent: i32, load: f64) -> Result<i32> {
+        let pid = self.next_pid;
+        self.next_pid += 1;
+        let since_boot = host::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64() - self.boot_time as f64;
+        let start = (since_boot * TICKS_PER_SECOND) as u64;
+        self.processes.insert(pid, FakeProcess { name: format!("fake-{}", pid), parent, load, ticks: 0.0, nice: 0, start, read_bytes: 0, write_bytes: 0 });
+        self.write_process(pid)?;
+        self.write(&format!("/proc/{}/cmdline", pid), &format!("/usr/bin/fake\0--id\0{}\0", pid))?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("/usr/bin/fake", self.root.join(format!("proc/{}/exe", pid)))?;
+        for fd in 0..3 {
+            self.write(&format!("/proc/{}/fd/{}", pid, fd), "")?;
+        }
+        self.write(&format!("/proc/{}/limits", pid),
+            "Limit                     Soft Limit           Hard Limit           Units     \n\
+             Max open files            1024                 524288               files     \n")?;
+        Ok(pid)
+    }
+
+    // xorshift64, enough to get reproducible values
+    fn random(&mut self) -> u64 {
+        let mut x = self.seed.max(1);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed = x;
+        x
+    }
+
+    fn write_cpu(&self) -> Result<()> {
+        let values = |ticks: &[u64]| ticks.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(" ");
+        let mut content = format!("cpu  {}\n", values(&self.cpu_ticks));
+        for cpu in 0..self.cpus {
+            let per_cpu = self.cpu_ticks.iter().map(|t| t / self.cpus as u64).collect::<Vec<u64>>();
+            content.push_str(&format!("cpu{} {}\n", cpu, values(&per_cpu)));
+        }
+        content.push_str(&format!("btime {}\n", self.boot_time));
+        self.write("/proc/stat", &content)
+    }
+
+    fn write_memory(&self) -> Result<()> {
+        let free = self.memory_available / 2;
+        let cached = self.memory_available - free;
+        self.write("/proc/meminfo", &format!(
+            "MemTotal: {} kB\nMemFree: {} kB\nMemAvailable: {} kB\nBuffers: 0 kB\nCached: {} kB\nSReclaimable: 0 kB\nDirty: 0 kB\n",
+            self.memory_total, free, self.memory_available, cached))
+    }
+
+    fn write_process(&self, pid: i32) -> Result<()> {
+        let process = match self.processes.get(&pid) {
+            Some(process) => process,
+            None => return Ok(())
+        };
+        // Same layout as /proc/<pid>/stat, utime is the 14th field
+        let mut fields = vec!["0".to_string(); 52];
+        fields[0] = pid.to_string();
+        fields[1] = format!("({})", process.name);
+        fields[2] = "R".to_string();
+        fields[3] = process.parent.to_string();
+        fields[13] = (process.ticks.round() as u64).to_string();
+        fields[18] = process.nice.to_string();
+        fields[21] = process.start.to_string();
+        self.write(&format!("/proc/{}/stat", pid), &format!("{}\n", fields.join(" ")))?;
+        // size resident shared text lib data dt, in pages
+        self.write(&format!("/proc/{}/statm", pid), "2048 512 128 16 0 256 0\n")?;
+        self.write(&format!("/proc/{}/io", pid), &format!(
+            "rchar: {}\nwchar: {}\nsyscr: 0\nsyscw: 0\nread_bytes: {}\nwrite_bytes: {}\ncancelled_write_bytes: 0\n",
+            process.read_bytes, process.write_bytes, process.read_bytes, process.write_bytes))
+    }
+}
+
+impl Drop for FakeTree {
+    fn drop(&mut self) {
+        *ROOT.write().unwrap_or_else(|e| e.into_inner()) = None;
+        GPU_LOST.store(false, Ordering::SeqCst);
+        CLOCK_OFFSET_MS.store(0, Ordering::SeqCst);
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_set_back_when_dropped() {
+        let mut tree = FakeTree::new(1).unwrap();
+        tree.tick(Duration::from_secs(3600), 0).unwrap();
+        assert_eq!(FakeClock::offset(), Duration::from_secs(3600));
+        assert!(host::path("/proc/stat").starts_with(tree.root()));
+        drop(tree);
+        assert_eq!(FakeClock::offset(), Duration::ZERO);
+        assert_eq!(host::path("/proc/stat"), PathBuf::from("/proc/stat"));
+    }
+
+    #[test]
+    fn reproducible_processes() {
+        let loads = |seed| {
+            let mut tree = FakeTree::new(seed).unwrap();
+            let pids = tree.spawn_random_processes(5).unwrap();
+            pids.iter().map(|pid| tree.processes[pid].load).collect::<Vec<f64>>()
+        };
+        assert_eq!(loads(7), loads(7));
+        assert_ne!(loads(7), loads(8));
+    }
+}
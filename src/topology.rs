@@ -0,0 +1,152 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use log::debug;
+use crate::model::{StorageTopology, RaidArray, VolumeGroup, ZfsPool};
+use crate::{host, storage};
+use crate::storage::{read_trimmed, SYS_BLOCK_PATH};
+
+const ZFS_KSTAT_PATH: &str = "/proc/spl/kstat/zfs";
+
+/// Devices below a virtual block device
+fn slaves(path: &Path) -> Vec<String> {
+    let mut slaves = fs::read_dir(path.join("slaves"))
+        .map(|entries| entries.flatten().map(|e| e.file_name().to_string_lossy().to_string()).collect())
+        .unwrap_or_else(|_| vec![]);
+    slaves.sort();
+    slaves
+}
+
+fn raid_arrays(blocks: &[(String, std::path::PathBuf)]) -> Vec<RaidArray> {
+    let mut arrays = Vec::new();
+    for (name, path) in blocks.iter().filter(|(name, _)| name.starts_with("md")) {
+        let md = path.join("md");
+        let state = match read_trimmed(&md.join("array_state")) {
+            Some(state) => state,
+            None => continue
+        };
+        let failed_devices = read_trimmed(&md.join("degraded"))
+            .and_then(|d| d.parse::<u32>().ok())
+            .unwrap_or(0);
+        arrays.push(RaidArray {
+            name: name.clone(),
+            level: read_trimmed(&md.join("level")).unwrap_or_default(),
+            degraded: failed_devices > 0 || state == "inactive",
+            state,
+            failed_devices,
+            members: slaves(path),
+        });
+    }
+    arrays
+}
+
+/// Device mapper escapes the dashes of the names doubling them, so a single dash separates the volume
+/// group from the logical volume
+fn split_lvm_name(name: &str) -> Option<(String, String)> {
+    let chars = name.chars().collect::<Vec<char>>();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '-' {
+            if chars.get(i + 1) == Some(&'-') {
+                i += 2;
+                continue;
+            }
+            let unescape = |s: String| s.replace("--", "-");
+            return Some((unescape(chars[..i].iter().collect()), unescape(chars[i + 1..].iter().collect())));
+        }
+        i += 1;
+    }
+    None
+}
+
+fn volume_groups(blocks: &[(String, std::path::PathBuf)]) -> Vec<VolumeGroup> {
+    let mut groups: BTreeMap<String, VolumeGroup> = BTreeMap::new();
+    for (_, path) in blocks {
+        let dm = path.join("dm");
+        let is_lvm = read_trimmed(&dm.join("uuid")).map(|uuid| uuid.starts_with("LVM-")).unwrap_or(false);
+        if !is_lvm {
+            continue;
+        }
+        let (group, volume) = match read_trimmed(&dm.join("name")).as_deref().and_then(split_lvm_name) {
+            Some(names) => names,
+            None => continue
+        };
+        let entry = groups.entry(group.clone()).or_insert_with(|| VolumeGroup {
+            name: group,
+            volumes: vec![],
+            members: vec![],
+        });
+        entry.volumes.push(volume);
+        for member in slaves(path) {
+            // Thin pools and snapshots are stacked over other logical volumes of the group
+            if member.starts_with("dm-") || entry.members.contains(&member) {
+                continue;
+            }
+            entry.members.push(member);
+        }
+    }
+    groups.into_values().collect()
+}
+
+/// Devices and partitions labelled as members of a ZFS pool in the udev database, by pool name
+fn zfs_members(blocks: &[(String, std::path::PathBuf)]) -> BTreeMap<String, Vec<String>> {
+    let mut members: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (name, path) in blocks {
+        let mut partitions = fs::read_dir(path)
+            .map(|entries| entries.flatten()
+                .filter(|entry| entry.path().join("partition").exists())
+                .map(|entry| (entry.file_name().to_string_lossy().to_string(), entry.path()))
+                .collect())
+            .unwrap_or_else(|_| vec![]);
+        partitions.sort();
+        for (device, sys) in std::iter::once((name.clone(), path.clone())).chain(partitions) {
+            let udev = storage::udev_data(&sys);
+            if storage::udev_property(&udev, "ID_FS_TYPE").as_deref() != Some("zfs_member") {
+                continue;
+            }
+            if let Some(pool) = storage::udev_property(&udev, "ID_FS_LABEL") {
+                members.entry(pool).or_default().push(device);
+            }
+        }
+    }
+    members
+}
+
+fn zfs_pools(blocks: &[(String, std::path::PathBuf)]) -> Vec<ZfsPool> {
+    let entries = match fs::read_dir(host::path(ZFS_KSTAT_PATH)) {
+        Ok(entries) => entries,
+        Err(_) => return vec![]
+    };
+    let mut members = zfs_members(blocks);
+    let mut pools = entries.flatten()
+        .filter_map(|entry| {
+            let state = read_trimmed(&entry.path().join("state"))?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            Some(ZfsPool {
+                members: members.remove(&name).unwrap_or_default(),
+                name,
+                degraded: state != "ONLINE",
+                state,
+            })
+        })
+        .collect::<Vec<ZfsPool>>();
+    pools.sort_by(|a, b| a.name.cmp(&b.name));
+    pools
+}
+
+pub fn storage_topology() -> Result<StorageTopology> {
+    let mut blocks = fs::read_dir(host::path(SYS_BLOCK_PATH))?
+        .flatten()
+        .map(|entry| (entry.file_name().to_string_lossy().to_string(), entry.path()))
+        .collect::<Vec<_>>();
+    blocks.sort();
+    let topology = StorageTopology {
+        raid_arrays: raid_arrays(&blocks),
+        volume_groups: volume_groups(&blocks),
+        zfs_pools: zfs_pools(&blocks),
+    };
+    debug!("Found {} RAID arrays, {} volume groups and {} ZFS pools",
+        topology.raid_arrays.len(), topology.volume_groups.len(), topology.zfs_pools.len());
+    Ok(topology)
+}
@@ -0,0 +1,54 @@
+#[cfg(unix)]
+use std::ffi::CString;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use crate::model::TpmInfo;
+use crate::sensors::read_text;
+use crate::host;
+
+const TPM_CLASS_PATH: &str = "/sys/class/tpm/tpm0";
+
+/// TPM version. Kernels since 5.6 expose the major version, otherwise only TPM 1.2 devices have caps
+fn version(dir: &Path) -> String {
+    match read_text(&dir.join("tpm_version_major")).as_deref() {
+        Some("1") => "1.2".to_string(),
+        Some(_) => "2.0".to_string(),
+        None if dir.join("caps").exists() || dir.join("device/caps").exists() => "1.2".to_string(),
+        None => "2.0".to_string()
+    }
+}
+
+/// True if the device node can be read and written, like the TPM tools need. It is not opened to check it:
+/// /dev/tpm0 only allows one client at a time, so opening it could make other clients fail meanwhile
+#[cfg(unix)]
+fn accessible(device: &str) -> bool {
+    match CString::new(host::path(device).as_os_str().as_bytes()) {
+        Ok(path) => unsafe { libc::access(path.as_ptr(), libc::R_OK | libc::W_OK) == 0 },
+        Err(_) => false
+    }
+}
+
+#[cfg(not(unix))]
+fn accessible(_device: &str) -> bool {
+    false
+}
+
+/// First TPM of the machine. None if the kernel did not find any
+pub fn tpm() -> Option<TpmInfo> {
+    let dir = host::path(TPM_CLASS_PATH);
+    if !dir.exists() {
+        return None;
+    }
+    let version = version(&dir);
+    // The resource manager allows several clients at the same time, so it is preferred
+    let device = ["/dev/tpmrm0", "/dev/tpm0"].into_iter()
+        .find(|device| host::path(device).exists())
+        .unwrap_or("/dev/tpm0");
+    Some(TpmInfo {
+        accessible: accessible(device),
+        device: device.to_string(),
+        description: read_text(&dir.join("device/description")),
+        version,
+    })
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use std::fs;
+use log::debug;
+use crate::model::UserSession;
+use crate::host;
+
+const UTMP_PATH: &str = "/var/run/utmp";
+const LOGIND_SESSIONS_PATH: &str = "/run/systemd/sessions";
+// struct utmp of glibc on Linux (the same size on 32 and 64 bits)
+const UTMP_SIZE: usize = 384;
+const USER_PROCESS: i16 = 7;
+
+/// NUL terminated string of a fixed size field
+fn field(record: &[u8], start: usize, len: usize) -> String {
+    let raw = &record[start..start + len];
+    let end = raw.iter().position(|&c| c == 0).unwrap_or(len);
+    String::from_utf8_lossy(&raw[..end]).to_string()
+}
+
+fn number(record: &[u8], start: usize) -> i32 {
+    i32::from_ne_bytes([record[start], record[start + 1], record[start + 2], record[start + 3]])
+}
+
+/// Login sessions from the utmp database. Entries of processes that are not running anymore are skipped
+fn utmp_sessions() -> Result<Vec<UserSession>> {
+    let raw = fs::read(host::path(UTMP_PATH))?;
+    let mut sessions = vec![];
+    for record in raw.chunks_exact(UTMP_SIZE) {
+        if i16::from_ne_bytes([record[0], record[1]]) != USER_PROCESS {
+            continue;
+        }
+        // ut_pid 4, ut_line 8, ut_id 40, ut_user 44, ut_host 76, ut_exit 332, ut_session 336, ut_tv 340
+        let pid = number(record, 4);
+        if !host::path(&format!("/proc/{}", pid)).exists() {
+            continue;
+        }
+        let terminal = field(record, 8, 32);
+        let remote_host = field(record, 76, 256);
+        sessions.push(UserSession {
+            user: field(record, 44, 32),
+            terminal: Some(terminal).filter(|terminal| !terminal.is_empty()),
+            login_time: number(record, 340).max(0) as u64,
+            remote_host: Some(remote_host).filter(|host| !host.is_empty()),
+            pid: Some(pid),
+        });
+    }
+    Ok(sessions)
+}
+
+/// Login sessions from systemd-logind, for systems without utmp
+fn logind_sessions() -> Result<Vec<UserSession>> {
+    let mut sessions = vec![];
+    for entry in fs::read_dir(host::path(LOGIND_SESSIONS_PATH))?.flatten() {
+        let content = match fs::read_to_string(entry.path()) {
+            Ok(content) => content,
+            Err(_) => continue
+        };
+        let value = |key: &str| content.lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(|value| value.to_string())
+            .filter(|value| !value.is_empty());
+        // Greeters, lock screens and background sessions are not users logged in
+        if value("CLASS").as_deref() != Some("user") || value("STATE").as_deref() == Some("closing") {
+            continue;
+        }
+        let user = match value("USER") {
+            Some(user) => user,
+            None => continue
+        };
+        sessions.push(UserSession {
+            user,
+            terminal: value("TTY").or_else(|| value("DISPLAY")),
+            // Microseconds since the epoch
+            login_time: value("REALTIME").and_then(|time| time.parse::<u64>().ok()).unwrap_or(0) / 1_000_000,
+            remote_host: value("REMOTE_HOST"),
+            pid: value("LEADER").and_then(|pid| pid.parse::<i32>().ok()),
+        });
+    }
+    Ok(sessions)
+}
+
+/// Active login sessions, from utmp or systemd-logind when utmp is not available
+pub fn users() -> Result<Vec<UserSession>> {
+    let mut sessions = match utmp_sessions() {
+        Ok(sessions) if !sessions.is_empty() => sessions,
+        Ok(_) => logind_sessions().unwrap_or_default(),
+        Err(err) => {
+            debug!("Cannot read utmp: {:?}", err);
+            logind_sessions()?
+        }
+    };
+    sessions.sort_by_key(|session| session.login_time);
+    Ok(sessions)
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use super::*;
+    use crate::testkit::FakeTree;
+
+    fn record(kind: i16, pid: i32, line: &str, user: &str, remote_host: &str, login_time: i32) -> Vec<u8> {
+        let mut record = vec![0; UTMP_SIZE];
+        record[0..2].copy_from_slice(&kind.to_ne_bytes());
+        record[4..8].copy_from_slice(&pid.to_ne_bytes());
+        record[8..8 + line.len()].copy_from_slice(line.as_bytes());
+        record[44..44 + user.len()].copy_from_slice(user.as_bytes());
+        record[76..76 + remote_host.len()].copy_from_slice(remote_host.as_bytes());
+        record[340..344].copy_from_slice(&login_time.to_ne_bytes());
+        record
+    }
+
+    #[test]
+    fn utmp_sessions_of_running_processes() {
+        let mut tree = FakeTree::new(1).unwrap();
+        let shell = tree.spawn_process(0.0).unwrap();
+        let sshd = tree.spawn_process(0.0).unwrap();
+        let utmp = [
+            // BOOT_TIME and LOGIN_PROCESS records are not sessions
+            record(2, 0, "~", "reboot", "", 1_700_000_000),
+            record(6, shell, "tty1", "LOGIN", "", 1_700_000_010),
+            record(USER_PROCESS, sshd, "pts/0", "alice", "192.168.1.10", 1_700_000_200),
+            record(USER_PROCESS, shell, "tty2", "bob", "", 1_700_000_100),
+            // The session of a process that is not running anymore
+            record(USER_PROCESS, 99999, "pts/1", "carol", "10.0.0.1", 1_700_000_300),
+        ].concat();
+        fs::create_dir_all(tree.root().join("var/run")).unwrap();
+        // A truncated record at the end is ignored
+        fs::write(tree.root().join("var/run/utmp"), [utmp, vec![0; 10]].concat()).unwrap();
+
+        let sessions = users().unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].user, "bob");
+        assert_eq!(sessions[0].terminal.as_deref(), Some("tty2"));
+        assert_eq!(sessions[0].remote_host, None);
+        assert_eq!(sessions[0].login_time, 1_700_000_100);
+        assert_eq!(sessions[0].pid, Some(shell));
+        assert_eq!(sessions[1].user, "alice");
+        assert_eq!(sessions[1].remote_host.as_deref(), Some("192.168.1.10"));
+    }
+
+    #[test]
+    fn logind_sessions_without_utmp() {
+        let tree = FakeTree::new(1).unwrap();
+        tree.write("/run/systemd/sessions/3", "UID=1000\nUSER=alice\nACTIVE=1\nSTATE=active\nCLASS=user\n\
+            TTY=tty2\nLEADER=1234\nREALTIME=1700000000123456\n").unwrap();
+        tree.write("/run/systemd/sessions/c1", "UID=120\nUSER=gdm\nSTATE=online\nCLASS=greeter\n").unwrap();
+        tree.write("/run/systemd/sessions/4", "USER=bob\nSTATE=closing\nCLASS=user\n").unwrap();
+
+        let sessions = users().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].user, "alice");
+        assert_eq!(sessions[0].terminal.as_deref(), Some("tty2"));
+        assert_eq!(sessions[0].login_time, 1_700_000_000);
+        assert_eq!(sessions[0].pid, Some(1234));
+    }
+}
@@ -0,0 +1,135 @@
+use std::fs;
+#[cfg(feature = "vaapi")]
+use std::collections::HashMap;
+#[cfg(feature = "vaapi")]
+use std::sync::Mutex;
+use crate::model::VaapiInfo;
+use crate::sensors::{class_entries, read_text};
+use crate::host;
+
+const DRM_PATH: &str = "/sys/class/drm";
+
+/// Reads a sysfs hexadecimal value like 0x8086
+fn read_id(path: &std::path::Path) -> Option<u16> {
+    u16::from_str_radix(read_text(path)?.trim_start_matches("0x"), 16).ok()
+}
+
+/// VA-API driver and profiles by render node. Initializing the driver is slow and they do not change while the
+/// node exists, so every node is only queried once
+#[cfg(feature = "vaapi")]
+pub type DriverCache = Mutex<HashMap<String, Option<(String, Vec<String>)>>>;
+
+/// Render nodes of the graphic cards with their kernel driver. With the `vaapi` feature libva is loaded to get
+/// the VA-API driver and the supported profiles
+pub fn vaapi_devices(#[cfg(feature = "vaapi")] drivers: &DriverCache) -> Vec<VaapiInfo> {
+    let nodes = match class_entries(DRM_PATH, "renderD") {
+        Ok(nodes) => nodes,
+        Err(_) => return vec![]
+    };
+    nodes.into_iter()
+        .filter_map(|(name, dir)| {
+            let path = format!("/dev/dri/{}", name);
+            if !host::path(&path).exists() {
+                return None;
+            }
+            #[cfg(feature = "vaapi")]
+            let (driver, profiles) = match drivers.lock().unwrap_or_else(|e| e.into_inner())
+                .entry(path.clone())
+                .or_insert_with(|| libva::query(&host::path(&path)))
+                .clone() {
+                Some((driver, profiles)) => (Some(driver), profiles),
+                None => (None, vec![])
+            };
+            #[cfg(not(feature = "vaapi"))]
+            let (driver, profiles) = (None, vec![]);
+            let device = dir.join("device");
+            Some(VaapiInfo {
+                path,
+                kernel_driver: fs::read_link(device.join("driver")).ok()
+                    .and_then(|driver| driver.file_name().map(|name| name.to_string_lossy().to_string())),
+                vendor_id: read_id(&device.join("vendor")),
+                device_id: read_id(&device.join("device")),
+                driver,
+                profiles,
+            })
+        })
+        .collect()
+}
+
+/// libva loaded at runtime, so the library does not need it to build or run
+#[cfg(feature = "vaapi")]
+mod libva {
+    use log::debug;
+    use std::ffi::{CStr, c_char, c_int, c_void};
+    use std::fs::File;
+    use std::os::fd::AsRawFd;
+    use std::path::Path;
+    use crate::dl::Library;
+
+    type Display = *mut c_void;
+    type GetDisplayDrm = unsafe extern "C" fn(c_int) -> Display;
+    type Initialize = unsafe extern "C" fn(Display, *mut c_int, *mut c_int) -> c_int;
+    type Terminate = unsafe extern "C" fn(Display) -> c_int;
+    type QueryVendorString = unsafe extern "C" fn(Display) -> *const c_char;
+    type MaxNumProfiles = unsafe extern "C" fn(Display) -> c_int;
+    type QueryConfigProfiles = unsafe extern "C" fn(Display, *mut c_int, *mut c_int) -> c_int;
+    type SetCallback = unsafe extern "C" fn(Display, *const c_void, *mut c_void) -> *const c_void;
+
+    /// VAProfile names (va.h), from 0
+    const PROFILES: [&str; 39] = [
+        "MPEG2Simple", "MPEG2Main", "MPEG4Simple", "MPEG4AdvancedSimple", "MPEG4Main", "H264Baseline",
+        "H264Main", "H264High", "VC1Simple", "VC1Main", "VC1Advanced", "H263Baseline", "JPEGBaseline",
+        "H264ConstrainedBaseline", "VP8Version0_3", "H264MultiviewHigh", "H264StereoHigh", "HEVCMain",
+        "HEVCMain10", "VP9Profile0", "VP9Profile1", "VP9Profile2", "VP9Profile3", "HEVCMain12",
+        "HEVCMain422_10", "HEVCMain422_12", "HEVCMain444", "HEVCMain444_10", "HEVCMain444_12", "HEVCSccMain",
+        "HEVCSccMain10", "HEVCSccMain444", "AV1Profile0", "AV1Profile1", "HEVCSccMain444_10", "Protected",
+        "H264High10", "VVCMain10", "VVCMultilayerMain10",
+    ];
+
+    /// VA-API driver vendor string and supported profiles of a render node
+    pub fn query(node: &Path) -> Option<(String, Vec<String>)> {
+        let va = Library::open("libva.so.2").map_err(|e| debug!("{}", e)).ok()?;
+        let va_drm = Library::open("libva-drm.so.2").map_err(|e| debug!("{}", e)).ok()?;
+        let file = File::open(node).map_err(|e| debug!("Cannot open {:?}: {}", node, e)).ok()?;
+        unsafe {
+            let get_display: GetDisplayDrm = va_drm.function("vaGetDisplayDRM")?;
+            let initialize: Initialize = va.function("vaInitialize")?;
+            let terminate: Terminate = va.function("vaTerminate")?;
+            let vendor_string: QueryVendorString = va.function("vaQueryVendorString")?;
+            let max_profiles: MaxNumProfiles = va.function("vaMaxNumProfiles")?;
+            let query_profiles: QueryConfigProfiles = va.function("vaQueryConfigProfiles")?;
+
+            let display = get_display(file.as_raw_fd());
+            if display.is_null() {
+                return None;
+            }
+            // Without callbacks libva prints its info messages to stderr
+            if let Some(set_info_callback) = va.function::<SetCallback>("vaSetInfoCallback") {
+                set_info_callback(display, std::ptr::null(), std::ptr::null_mut());
+            }
+            let (mut major, mut minor) = (0, 0);
+            if initialize(display, &mut major, &mut minor) != 0 {
+                debug!("Cannot initialize VA-API on {:?}", node);
+                terminate(display);
+                return None;
+            }
+            let vendor = vendor_string(display);
+            let driver = if vendor.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(vendor).to_string_lossy().to_string()
+            };
+            let mut profiles = vec![0 as c_int; max_profiles(display).max(0) as usize];
+            let mut count = 0;
+            if query_profiles(display, profiles.as_mut_ptr(), &mut count) != 0 {
+                count = 0;
+            }
+            profiles.truncate(count.max(0) as usize);
+            terminate(display);
+            Some((driver, profiles.into_iter()
+                .map(|profile| PROFILES.get(profile as usize).map(|name| name.to_string())
+                    .unwrap_or_else(|| format!("Profile{}", profile)))
+                .collect()))
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use crate::model::Virtualization;
+use crate::sensors::read_text;
+use crate::dmi;
+use crate::host;
+
+/// Container from the files left by the runtimes or the init system
+fn container() -> Option<Virtualization> {
+    if host::path("/.dockerenv").exists() {
+        return Some(Virtualization::Docker);
+    }
+    if host::path("/run/.containerenv").exists() {
+        return Some(Virtualization::Podman);
+    }
+    // Written by systemd from the container environment variable of the init process
+    let name = read_text(&host::path("/run/systemd/container"))
+        .or_else(|| {
+            let environ = fs::read(host::path("/proc/1/environ")).ok()?;
+            environ.split(|c| *c == 0)
+                .find_map(|variable| variable.strip_prefix(b"container="))
+                .map(|value| String::from_utf8_lossy(value).to_string())
+        });
+    if let Some(name) = name {
+        return Some(match name.as_str() {
+            "docker" => Virtualization::Docker,
+            "podman" => Virtualization::Podman,
+            "lxc" | "lxc-libvirt" => Virtualization::Lxc,
+            _ => Virtualization::OtherContainer
+        });
+    }
+    // Without systemd, the cgroup of the init process tells the runtime
+    let cgroup = fs::read_to_string(host::path("/proc/1/cgroup")).unwrap_or_default();
+    if cgroup.contains("/docker") {
+        Some(Virtualization::Docker)
+    } else if cgroup.contains("/lxc") {
+        Some(Virtualization::Lxc)
+    } else {
+        None
+    }
+}
+
+/// Virtual machine from the DMI vendor strings, the Xen hypervisor interface and the CPU flags
+fn virtual_machine() -> Option<Virtualization> {
+    let vendors = [dmi::attribute("sys_vendor"), dmi::attribute("product_name"), dmi::attribute("bios_vendor"), dmi::attribute("board_vendor")];
+    for vendor in vendors.iter().flatten() {
+        let found = match vendor.as_str() {
+            vendor if vendor.starts_with("KVM") || vendor.starts_with("Amazon EC2") || vendor == "Google" => Virtualization::Kvm,
+            vendor if vendor.starts_with("QEMU") => Virtualization::Qemu,
+            vendor if vendor.starts_with("VMware") => Virtualization::Vmware,
+            vendor if vendor.starts_with("innotek GmbH") || vendor.starts_with("VirtualBox") => Virtualization::VirtualBox,
+            vendor if vendor.starts_with("Xen") => Virtualization::Xen,
+            // Oracle also makes bare metal servers, only its VirtualBox product is a virtual machine
+            "Oracle Corporation" if dmi::attribute("product_name").as_deref() == Some("VirtualBox") => Virtualization::VirtualBox,
+            "Microsoft Corporation" if dmi::attribute("product_name").as_deref() == Some("Virtual Machine") => Virtualization::HyperV,
+            _ => continue
+        };
+        return Some(found);
+    }
+    if read_text(&host::path("/sys/hypervisor/type")).as_deref() == Some("xen") {
+        return Some(Virtualization::Xen);
+    }
+    let cpuinfo = fs::read_to_string(host::path("/proc/cpuinfo")).unwrap_or_default();
+    let hypervisor = cpuinfo.lines()
+        .filter(|line| line.starts_with("flags"))
+        .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"));
+    hypervisor.then_some(Virtualization::OtherVm)
+}
+
+/// Virtualization technology of the system
+pub fn virtualization() -> Virtualization {
+    if let Some(container) = container() {
+        return container;
+    }
+    // WSL has no DMI and reports a Microsoft kernel
+    let kernel = read_text(&host::path("/proc/sys/kernel/osrelease")).unwrap_or_default();
+    if kernel.contains("microsoft") || kernel.contains("Microsoft") || kernel.contains("WSL") {
+        return Virtualization::Wsl;
+    }
+    virtual_machine().unwrap_or(Virtualization::BareMetal)
+}
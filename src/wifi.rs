@@ -0,0 +1,313 @@
+//! Wireless link information using nl80211 (generic netlink)
+use anyhow::Result;
+#[cfg(target_os = "linux")]
+use log::debug;
+use crate::model::WifiLink;
+#[cfg(target_os = "linux")]
+use crate::host;
+#[cfg(target_os = "linux")]
+use crate::network::interfaces;
+
+/// Interfaces with wireless extensions
+#[cfg(target_os = "linux")]
+fn wireless_interfaces() -> Result<Vec<String>> {
+    Ok(interfaces()?
+        .into_iter()
+        .filter(|name| {
+            let path = host::path(&format!("/sys/class/net/{}", name));
+            path.join("wireless").exists() || path.join("phy80211").exists()
+        })
+        .collect())
+}
+
+/// Wi-Fi channel of a frequency in MHz
+#[cfg(target_os = "linux")]
+fn channel(frequency: u32) -> Option<u32> {
+    match frequency {
+        2484 => Some(14),
+        2412..=2472 => Some((frequency - 2407) / 5),
+        5955..=7115 => Some((frequency - 5950) / 5),
+        5000..=5900 => Some((frequency - 5000) / 5),
+        _ => None
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod netlink {
+    use anyhow::Result;
+
+    const NETLINK_GENERIC: i32 = 16;
+    const NLM_F_REQUEST: u16 = 0x1;
+    const NLM_F_DUMP: u16 = 0x300;
+    const NLMSG_ERROR: u16 = 2;
+    const NLMSG_DONE: u16 = 3;
+    const GENL_ID_CTRL: u16 = 0x10;
+    const CTRL_CMD_GETFAMILY: u8 = 3;
+    const CTRL_ATTR_FAMILY_ID: u16 = 1;
+    const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+    pub const NL80211_CMD_GET_INTERFACE: u8 = 5;
+    pub const NL80211_CMD_GET_STATION: u8 = 17;
+    pub const NL80211_ATTR_IFINDEX: u16 = 3;
+    pub const NL80211_ATTR_STA_INFO: u16 = 21;
+    pub const NL80211_ATTR_WIPHY_FREQ: u16 = 38;
+    pub const NL80211_ATTR_SSID: u16 = 52;
+    pub const NL80211_STA_INFO_SIGNAL: u16 = 7;
+    pub const NL80211_STA_INFO_TX_BITRATE: u16 = 8;
+    pub const NL80211_RATE_INFO_BITRATE: u16 = 1;
+    pub const NL80211_RATE_INFO_BITRATE32: u16 = 5;
+    // Header sizes: nlmsghdr and genlmsghdr
+    const HEADER: usize = 16;
+    const GENL_HEADER: usize = 4;
+
+    fn align(len: usize) -> usize {
+        (len + 3) & !3
+    }
+
+    /// Attributes of a message or a nested attribute as (type, payload)
+    pub fn attributes(mut data: &[u8]) -> Vec<(u16, &[u8])> {
+        let mut result = Vec::new();
+        while data.len() >= 4 {
+            let len = u16::from_ne_bytes([data[0], data[1]]) as usize;
+            // The upper bits are flags (nested, byte order)
+            let kind = u16::from_ne_bytes([data[2], data[3]]) & 0x3FFF;
+            if len < 4 || len > data.len() {
+                break;
+            }
+            result.push((kind, &data[4..len]));
+            data = &data[align(len).min(data.len())..];
+        }
+        result
+    }
+
+    pub fn attribute<'a>(attributes: &[(u16, &'a [u8])], kind: u16) -> Option<&'a [u8]> {
+        attributes.iter().find(|(k, _)| *k == kind).map(|(_, payload)| *payload)
+    }
+
+    pub struct Socket {
+        fd: i32,
+        seq: u32,
+    }
+
+    impl Socket {
+        pub fn new() -> Result<Socket> {
+            let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, NETLINK_GENERIC) };
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            Ok(Socket { fd, seq: 0 })
+        }
+
+        /// Sends a generic netlink request and returns the payload of every answer
+        pub fn request(&mut self, family: u16, command: u8, dump: bool, attributes: &[(u16, Vec<u8>)]) -> Result<Vec<Vec<u8>>> {
+            self.seq += 1;
+            let mut message = vec![0u8; HEADER + GENL_HEADER];
+            for (kind, payload) in attributes {
+                let len = 4 + payload.len();
+                message.extend_from_slice(&(len as u16).to_ne_bytes());
+                message.extend_from_slice(&kind.to_ne_bytes());
+                message.extend_from_slice(payload);
+                message.resize(align(message.len()), 0);
+            }
+            let flags = NLM_F_REQUEST | if dump { NLM_F_DUMP } else { 0 };
+            let len = message.len() as u32;
+            message[0..4].copy_from_slice(&len.to_ne_bytes());
+            message[4..6].copy_from_slice(&family.to_ne_bytes());
+            message[6..8].copy_from_slice(&flags.to_ne_bytes());
+            message[8..12].copy_from_slice(&self.seq.to_ne_bytes());
+            message[HEADER] = command;
+            message[HEADER + 1] = 1;
+
+            let sent = unsafe { libc::send(self.fd, message.as_ptr() as *const libc::c_void, message.len(), 0) };
+            if sent < 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+
+            let mut answers = Vec::new();
+            let mut buffer = vec![0u8; 32768];
+            loop {
+                let received = unsafe { libc::recv(self.fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len(), 0) };
+                if received < 0 {
+                    return Err(std::io::Error::last_os_error().into());
+                }
+                let mut data = &buffer[..received as usize];
+                while data.len() >= HEADER {
+                    let len = u32::from_ne_bytes([data[0], data[1], data[2], data[3]]) as usize;
+                    let kind = u16::from_ne_bytes([data[4], data[5]]);
+                    if len < HEADER || len > data.len() {
+                        return Err(anyhow::anyhow!("Invalid netlink message"));
+                    }
+                    match kind {
+                        NLMSG_DONE => return Ok(answers),
+                        NLMSG_ERROR => {
+                            let code = i32::from_ne_bytes([data[16], data[17], data[18], data[19]]);
+                            if code == 0 {
+                                return Ok(answers);
+                            }
+                            return Err(std::io::Error::from_raw_os_error(-code).into());
+                        },
+                        _ => answers.push(data[HEADER + GENL_HEADER..len].to_vec())
+                    }
+                    data = &data[align(len).min(data.len())..];
+                }
+                if !dump {
+                    return Ok(answers);
+                }
+            }
+        }
+
+        /// Id of a generic netlink family like nl80211
+        pub fn family(&mut self, name: &str) -> Result<u16> {
+            let mut payload = name.as_bytes().to_vec();
+            payload.push(0);
+            let answers = self.request(GENL_ID_CTRL, CTRL_CMD_GETFAMILY, false, &[(CTRL_ATTR_FAMILY_NAME, payload)])?;
+            answers.iter()
+                .find_map(|answer| attribute(&attributes(answer), CTRL_ATTR_FAMILY_ID)
+                    .filter(|id| id.len() >= 2)
+                    .map(|id| u16::from_ne_bytes([id[0], id[1]])))
+                .ok_or_else(|| anyhow::anyhow!("Netlink family {} not found", name))
+        }
+    }
+
+    impl Drop for Socket {
+        fn drop(&mut self) {
+            unsafe { libc::close(self.fd) };
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn link(socket: &mut netlink::Socket, family: u16, interface: &str) -> Result<WifiLink> {
+    use netlink::*;
+
+    let index = unsafe { libc::if_nametoindex(std::ffi::CString::new(interface)?.as_ptr()) };
+    if index == 0 {
+        return Err(anyhow::anyhow!("Interface {} not found", interface));
+    }
+    let index_attribute = vec![(NL80211_ATTR_IFINDEX, index.to_ne_bytes().to_vec())];
+    let mut link = WifiLink {
+        interface: interface.to_string(),
+        ssid: None,
+        signal: None,
+        frequency: None,
+        channel: None,
+        bitrate: None,
+    };
+
+    for answer in socket.request(family, NL80211_CMD_GET_INTERFACE, false, &index_attribute)? {
+        let attrs = attributes(&answer);
+        if let Some(ssid) = attribute(&attrs, NL80211_ATTR_SSID) {
+            link.ssid = Some(String::from_utf8_lossy(ssid).to_string());
+        }
+        if let Some(frequency) = attribute(&attrs, NL80211_ATTR_WIPHY_FREQ).filter(|f| f.len() >= 4) {
+            let frequency = u32::from_ne_bytes([frequency[0], frequency[1], frequency[2], frequency[3]]);
+            link.frequency = Some(frequency);
+            link.channel = channel(frequency);
+        }
+    }
+
+    // In station mode there is only one station: the access point
+    for answer in socket.request(family, NL80211_CMD_GET_STATION, true, &index_attribute)? {
+        let attrs = attributes(&answer);
+        let info = match attribute(&attrs, NL80211_ATTR_STA_INFO) {
+            Some(info) => attributes(info),
+            None => continue
+        };
+        if let Some(signal) = attribute(&info, NL80211_STA_INFO_SIGNAL).and_then(|s| s.first()) {
+            link.signal = Some(*signal as i8 as i32);
+        }
+        if let Some(rate) = attribute(&info, NL80211_STA_INFO_TX_BITRATE) {
+            let rate = attributes(rate);
+            // Bitrates are in units of 100kbit/s
+            let bitrate = attribute(&rate, NL80211_RATE_INFO_BITRATE32)
+                .filter(|b| b.len() >= 4)
+                .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+                .or_else(|| attribute(&rate, NL80211_RATE_INFO_BITRATE)
+                    .filter(|b| b.len() >= 2)
+                    .map(|b| u16::from_ne_bytes([b[0], b[1]]) as u32));
+            link.bitrate = bitrate.map(|b| b as f64 / 10.0);
+        }
+    }
+    Ok(link)
+}
+
+#[cfg(target_os = "linux")]
+pub fn wifi_links() -> Result<Vec<WifiLink>> {
+    let interfaces = wireless_interfaces()?;
+    if interfaces.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut socket = netlink::Socket::new()?;
+    let family = socket.family("nl80211")?;
+    let mut links = Vec::new();
+    for interface in interfaces {
+        match link(&mut socket, family, &interface) {
+            Ok(link) => links.push(link),
+            Err(e) => debug!("Cannot get Wi-Fi link of {}: {}", interface, e)
+        }
+    }
+    Ok(links)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn wifi_links() -> Result<Vec<WifiLink>> {
+    Err(anyhow::anyhow!("Wi-Fi information is only available on Linux"))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use super::netlink::*;
+
+    /// Attribute with its header and the padding to 4 bytes
+    fn encode(kind: u16, payload: &[u8]) -> Vec<u8> {
+        let len = 4 + payload.len() as u16;
+        let mut data = [len.to_ne_bytes(), kind.to_ne_bytes()].concat();
+        data.extend_from_slice(payload);
+        data.resize(data.len().div_ceil(4) * 4, 0);
+        data
+    }
+
+    #[test]
+    fn station_info_attributes() {
+        // Nested attributes have the NLA_F_NESTED flag in the type
+        let rate = encode(NL80211_RATE_INFO_BITRATE32, &8667u32.to_ne_bytes());
+        let info = [
+            encode(NL80211_STA_INFO_SIGNAL, &[-54i8 as u8]),
+            encode(NL80211_STA_INFO_TX_BITRATE | 0x8000, &rate),
+        ].concat();
+        let message = [
+            encode(NL80211_ATTR_IFINDEX, &3u32.to_ne_bytes()),
+            encode(NL80211_ATTR_SSID, b"home"),
+            encode(NL80211_ATTR_STA_INFO | 0x8000, &info),
+        ].concat();
+
+        let attrs = attributes(&message);
+        assert_eq!(attrs.iter().map(|(kind, _)| *kind).collect::<Vec<u16>>(),
+            vec![NL80211_ATTR_IFINDEX, NL80211_ATTR_SSID, NL80211_ATTR_STA_INFO]);
+        assert_eq!(attribute(&attrs, NL80211_ATTR_SSID), Some(&b"home"[..]));
+        let info = attributes(attribute(&attrs, NL80211_ATTR_STA_INFO).unwrap());
+        assert_eq!(attribute(&info, NL80211_STA_INFO_SIGNAL), Some(&[-54i8 as u8][..]));
+        let rate = attributes(attribute(&info, NL80211_STA_INFO_TX_BITRATE).unwrap());
+        assert_eq!(attribute(&rate, NL80211_RATE_INFO_BITRATE32), Some(&8667u32.to_ne_bytes()[..]));
+    }
+
+    #[test]
+    fn truncated_attributes() {
+        let mut message = [encode(NL80211_ATTR_SSID, b"cafe wifi"), encode(NL80211_ATTR_IFINDEX, &3u32.to_ne_bytes())].concat();
+        // The length of the last attribute goes past the end of the message
+        message.truncate(message.len() - 2);
+        let attrs = attributes(&message);
+        assert_eq!(attrs.len(), 1);
+        assert_eq!(attrs[0], (NL80211_ATTR_SSID, &b"cafe wifi"[..]));
+        assert!(attributes(&[8, 0]).is_empty());
+    }
+
+    #[test]
+    fn channels() {
+        assert_eq!(channel(2412), Some(1));
+        assert_eq!(channel(2484), Some(14));
+        assert_eq!(channel(5180), Some(36));
+        assert_eq!(channel(5955), Some(1));
+        assert_eq!(channel(60480), None);
+    }
+}
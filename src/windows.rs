@@ -0,0 +1,118 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::process::Command;
+use log::debug;
+use crate::model::{HardwareIdentity, FirmwareInfo, Baseboard};
+use crate::dmi::PLACEHOLDERS;
+
+// Windows copies the SMBIOS strings to the registry at boot, so they can be read without WMI
+const BIOS_KEY: &str = r"HKLM\HARDWARE\DESCRIPTION\System\BIOS";
+const SECURE_BOOT_KEY: &str = r"HKLM\SYSTEM\CurrentControlSet\Control\SecureBoot\State";
+// Status numbers are not localized, unlike the output of manage-bde
+const BITLOCKER_QUERY: &str = "Get-CimInstance -Namespace root/cimv2/security/MicrosoftVolumeEncryption \
+    -ClassName Win32_EncryptableVolume | ForEach-Object { \"$($_.DriveLetter) $($_.ProtectionStatus) $($_.ConversionStatus)\" }";
+const NVSMI_NVML: &str = r"C:\Program Files\NVIDIA Corporation\NVSMI\nvml.dll";
+
+/// Values of a registry key, from the output of a single `reg query`. DWORD values are returned in hexadecimal
+/// like 0x1
+fn registry(key: &str) -> HashMap<String, String> {
+    let output = match Command::new("reg").args(["query", key]).output() {
+        Ok(output) if output.status.success() => output,
+        Ok(_) => return HashMap::new(),
+        Err(e) => {
+            debug!("Cannot run reg query: {}", e);
+            return HashMap::new();
+        }
+    };
+    // Lines are like "    SystemProductName    REG_SZ    Precision 5550"
+    String::from_utf8_lossy(&output.stdout).lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?;
+            if !fields.next()?.starts_with("REG_") {
+                return None;
+            }
+            Some((name.to_string(), fields.collect::<Vec<&str>>().join(" ")))
+        })
+        .filter(|(_, value)| !value.is_empty() && !PLACEHOLDERS.contains(&value.as_str()))
+        .collect()
+}
+
+/// Manufacturer and product name, the equivalent of the device tree model of ARM boards
+pub fn model() -> Option<String> {
+    let mut bios = registry(BIOS_KEY);
+    match (bios.remove("SystemManufacturer"), bios.remove("SystemProductName")) {
+        (Some(manufacturer), Some(product)) => Some(format!("{} {}", manufacturer, product)),
+        (manufacturer, product) => product.or(manufacturer)
+    }
+}
+
+/// Hardware identity from the SMBIOS values in the registry. Serials, UUID and chassis type are not copied there
+pub fn hardware_identity() -> Option<HardwareIdentity> {
+    let mut bios = registry(BIOS_KEY);
+    let mut bios = |name: &str| bios.remove(name);
+    let vendor = bios("SystemManufacturer");
+    let product_name = bios("SystemProductName");
+    if vendor.is_none() && product_name.is_none() {
+        return None;
+    }
+    // The Secure Boot state only exists on UEFI firmware
+    let secure_boot = registry(SECURE_BOOT_KEY).get("UEFISecureBootEnabled").map(|value| value == "0x1");
+    let baseboard = Baseboard {
+        manufacturer: bios("BaseBoardManufacturer"),
+        product: bios("BaseBoardProduct"),
+        version: bios("BaseBoardVersion"),
+        serial: None,
+    };
+    Some(HardwareIdentity {
+        vendor,
+        product_name,
+        product_version: bios("SystemVersion"),
+        chassis_type: None,
+        serial: None,
+        uuid: None,
+        firmware: FirmwareInfo {
+            vendor: bios("BIOSVendor"),
+            version: bios("BIOSVersion"),
+            release_date: bios("BIOSReleaseDate"),
+            uefi: secure_boot.is_some(),
+            secure_boot: secure_boot.or(Some(false)),
+        },
+        baseboard: Some(baseboard).filter(|baseboard| baseboard.manufacturer.is_some() || baseboard.product.is_some()),
+    })
+}
+
+/// nvml.dll of older drivers, installed out of the PATH. Recent drivers put it in System32
+pub fn nvml_library() -> Option<PathBuf> {
+    let path = PathBuf::from(NVSMI_NVML);
+    if path.exists() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Drive letters (like C:) of the volumes encrypted with BitLocker, from the ProtectionStatus and ConversionStatus
+/// of Win32_EncryptableVolume, asked once for all the volumes. It requires administrator permissions, so it is
+/// empty when they cannot be queried
+pub fn bitlocker_volumes() -> HashSet<String> {
+    let output = match Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-Command", BITLOCKER_QUERY]).output() {
+        Ok(output) if output.status.success() => output,
+        Ok(_) => return HashSet::new(),
+        Err(e) => {
+            debug!("Cannot run powershell: {}", e);
+            return HashSet::new();
+        }
+    };
+    // Lines are like "C: 1 1". Volumes without drive letter have only the two numbers
+    String::from_utf8_lossy(&output.stdout).lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields[..] {
+                // Protection on, or encrypted but suspended (fully decrypted is conversion status 0)
+                [drive, protection, conversion] if protection == "1" || conversion != "0" => Some(drive.to_uppercase()),
+                _ => None
+            }
+        })
+        .collect()
+}